@@ -0,0 +1,322 @@
+use crate::client::{
+    execute_request_with, extract_json_path, load_config_presets, parse_duration, Config,
+    ResponseInfo, TimingInfo, QUIET_BODY,
+};
+use crate::observer::ResponseObserver;
+use crate::signer::default_signers;
+use crate::variables::resolve_variables;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// エラーメッセージ
+const ERROR_NO_ASSERTIONS: &str = "No presets with [preset.<name>.assert] found in {}";
+const ERROR_NO_RESPONSE: &str = "No response received";
+const ERROR_MISSING_HEADER: &str = "header is missing";
+const ERROR_UNEXPECTED_VALUE: &str = "expected {1}, got {2}";
+const ERROR_TOO_SLOW: &str = "took {1}, limit {2}";
+const ERROR_INVALID_JSON_RESPONSE: &str = "response is not JSON";
+
+// チェック名
+const CHECK_REQUEST: &str = "request";
+const CHECK_STATUS: &str = "status == {}";
+const CHECK_MAX_TIME: &str = "time <= {}";
+const CHECK_HEADER: &str = "header {1} == {2}";
+const CHECK_JSON: &str = "json {1} == {2}";
+
+// 表示メッセージ
+const PASS_MSG: &str = "PASS {1} ({2} assertions, {3})";
+const FAIL_MSG: &str = "FAIL {1} ({2} of {3} assertions failed, {4})";
+const CHECK_FAILURE_MSG: &str = "  - {1}: {2}";
+const TEST_TOTAL_MSG: &str = "Tests: {1} presets, {2} passed, {3} failed";
+
+/// プリセットに定義するアサーション（[preset.<name>.assert]）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssertConfig {
+    /// 期待するステータスコード
+    pub status: Option<u16>,
+    /// レスポンス受信完了までの上限（"500ms"、"2s" など）
+    pub max_time: Option<String>,
+    /// 期待するヘッダーの値（名前は大文字小文字を区別しない）
+    pub headers: Option<BTreeMap<String, String>>,
+    /// JSONパス（--json-filter と同じ記法）ごとの期待値
+    pub json: Option<BTreeMap<String, Value>>,
+}
+
+/// 1プリセット分のテスト結果
+#[derive(Debug, Clone)]
+pub struct PresetResult {
+    pub name: String,
+    pub checks: Vec<CheckResult>,
+    pub duration: Duration,
+}
+
+/// 1アサーション分の結果
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub failure: Option<String>,
+}
+
+impl PresetResult {
+    /// すべてのアサーションが成功したか
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.failure.is_none())
+    }
+
+    /// 失敗したアサーションの数
+    pub fn failed_count(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.failure.is_some())
+            .count()
+    }
+}
+
+/// アサーションの評価に使うレスポンス
+struct RecordedResponse {
+    url: String,
+    status: u16,
+    headers: HeaderMap,
+    total_time: Duration,
+    body: String,
+}
+
+/// 受信したレスポンスを記録するオブザーバー
+struct ResponseRecorder {
+    responses: Arc<Mutex<Vec<RecordedResponse>>>,
+}
+
+impl ResponseObserver for ResponseRecorder {
+    fn on_response(
+        &self,
+        _config: &Config,
+        response: &ResponseInfo,
+        timing: &TimingInfo,
+        body: &str,
+    ) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.push(RecordedResponse {
+                url: response.url().to_string(),
+                status: response.status().as_u16(),
+                headers: response.headers().clone(),
+                total_time: timing.total_time,
+                body: body.to_string(),
+            });
+        }
+    }
+}
+
+/// 設定ファイル内のアサーションを持つプリセットを名前順に実行
+pub fn run_preset_tests(
+    config_path: &str,
+    identity: Option<&str>,
+    environment: Option<&str>,
+) -> Result<Vec<PresetResult>, Box<dyn Error>> {
+    let results: Vec<PresetResult> = load_config_presets(config_path, identity, environment)?
+        .into_iter()
+        .filter(|(_, config)| config.assert.is_some())
+        .map(|(name, config)| run_preset_test(name, config))
+        .collect();
+
+    if results.is_empty() {
+        return Err(ERROR_NO_ASSERTIONS.replace("{}", config_path).into());
+    }
+
+    Ok(results)
+}
+
+/// テスト結果を表示
+pub fn display_test_results(results: &[PresetResult]) {
+    for result in results {
+        let duration = format!("{:.2?}", result.duration);
+        if result.passed() {
+            println!(
+                "{}",
+                PASS_MSG
+                    .replace("{1}", &result.name)
+                    .replace("{2}", &result.checks.len().to_string())
+                    .replace("{3}", &duration)
+            );
+            continue;
+        }
+
+        println!(
+            "{}",
+            FAIL_MSG
+                .replace("{1}", &result.name)
+                .replace("{2}", &result.failed_count().to_string())
+                .replace("{3}", &result.checks.len().to_string())
+                .replace("{4}", &duration)
+        );
+        for check in &result.checks {
+            if let Some(failure) = &check.failure {
+                println!(
+                    "{}",
+                    CHECK_FAILURE_MSG
+                        .replace("{1}", &check.name)
+                        .replace("{2}", failure)
+                );
+            }
+        }
+    }
+
+    let passed = results.iter().filter(|result| result.passed()).count();
+    println!(
+        "{}",
+        TEST_TOTAL_MSG
+            .replace("{1}", &results.len().to_string())
+            .replace("{2}", &passed.to_string())
+            .replace("{3}", &(results.len() - passed).to_string())
+    );
+}
+
+/// 1プリセットのリクエストを送信してアサーションを評価
+fn run_preset_test(name: String, mut config: Config) -> PresetResult {
+    let start = Instant::now();
+    let assert_config = config.assert.clone().unwrap_or_default();
+
+    // レスポンスは表示せず記録のみ行う
+    config.quiet = QUIET_BODY;
+    config.verbose = false;
+    config.timing = false;
+
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let recorder = ResponseRecorder {
+        responses: Arc::clone(&responses),
+    };
+    let outcome = resolve_variables(&mut config)
+        .and_then(|()| execute_request_with(config, default_signers(), vec![Box::new(recorder)]));
+
+    let responses = responses
+        .lock()
+        .map(|mut responses| std::mem::take(&mut *responses))
+        .unwrap_or_default();
+    let checks = match outcome {
+        Err(e) => vec![failed_check(CHECK_REQUEST.to_string(), e.to_string())],
+        Ok(()) if responses.is_empty() => {
+            vec![failed_check(
+                CHECK_REQUEST.to_string(),
+                ERROR_NO_RESPONSE.to_string(),
+            )]
+        }
+        Ok(()) => {
+            let labeled = responses.len() > 1;
+            responses
+                .iter()
+                .flat_map(|response| check_response(&assert_config, response, labeled))
+                .collect()
+        }
+    };
+
+    PresetResult {
+        name,
+        checks,
+        duration: start.elapsed(),
+    }
+}
+
+/// レスポンスに対してアサーションを評価（複数URLの場合はチェック名にURLを付与）
+fn check_response(
+    assert_config: &AssertConfig,
+    response: &RecordedResponse,
+    labeled: bool,
+) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+
+    if let Some(status) = assert_config.status {
+        checks.push(CheckResult {
+            name: CHECK_STATUS.replace("{}", &status.to_string()),
+            failure: (response.status != status)
+                .then(|| unexpected_value(&status.to_string(), &response.status.to_string())),
+        });
+    }
+
+    if let Some(max_time) = &assert_config.max_time {
+        checks.push(CheckResult {
+            name: CHECK_MAX_TIME.replace("{}", max_time),
+            failure: check_max_time(max_time, response.total_time),
+        });
+    }
+
+    for (header, expected) in assert_config.headers.iter().flatten() {
+        let actual = response
+            .headers
+            .get(header.as_str())
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+        checks.push(CheckResult {
+            name: CHECK_HEADER.replace("{1}", header).replace("{2}", expected),
+            failure: match actual {
+                Some(actual) if actual == *expected => None,
+                Some(actual) => Some(unexpected_value(expected, &actual)),
+                None => Some(ERROR_MISSING_HEADER.to_string()),
+            },
+        });
+    }
+
+    if let Some(json_assertions) = &assert_config.json {
+        let body = serde_json::from_str::<Value>(&response.body).ok();
+        for (path, expected) in json_assertions {
+            checks.push(CheckResult {
+                name: CHECK_JSON
+                    .replace("{1}", path)
+                    .replace("{2}", &expected.to_string()),
+                failure: check_json_path(body.as_ref(), path, expected),
+            });
+        }
+    }
+
+    if labeled {
+        for check in &mut checks {
+            check.name = format!("{}: {}", response.url, check.name);
+        }
+    }
+
+    checks
+}
+
+/// 所要時間が上限以内か
+fn check_max_time(max_time: &str, total_time: Duration) -> Option<String> {
+    match parse_duration(max_time) {
+        Ok(limit) if total_time <= limit => None,
+        Ok(_) => Some(
+            ERROR_TOO_SLOW
+                .replace("{1}", &format!("{:.2?}", total_time))
+                .replace("{2}", max_time),
+        ),
+        Err(e) => Some(e.to_string()),
+    }
+}
+
+/// JSONパスの値が期待値と一致するか
+fn check_json_path(body: Option<&Value>, path: &str, expected: &Value) -> Option<String> {
+    let body = match body {
+        Some(body) => body,
+        None => return Some(ERROR_INVALID_JSON_RESPONSE.to_string()),
+    };
+
+    match extract_json_path(body.clone(), path) {
+        Ok(actual) if actual == *expected => None,
+        Ok(actual) => Some(unexpected_value(&expected.to_string(), &actual.to_string())),
+        Err(e) => Some(e.to_string()),
+    }
+}
+
+/// 失敗したチェック
+fn failed_check(name: String, failure: String) -> CheckResult {
+    CheckResult {
+        name,
+        failure: Some(failure),
+    }
+}
+
+/// 期待値と実際の値の不一致メッセージ
+fn unexpected_value(expected: &str, actual: &str) -> String {
+    ERROR_UNEXPECTED_VALUE
+        .replace("{1}", expected)
+        .replace("{2}", actual)
+}
@@ -0,0 +1,260 @@
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{
+    CONTENT_SECURITY_POLICY, REFERRER_POLICY, SET_COOKIE, STRICT_TRANSPORT_SECURITY,
+    X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+};
+use std::error::Error;
+
+// 既定の合格点
+pub const DEFAULT_AUDIT_MIN_SCORE: u8 = 80;
+
+// 判定に使う値
+const HTTPS_SCHEME: &str = "https";
+const HSTS_MAX_AGE: &str = "max-age=";
+const HSTS_MIN_MAX_AGE_SECS: u64 = 15_552_000; // 180日
+const NOSNIFF: &str = "nosniff";
+const FRAME_OPTIONS_ALLOWED: [&str; 2] = ["deny", "sameorigin"];
+const CSP_FRAME_ANCESTORS: &str = "frame-ancestors";
+const UNSAFE_REFERRER_POLICIES: [&str; 2] = ["unsafe-url", "no-referrer-when-downgrade"];
+const COOKIE_SECURE: &str = "secure";
+const COOKIE_HTTP_ONLY: &str = "httponly";
+const COOKIE_SAME_SITE: &str = "samesite";
+const COOKIE_SAME_SITE_NONE: &str = "none";
+
+// エラーメッセージ
+const ERROR_INVALID_MIN_SCORE: &str = "--min-score must be between 0 and 100.";
+const ERROR_AUDIT_FAILED: &str = "Security headers score for {1} is {2}, below --min-score {3}";
+
+// 判定理由
+const REASON_MISSING: &str = "missing";
+const REASON_NOT_HTTPS: &str = "not served over HTTPS";
+const REASON_SHORT_MAX_AGE: &str = "max-age is shorter than 180 days";
+const REASON_UNEXPECTED_VALUE: &str = "unexpected value";
+const REASON_UNSAFE_POLICY: &str = "leaks the full URL to other origins";
+const REASON_COOKIE_FLAGS: &str = "missing {}";
+
+// 表示メッセージ
+const AUDIT_HEADER: &str = "--- Security Headers Audit ---";
+const AUDIT_REQUEST_MSG: &str = "GET {1} ({2})";
+const CHECK_PASS_MSG: &str = "PASS {1}: {2}";
+const CHECK_FAIL_MSG: &str = "FAIL {1}: {2} ({3})";
+const COOKIE_LABEL: &str = "Cookie {}";
+const MISSING_HEADER_MSG: &str = "<missing>";
+const FRAME_ANCESTORS_MSG: &str = "<covered by CSP frame-ancestors>";
+const SCORE_MSG: &str = "Score: {1}/100 ({2} of {3} checks passed, threshold {4})";
+
+/// 1項目の判定結果（失敗時は理由を持つ）
+struct AuditCheck {
+    label: String,
+    value: String,
+    failure: Option<String>,
+}
+
+/// レスポンスのセキュリティ関連ヘッダーを採点し、合格点未満の場合はエラー
+pub fn audit_security_headers(client: &Client, url: &str, min_score: u8) -> Result<(), Box<dyn Error>> {
+    if min_score > 100 {
+        return Err(ERROR_INVALID_MIN_SCORE.into());
+    }
+
+    let response = client.get(url).send()?;
+
+    let checks = evaluate_headers(&response);
+    let passed = checks
+        .iter()
+        .filter(|check| check.failure.is_none())
+        .count();
+    let score = (passed * 100 / checks.len()) as u8;
+
+    println!("{}", AUDIT_HEADER);
+    println!(
+        "{}",
+        AUDIT_REQUEST_MSG
+            .replace("{1}", response.url().as_str())
+            .replace("{2}", &response.status().to_string())
+    );
+    for check in &checks {
+        let line = match &check.failure {
+            None => CHECK_PASS_MSG
+                .replace("{1}", &check.label)
+                .replace("{2}", &check.value),
+            Some(reason) => CHECK_FAIL_MSG
+                .replace("{1}", &check.label)
+                .replace("{2}", &check.value)
+                .replace("{3}", reason),
+        };
+        println!("{}", line);
+    }
+    println!(
+        "{}",
+        SCORE_MSG
+            .replace("{1}", &score.to_string())
+            .replace("{2}", &passed.to_string())
+            .replace("{3}", &checks.len().to_string())
+            .replace("{4}", &min_score.to_string())
+    );
+
+    if score < min_score {
+        return Err(ERROR_AUDIT_FAILED
+            .replace("{1}", url)
+            .replace("{2}", &score.to_string())
+            .replace("{3}", &min_score.to_string())
+            .into());
+    }
+
+    Ok(())
+}
+
+/// レスポンスヘッダーを項目ごとに判定
+fn evaluate_headers(response: &Response) -> Vec<AuditCheck> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let check = |name: &reqwest::header::HeaderName, value: Option<&str>, failure| AuditCheck {
+        label: name.to_string(),
+        value: value.unwrap_or(MISSING_HEADER_MSG).to_string(),
+        failure,
+    };
+    let mut checks = Vec::new();
+
+    // Strict-Transport-Security（HTTPSでのみ有効）
+    let hsts = header(STRICT_TRANSPORT_SECURITY);
+    let hsts_failure = if response.url().scheme() != HTTPS_SCHEME {
+        Some(REASON_NOT_HTTPS)
+    } else {
+        match hsts.map(hsts_max_age) {
+            None => Some(REASON_MISSING),
+            Some(Some(max_age)) if max_age >= HSTS_MIN_MAX_AGE_SECS => None,
+            Some(_) => Some(REASON_SHORT_MAX_AGE),
+        }
+    };
+    checks.push(check(
+        &STRICT_TRANSPORT_SECURITY,
+        hsts,
+        hsts_failure.map(str::to_string),
+    ));
+
+    let csp = header(CONTENT_SECURITY_POLICY);
+    checks.push(check(
+        &CONTENT_SECURITY_POLICY,
+        csp,
+        csp.is_none().then(|| REASON_MISSING.to_string()),
+    ));
+
+    let content_type_options = header(X_CONTENT_TYPE_OPTIONS);
+    checks.push(check(
+        &X_CONTENT_TYPE_OPTIONS,
+        content_type_options,
+        match content_type_options {
+            None => Some(REASON_MISSING.to_string()),
+            Some(value) if value.eq_ignore_ascii_case(NOSNIFF) => None,
+            Some(_) => Some(REASON_UNEXPECTED_VALUE.to_string()),
+        },
+    ));
+
+    // CSPの frame-ancestors でも代用できる
+    let frame_options = header(X_FRAME_OPTIONS);
+    let frame_ancestors =
+        csp.is_some_and(|csp| csp.to_ascii_lowercase().contains(CSP_FRAME_ANCESTORS));
+    checks.push(check(
+        &X_FRAME_OPTIONS,
+        frame_options.or(frame_ancestors.then_some(FRAME_ANCESTORS_MSG)),
+        match frame_options {
+            None if frame_ancestors => None,
+            None => Some(REASON_MISSING.to_string()),
+            Some(value) if FRAME_OPTIONS_ALLOWED.contains(&value.to_ascii_lowercase().as_str()) => {
+                None
+            }
+            Some(_) => Some(REASON_UNEXPECTED_VALUE.to_string()),
+        },
+    ));
+
+    let referrer_policy = header(REFERRER_POLICY);
+    checks.push(check(
+        &REFERRER_POLICY,
+        referrer_policy,
+        match referrer_policy {
+            None => Some(REASON_MISSING.to_string()),
+            Some(value)
+                if UNSAFE_REFERRER_POLICIES.contains(&value.to_ascii_lowercase().as_str()) =>
+            {
+                Some(REASON_UNSAFE_POLICY.to_string())
+            }
+            Some(_) => None,
+        },
+    ));
+
+    for set_cookie in response.headers().get_all(SET_COOKIE) {
+        let set_cookie = String::from_utf8_lossy(set_cookie.as_bytes());
+        checks.push(check_cookie(&set_cookie));
+    }
+
+    checks
+}
+
+/// Set-Cookie の Secure / HttpOnly / SameSite 属性を判定（値は表示しない）
+fn check_cookie(set_cookie: &str) -> AuditCheck {
+    let mut parts = set_cookie.split(';').map(str::trim);
+    let name = parts
+        .next()
+        .and_then(|pair| pair.split('=').next())
+        .unwrap_or_default()
+        .to_string();
+    let attributes: Vec<&str> = parts.filter(|attribute| !attribute.is_empty()).collect();
+
+    let (mut secure, mut http_only, mut same_site) = (false, false, None);
+    for attribute in &attributes {
+        let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+        match key.trim().to_ascii_lowercase().as_str() {
+            COOKIE_SECURE => secure = true,
+            COOKIE_HTTP_ONLY => http_only = true,
+            COOKIE_SAME_SITE => same_site = Some(value.trim().to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+
+    // SameSite=None は Secure が必須
+    let same_site_ok = match same_site.as_deref() {
+        None => false,
+        Some(COOKIE_SAME_SITE_NONE) => secure,
+        Some(_) => true,
+    };
+    let missing: Vec<&str> = [
+        (!secure).then_some("Secure"),
+        (!http_only).then_some("HttpOnly"),
+        (!same_site_ok).then_some("SameSite"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    AuditCheck {
+        label: COOKIE_LABEL.replace("{}", &name),
+        value: if attributes.is_empty() {
+            MISSING_HEADER_MSG.to_string()
+        } else {
+            attributes.join("; ")
+        },
+        failure: (!missing.is_empty())
+            .then(|| REASON_COOKIE_FLAGS.replace("{}", &missing.join(", "))),
+    }
+}
+
+/// HSTSの max-age を取得
+fn hsts_max_age(value: &str) -> Option<u64> {
+    value.split(';').find_map(|directive| {
+        let directive = directive.trim();
+        directive
+            .get(..HSTS_MAX_AGE.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(HSTS_MAX_AGE))
+            .and_then(|_| {
+                directive[HSTS_MAX_AGE.len()..]
+                    .trim_matches('"')
+                    .parse()
+                    .ok()
+            })
+    })
+}
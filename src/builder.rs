@@ -0,0 +1,247 @@
+use crate::client::{BasicAuthConfig, Config, QUIET_BODY};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
+use serde_json::Value;
+use std::time::Duration;
+
+/// 型付きの値からConfigを組み立てる
+///
+/// ```no_run
+/// use reqwest::Method;
+/// use rs_w3r::client::{execute_request, Config};
+/// use serde_json::json;
+/// use std::time::Duration;
+///
+/// let config = Config::builder()
+///     .url("https://api.example.com/orders")
+///     .method(Method::POST)
+///     .json(json!({ "item": "book", "quantity": 1 }))
+///     .retry(3)
+///     .timeout(Duration::from_secs(10))
+///     .build();
+/// execute_request(config).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl Config {
+    /// 既定値から始めるビルダーを作成
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl From<Config> for ConfigBuilder {
+    /// 既存の設定（プリセットなど）を上書きするビルダーを作成
+    fn from(config: Config) -> Self {
+        ConfigBuilder { config }
+    }
+}
+
+impl ConfigBuilder {
+    /// リクエスト先のURL
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.config.url = url.into();
+        self
+    }
+
+    /// HTTPメソッド
+    pub fn method(mut self, method: Method) -> Self {
+        self.config.method = method.to_string();
+        self
+    }
+
+    /// ヘッダーを追加
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.config
+            .headers
+            .get_or_insert_with(Vec::new)
+            .push(header_entry(&name, &value));
+        self
+    }
+
+    /// ヘッダーをまとめて追加
+    pub fn headers(self, headers: &HeaderMap) -> Self {
+        headers.iter().fold(self, |builder, (name, value)| {
+            builder.header(name.clone(), value.clone())
+        })
+    }
+
+    /// クッキーを追加（"name=value" 形式）
+    pub fn cookie(mut self, cookie: impl Into<String>) -> Self {
+        self.config
+            .cookies
+            .get_or_insert_with(Vec::new)
+            .push(cookie.into());
+        self
+    }
+
+    /// クエリパラメータを追加（同名のパラメータは置き換える）
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config
+            .query
+            .get_or_insert_with(Default::default)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// JSONボディ
+    pub fn json(mut self, value: Value) -> Self {
+        self.config.json = Some(value.to_string());
+        self
+    }
+
+    /// フォームパラメータを追加
+    pub fn form(mut self, name: &str, value: &str) -> Self {
+        self.config
+            .form
+            .get_or_insert_with(Vec::new)
+            .push(format!("{}={}", name, value));
+        self
+    }
+
+    /// Basic認証
+    pub fn basic_auth(mut self, user: impl Into<String>, pass: impl Into<String>) -> Self {
+        self.config.basic_auth = Some(BasicAuthConfig {
+            user: user.into(),
+            pass: pass.into(),
+        });
+        self
+    }
+
+    /// Bearerトークン認証
+    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.config.bearer_token = Some(token.into());
+        self
+    }
+
+    /// AWS Signature Version 4 による署名（リージョンとサービス）
+    pub fn aws_sigv4(mut self, region: &str, service: &str) -> Self {
+        self.config.aws_sigv4 = Some(format!("{}/{}", region, service));
+        self
+    }
+
+    /// リクエスト全体のタイムアウト（秒単位に切り上げ）
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = whole_secs(timeout);
+        self.config.explicit_settings.timeout = true;
+        self
+    }
+
+    /// TCP接続確立までのタイムアウト（秒単位に切り上げ）
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(whole_secs(connect_timeout));
+        self
+    }
+
+    /// TCP_NODELAYの有効・無効（false でNagleアルゴリズムを有効にする）
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.config.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// TCPキープアライブの間隔（秒単位に切り上げ）
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.config.tcp_keepalive = Some(whole_secs(interval));
+        self
+    }
+
+    /// 4xx/5xx・通信エラー時のリトライ回数
+    pub fn retry(mut self, retry: u32) -> Self {
+        self.config.retry = retry;
+        self.config.explicit_settings.retry = true;
+        self
+    }
+
+    /// 初回のリトライ待機時間（以降は指数バックオフ）
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.config.retry_delay = retry_delay.as_secs_f64();
+        self.config.explicit_settings.retry_delay = true;
+        self
+    }
+
+    /// 1回の試行のタイムアウト（リトライごとにやり直す）
+    pub fn attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.config.attempt_timeout = Some(humantime::format_duration(timeout).to_string());
+        self
+    }
+
+    /// リトライを含めた全体の上限時間
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.config.max_time = Some(humantime::format_duration(max_time).to_string());
+        self
+    }
+
+    /// 2xxでも再試行するボディの条件（`json(.status) == "pending"` など、--until と同じ記法）
+    pub fn retry_if_body(mut self, condition: impl Into<String>) -> Self {
+        self.config.retry_if_body = Some(condition.into());
+        self
+    }
+
+    /// 接続エラー時に --retry とは別枠で再試行する回数
+    pub fn connect_retry(mut self, connect_retry: u32) -> Self {
+        self.config.connect_retry = connect_retry;
+        self
+    }
+
+    /// 並列に送信するリクエスト数
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.config.concurrency = Some(concurrency);
+        self
+    }
+
+    /// レスポンスの保存先
+    pub fn output(mut self, output: impl Into<String>) -> Self {
+        self.config.output = Some(output.into());
+        self
+    }
+
+    /// URLの最後のパス要素とContent-Typeから決めたファイル名で保存（-O）
+    pub fn remote_name(mut self, remote_name: bool) -> Self {
+        self.config.remote_name = remote_name;
+        self
+    }
+
+    /// 詳細出力
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.config.verbose = verbose;
+        self
+    }
+
+    /// 出力の抑制レベル（1: 補足情報を表示しない、2: ボディも表示しない）
+    pub fn quiet(mut self, level: u8) -> Self {
+        self.config.quiet = level;
+        self
+    }
+
+    /// 出力の抑制（`quiet(QUIET_BODY)` と同じ）
+    pub fn silent(mut self, silent: bool) -> Self {
+        self.config.quiet = if silent { QUIET_BODY } else { 0 };
+        self
+    }
+
+    /// 送信中の進捗表示を行わない
+    pub fn no_progress(mut self, no_progress: bool) -> Self {
+        self.config.no_progress = no_progress;
+        self
+    }
+
+    /// 設定を確定
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+/// ヘッダーを設定の "Name: value" 形式に変換
+fn header_entry(name: &HeaderName, value: &HeaderValue) -> String {
+    format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes()))
+}
+
+/// 秒単位に切り上げ
+fn whole_secs(duration: Duration) -> u64 {
+    duration
+        .as_secs()
+        .saturating_add(u64::from(duration.subsec_nanos() > 0))
+}
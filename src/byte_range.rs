@@ -0,0 +1,92 @@
+use crate::client::{shows_meta, Config};
+use reqwest::header::{HeaderMap, CONTENT_RANGE};
+use reqwest::StatusCode;
+use std::error::Error;
+
+// Rangeヘッダー
+const RANGE_UNIT_PREFIX: &str = "bytes=";
+const RANGE_SEPARATOR: char = '-';
+
+// エラーメッセージ
+const ERROR_INVALID_RANGE: &str =
+    "Invalid --range '{}'. Use <start>-<end> (e.g. 0-1023), <start>- or -<suffix length> (e.g. -500).";
+const ERROR_RANGE_IGNORED: &str =
+    "Server ignored --range {1} and returned {2} with the full body ({3}); not downloading it";
+const UNKNOWN_LENGTH: &str = "unknown length";
+const LENGTH_BYTES: &str = "{} bytes";
+
+// 表示メッセージ
+const CONTENT_RANGE_MSG: &str = "* Content-Range: {}";
+const CONTENT_RANGE_MISSING_MSG: &str = "* Warning: 206 Partial Content without a Content-Range header";
+
+/// --range の値を Range ヘッダーの値（bytes=0-1023）に変換
+///
+/// `0-1023`（範囲）、`1024-`（指定位置から最後まで）、`-500`（末尾500バイト）を受け付ける。
+pub fn range_header_value(range: &str) -> Result<String, Box<dyn Error>> {
+    let invalid = || ERROR_INVALID_RANGE.replace("{}", range);
+    let range = range.trim();
+    let range = range.strip_prefix(RANGE_UNIT_PREFIX).unwrap_or(range);
+
+    let (start, end) = range.split_once(RANGE_SEPARATOR).ok_or_else(invalid)?;
+    let parse = |value: &str| {
+        (!value.is_empty())
+            .then(|| value.parse::<u64>())
+            .transpose()
+            .map_err(|_| invalid())
+    };
+    match (parse(start.trim())?, parse(end.trim())?) {
+        (Some(start), Some(end)) if start <= end => {}
+        (Some(_), None) => {}
+        (None, Some(suffix)) if suffix > 0 => {}
+        _ => return Err(invalid().into()),
+    }
+
+    Ok(format!(
+        "{}{}{}{}",
+        RANGE_UNIT_PREFIX,
+        start.trim(),
+        RANGE_SEPARATOR,
+        end.trim()
+    ))
+}
+
+/// --range を指定したリクエストで、サーバーが範囲の指定に応じたかを確認
+///
+/// 206 の場合は Content-Range を標準エラー出力に表示し、200 の場合は
+/// ボディ全体を受信する前にエラーにする（巨大なファイルを誤ってダウンロードしないため）。
+pub fn check_range_response(
+    config: &Config,
+    status: StatusCode,
+    headers: &HeaderMap,
+    content_length: Option<u64>,
+) -> Result<(), Box<dyn Error>> {
+    let Some(range) = &config.range else {
+        return Ok(());
+    };
+
+    match status {
+        StatusCode::PARTIAL_CONTENT if shows_meta(config) => {
+            match headers
+                .get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+            {
+                Some(content_range) => {
+                    eprintln!("{}", CONTENT_RANGE_MSG.replace("{}", content_range))
+                }
+                None => eprintln!("{}", CONTENT_RANGE_MISSING_MSG),
+            }
+            Ok(())
+        }
+        StatusCode::OK => Err(ERROR_RANGE_IGNORED
+            .replace("{1}", range)
+            .replace("{2}", &status.to_string())
+            .replace(
+                "{3}",
+                &content_length.map_or(UNKNOWN_LENGTH.to_string(), |length| {
+                    LENGTH_BYTES.replace("{}", &length.to_string())
+                }),
+            )
+            .into()),
+        _ => Ok(()),
+    }
+}
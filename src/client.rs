@@ -1,31 +1,105 @@
-use reqwest::blocking::Client;
+use crate::assertion::AssertConfig;
+use crate::byte_range::{check_range_response, range_header_value};
+use crate::condition::Condition;
+use crate::config_include::read_config_value;
+use crate::connection_stats::{ConnectionCountingLayer, ConnectionStats, HandshakeCounter};
+use crate::cookie_import::import_cookie_file;
+use crate::dns_cache::CachingResolver;
+use crate::environment::Environment;
+use crate::golden::{compare_golden_file, update_golden_file};
+use crate::glob::{apply_glob_placeholders, expand_url_glob, GlobUrl};
+use crate::header_assertion::{check_header_assertions, validate_header_assertions};
+use crate::header_filter::is_header_visible;
+use crate::host_defaults::{
+    apply_host_defaults, apply_host_request_defaults, has_header, ExplicitSettings, HostDefaults,
+};
+use crate::hsts::{default_hsts_path, record_hsts_header, upgrade_url, HstsStore};
+use crate::idn::{display_idn_host, to_ascii_url};
+use crate::interrupt::{is_interrupted, sleep_unless_interrupted};
+use crate::json_viewer::view_json;
+use crate::jwt::JwtConfig;
+use crate::load::{parse_report_interval, run_load_test};
+use crate::oauth2::{invalidate_access_token, resolve_access_token, OAuth2Config};
+#[cfg(feature = "otel")]
+use crate::otel::export_request_span;
+use crate::observer::{ResponseObserver, RetryReason};
+use crate::output_file::{parse_byte_size, parse_output_rotate, write_output_file};
+use crate::output_path::{
+    apply_status_placeholder, extension_for_content_type, is_binary_content_type, is_output_directory,
+    remote_file_name, render_output_path,
+};
+use crate::phase_timing::{
+    display_timing_waterfall, ConnectTimingLayer, PhaseRecorder, TimingResolver, TlsStartRecorder,
+};
+use crate::proxy_rules::build_rules_proxy;
+use crate::redirect::{execute_following_redirects, setup_redirect_policy};
+use crate::result_document::{
+    is_json_format, is_json_meta_format, parse_meta_format, parse_output_format,
+    render_request_meta, render_response_meta, render_result_document,
+};
+use crate::secrets::{decrypt_secrets, resolve_keychain_refs};
+use crate::shell_export::{render_shell_exports, validate_shell_exports};
+use crate::signer::{default_signers, RequestSigner};
+use crate::sigv4::validate_aws_sigv4;
+use crate::summary::{display_run_summary, parse_summary_format, RequestSummary};
+use crate::tls::{
+    build_recording_tls_config, dump_certificate_chain, load_client_identity, open_key_log,
+    CertificateChain, SSLKEYLOGFILE_ENV,
+};
+use crate::trace_context::apply_trace_context;
+#[cfg(feature = "otel")]
+use crate::trace_context::TRACEPARENT_HEADER;
+use crate::upload_progress::UploadProgress;
+use crate::watch::{ChangeTracker, WatchStats};
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::blocking::multipart::Form;
+use reqwest::blocking::{Body, Client};
+use reqwest::dns::Resolve;
 use reqwest::cookie::Jar;
-use reqwest::header::{HeaderName, CONTENT_TYPE};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, COOKIE, RANGE};
 use reqwest::{Method, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
-use std::fs::File;
-use std::io::{Read, Write};
-use std::sync::Arc;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Cursor, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 // アプリケーション情報
-const USER_AGENT: &str = "rs-w3r/1.0";
+pub(crate) const USER_AGENT: &str = "rs-w3r/1.0";
 
 // デフォルト値
-pub(crate) const DEFAULT_RETRY_COUNT: u32 = 0;
-pub(crate) const DEFAULT_RETRY_DELAY: f64 = 1.0;
-pub(crate) const DEFAULT_TIMEOUT_SECS: u64 = 30;
-pub(crate) const DEFAULT_METHOD: &str = "GET";
+pub const DEFAULT_RETRY_COUNT: u32 = 0;
+pub const DEFAULT_RETRY_DELAY: f64 = 1.0;
+pub const DEFAULT_CONNECT_RETRY_COUNT: u32 = 0;
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_METHOD: &str = "GET";
+pub const DEFAULT_POLL_INTERVAL: &str = "5s";
+pub const DEFAULT_MAX_FORMAT_SIZE: &str = "64M";
+
+// 出力の抑制レベル（-q: 補足情報を表示しない、-qq: ボディも表示しない）
+pub const QUIET_META: u8 = 1;
+pub const QUIET_BODY: u8 = 2;
 
 // リトライ関連
 const RETRY_BACKOFF_MULTIPLIER: f64 = 2.0;
 
 // ファイルサイズ計算
 const BYTES_PER_KB: f64 = 1024.0;
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+// TLSセッションキャッシュの件数（rustlsの既定と同じ）
+const TLS_SESSION_CACHE_SIZE: usize = 256;
+
+// レスポンスボディの読み込み
+const BODY_CHUNK_SIZE: usize = 8192;
+const CHARSET_PARAM: &str = "charset=";
 
 // HTTPステータスコード
 const SERVER_ERROR_START: u16 = 500;
@@ -36,18 +110,85 @@ const REQUEST_TIMEOUT: u16 = 408;
 // Content-Type
 const CONTENT_TYPE_FORM: &str = "application/x-www-form-urlencoded";
 const CONTENT_TYPE_JSON: &str = "application/json; charset=utf-8";
+const CONTENT_TYPE_NDJSON: &str = "application/x-ndjson";
+const CONTENT_TYPE_OCTET_STREAM: &str = "application/octet-stream";
 
 // 認証プレースホルダー
-const BASIC_AUTH_PLACEHOLDER: &str = "Basic <credentials>";
+const CREDENTIALS_PLACEHOLDER: &str = "<credentials>";
+
+// APIキーの付与先
+const API_KEY_LOCATION_HEADER: &str = "header";
+const API_KEY_LOCATION_QUERY: &str = "query";
+const API_KEY_LOCATION_COOKIE: &str = "cookie";
+const DEFAULT_API_KEY_IN: &str = "header:X-Api-Key";
+
+// スキーム
+const HTTPS_SCHEME: &str = "https";
+const HTTP_PROXY_SCHEME: &str = "http://";
+
+// ファイル参照
+const FILE_REFERENCE_PREFIX: char = '@';
+const STDIN_SOURCE: &str = "-";
+const URL_LIST_COMMENT_PREFIX: char = '#';
+
+// 並列実行の出力順序
+const OUTPUT_ORDER_ORDERED: &str = "ordered";
+const OUTPUT_ORDER_COMPLETED: &str = "completed";
 
 // JSONフィルタ関連
 const JSON_PATH_ROOT: &str = ".";
 
+// レスポンス表示関連
+const EDITOR_ENV: &str = "EDITOR";
+const OPEN_FILE_PREFIX: &str = "rs-w3r-";
+const PAGER_ENV: &str = "PAGER";
+const DEFAULT_PAGER: &str = "less -R";
+
+// サイズ指定のオプション名
+const WARN_SIZE_OPTION: &str = "--warn-size";
+const MAX_FORMAT_SIZE_OPTION: &str = "--max-format-size";
+
+// 出力テンプレート関連
+const TEMPLATE_OPEN: &str = "{{";
+const TEMPLATE_CLOSE: &str = "}}";
+const TEMPLATE_PIPE: char = '|';
+
 // エラーメッセージ
 const ERROR_REQUEST_CLONE: &str = "Failed to clone request for retry";
 const ERROR_PRESET_NOT_FOUND: &str = "Preset '{}' not found in config file";
 const ERROR_NO_PRESETS: &str = "No presets found in config file";
+const ERROR_ENVIRONMENT_NOT_FOUND: &str = "Environment '{}' not found in config file";
 const ERROR_UNKNOWN_METHOD: &str = "Unknown HTTP method";
+const ERROR_INVALID_JSON_BODY: &str = "Invalid JSON body: {}";
+const ERROR_INVALID_YAML_BODY: &str = "Invalid YAML body: {}";
+const ERROR_FORM_FILE: &str = "Failed to read --form file '{1}': {2}";
+const ERROR_UNCLOSED_TEMPLATE: &str = "Unclosed '{{' in output template";
+const ERROR_UNKNOWN_TEMPLATE_FUNCTION: &str = "Unknown template function: {}";
+const ERROR_EMPTY_EDITOR: &str = "EDITOR environment variable is empty";
+const ERROR_INVALID_DURATION: &str = "Invalid duration '{1}': {2}";
+const ERROR_INVALID_API_KEY_IN: &str =
+    "Invalid API key location '{}'. Use header:<name>, query:<name> or cookie:<name>.";
+const ERROR_INVALID_OUTPUT_ORDER: &str = "Invalid output order '{}'. Use ordered or completed.";
+const ERROR_MISSING_PROXY_HOST: &str = "Proxy host is required. Use --proxy <URL> or --proxy-host and --proxy-port.";
+const ERROR_WORKER_STOPPED: &str = "Request workers stopped unexpectedly";
+const ERROR_REQUEST_FAILED: &str = "Request to {1} failed with HTTP {2}";
+const ERROR_REQUESTS_FAILED: &str = "{1} of {2} requests failed";
+const ERROR_MAX_TIME_EXCEEDED: &str = "Request to {1} took {2}, exceeding --max-time-assert {3}";
+const ERROR_INTERRUPTED: &str = "Interrupted before the request completed ({} attempts)";
+const ERROR_MAX_TIME_REACHED: &str = "Request did not complete within --max-time {1} ({2} attempts, last: {3})";
+const ERROR_WATERFALL_CONCURRENCY: &str =
+    "--timing-waterfall cannot be combined with --concurrency greater than 1.";
+const ERROR_UNTIL_TIMED_OUT: &str = "Condition '{1}' was not met within --max-wait {2} (last: {3})";
+const ERROR_INVALID_SIZE: &str = "Invalid {1} '{2}'. Use <N>[K|M|G] (e.g. 50M).";
+const ERROR_LARGE_RESPONSE: &str =
+    "Response is {1} bytes, exceeding --warn-size {2}. Use --yes to print it or --output to save it.";
+const ERROR_LARGE_RESPONSE_DECLINED: &str = "Aborted: response of {} bytes was not read";
+const ERROR_FORMAT_SIZE_EXCEEDED: &str = "Response body is {1} bytes, exceeding --max-format-size {2}. Raise the limit to apply --json-filter, --transform or --template.";
+const ERROR_NO_OAUTH2_TOKEN: &str =
+    "No valid OAuth2 token found. Run `rs-w3r auth device` or `rs-w3r auth login` with the same preset first, or set grant_type = \"client_credentials\".";
+
+// Authorizationヘッダーの形式
+const BEARER_PREFIX: &str = "Bearer ";
 
 // 表示メッセージ
 const TIMING_HEADER: &str = "--- Timing Information ---";
@@ -57,8 +198,28 @@ const BODY_READ_TIME_MSG: &str = "Body read time: {}";
 const TOTAL_TIME_MSG: &str = "Total time: {}";
 const RESPONSE_SIZE_MSG: &str = "Response size: {1} bytes ({2} KB)";
 const THROUGHPUT_MSG: &str = "Throughput: {} KB/s";
+const COOKIES_HEADER: &str = "--- Set-Cookie ---";
+const ATTEMPTS_HEADER: &str = "--- Attempts ---";
+const ATTEMPT_SUMMARY_MSG: &str = "Transfer time: {1} / Backoff time: {2}";
+const EMPTY_VALUE_MARK: &str = "-";
 const HTTP_RETRY_MSG: &str = "HTTP {} - retrying after delay...";
+const DRY_RUN_MSG: &str = "* Dry run: {1} {2}";
+const COOKIES_IMPORTED_MSG: &str = "* Cookies: loaded {1} from {2}";
+const HSTS_UPGRADE_MSG: &str = "* HSTS: upgraded request to {}";
+const HSTS_SAVE_ERROR_MSG: &str = "* HSTS: failed to save store: {}";
+const BODY_RETRY_MSG: &str = "HTTP {} - body matched --retry-if-body, retrying after delay...";
+const BODY_RETRY_OUTCOME: &str = "HTTP {} (retry-if-body)";
 const REQUEST_ERROR_RETRY_MSG: &str = "Request error: {} - retrying after delay...";
+const UNAUTHORIZED_RETRY_MSG: &str = "HTTP 401 - OAuth2 token rejected, retrying once with a fresh token...";
+const NDJSON_BATCH_MSG: &str = "--- NDJSON Batch {1} ({2} records) ---";
+const REQUEST_FAILED_MSG: &str = "Error: {1}: {2}";
+const UNTIL_WAITING_MSG: &str = "* Waiting: {1} (condition not met, {2} elapsed)";
+const LARGE_RESPONSE_PROMPT: &str = "Response is {1} bytes ({2} MB), exceeding --warn-size {3}. Print it? [y/N] ";
+const PARTIAL_BODY_MSG: &str = "* Interrupted after receiving {} bytes of the body";
+const PARTIAL_BODY_SAVED_MSG: &str = "* Interrupted: saved the {1} bytes received so far to {2}";
+const SAVED_FILE_MSG: &str = "* Saved {1} ({2} bytes)";
+const FORMAT_SKIPPED_MSG: &str = "* Body is {1} bytes, exceeding --max-format-size {2}; printing it unformatted";
+const CONFIRM_YES: [&str; 2] = ["y", "yes"];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BasicAuthConfig {
@@ -68,38 +229,162 @@ pub struct BasicAuthConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyConfig {
+    /// プロキシのURL（http://proxy.example.com:8080 など。指定時は host・port より優先）
+    pub url: Option<String>,
+    #[serde(default)]
     pub host: String,
+    #[serde(default)]
     pub port: String,
     pub user: Option<String>,
     pub pass: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transform {
+    Map(String),
+    Sort,
+    Unique,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    pub api_key: Option<String>,
+    pub api_key_in: Option<String>,
+    pub assert: Option<AssertConfig>,
+    pub assert_header: Option<Vec<String>>,
+    pub assert_header_regex: Option<Vec<String>>,
+    pub attempt_timeout: Option<String>,
+    /// AWS SigV4で署名するリージョンとサービス（--aws-sigv4 us-east-1/execute-api）
+    pub aws_sigv4: Option<String>,
     pub basic_auth: Option<BasicAuthConfig>,
+    /// Authorization: Bearer に付与するトークン（--bearer）
+    pub bearer_token: Option<String>,
+    pub bench_output: Option<String>,
+    pub batch_size: Option<usize>,
+    /// 相互TLSのクライアント証明書（PEM・PKCS#12）
+    pub cert: Option<String>,
+    pub cert_type: Option<String>,
+    pub changes_only: bool,
+    pub concurrency: Option<usize>,
+    pub connect_retry: u32,
+    pub connect_timeout: Option<u64>,
+    /// --data-binary のContent-Type（省略時は application/octet-stream）
+    pub content_type: Option<String>,
     pub cookies: Option<Vec<String>>,
+    pub cookies_from: Option<String>,
+    pub data_binary: Option<String>,
+    pub dns_cache: Option<String>,
     pub dry_run: bool,
+    pub dump_certs: Option<String>,
+    pub duration: Option<String>,
+    pub each: Option<String>,
+    pub expect_body_file: Option<String>,
+    /// 既定値と同じでもホストの既定値より優先する設定（--timeout・--retry・--retry-delay）
+    pub explicit_settings: ExplicitSettings,
+    /// JSONの値を `export NAME='value'` として出力（--export-shell NAME=.path）
+    pub export_shell: Option<Vec<String>>,
+    pub fail_fast: bool,
+    /// 保存済みのOAuth2トークンを使わずに更新・再取得する（--force-token-refresh）
+    pub force_token_refresh: bool,
     pub form_data: Option<String>,
     pub form: Option<Vec<String>>,
+    pub format: Option<String>,
+    pub globoff: bool,
     pub headers: Option<Vec<String>>,
+    pub hide_headers: Option<String>,
+    /// 接続先のホストごとの既定値（設定ファイルの [hosts]）
+    pub hosts: Option<HashMap<String, HostDefaults>>,
+    pub hsts_file: Option<String>,
     pub json: Option<String>,
     pub json_filter: Option<String>,
+    pub jwt: Option<JwtConfig>,
+    /// クライアント証明書の秘密鍵（省略時は cert のファイルから読み込む）
+    pub key: Option<String>,
+    pub keep_going: bool,
+    pub max_format_size: Option<String>,
+    pub max_time: Option<String>,
+    pub max_time_assert: Option<String>,
+    pub max_wait: Option<String>,
+    pub meta_format: Option<String>,
     pub method: String,
+    pub ndjson: Option<String>,
+    pub ndjson_response: bool,
+    pub no_hsts: bool,
+    pub no_input: bool,
+    pub no_pager: bool,
+    pub no_progress: bool,
+    pub oauth2: Option<OAuth2Config>,
+    #[cfg(feature = "otel")]
+    pub otel_endpoint: Option<String>,
+    pub open: bool,
     pub output: Option<String>,
+    pub output_append: bool,
+    pub output_order: Option<String>,
+    pub output_rotate: Option<String>,
+    pub output_template: Option<String>,
+    pub params: Option<Vec<String>>,
+    pub poll: Option<String>,
+    pub post301: bool,
+    pub post302: bool,
+    pub post303: bool,
     pub pretty_json: bool,
     pub proxy: Option<ProxyConfig>,
+    /// ホストのパターンごとのプロキシ（設定ファイルの [proxy_rules]）
+    pub proxy_rules: Option<HashMap<String, String>>,
+    pub query: Option<BTreeMap<String, String>>,
+    pub quiet: u8,
+    /// 取得するバイト範囲（--range 0-1023、-500）
+    pub range: Option<String>,
+    /// URLの最後のパス要素をファイル名にして保存（-O）
+    pub remote_name: bool,
+    pub report_interval: Option<String>,
     pub retry: u32,
+    pub retry_if_body: Option<String>,
+    pub rps: Option<u32>,
     pub retry_delay: f64,
-    pub silent: bool,
+    pub secret_vars: Option<Vec<String>>,
+    pub show_cookies: bool,
+    pub show_diff: bool,
+    pub show_headers: Option<String>,
+    pub summary_format: Option<String>,
+    pub tcp_keepalive: Option<u64>,
+    /// 指定しない場合はreqwestの既定（有効）のまま
+    pub tcp_nodelay: Option<bool>,
     pub timeout: u64,
     pub timing: bool,
+    pub timing_waterfall: bool,
+    pub tls_keylog: Option<String>,
+    pub traceparent: Option<String>,
+    pub tracestate: Option<String>,
+    pub transforms: Option<Vec<Transform>>,
+    pub until: Option<String>,
+    pub update_golden: bool,
     pub url: String,
+    pub url_file: Option<String>,
+    pub vars: Option<HashMap<String, String>>,
     pub verbose: bool,
+    pub view: bool,
+    pub warmup: Option<String>,
+    pub warn_size: Option<String>,
+    pub watch: Option<String>,
+    pub yaml: Option<String>,
+    pub yes: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct ConfigFile {
     preset: HashMap<String, ConfigPreset>,
+    environments: Option<HashMap<String, Environment>>,
+    proxy_rules: Option<HashMap<String, String>>,
+    hosts: Option<HashMap<String, HostDefaults>>,
+    status: Option<StatusGroup>,
+}
+
+/// status サブコマンドで確認するプリセットのグループ（[status]）
+#[derive(Debug, Clone, Deserialize)]
+struct StatusGroup {
+    presets: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -108,68 +393,360 @@ struct ConfigPreset {
     method: Option<String>,
     headers: Option<Vec<String>>,
     timeout: Option<u64>,
+    connect_timeout: Option<u64>,
+    content_type: Option<String>,
+    connect_retry: Option<u32>,
+    tcp_nodelay: Option<bool>,
+    tcp_keepalive: Option<u64>,
     pretty_json: Option<bool>,
     timing: Option<bool>,
+    timing_waterfall: Option<bool>,
+    tls_keylog: Option<String>,
+    traceparent: Option<String>,
+    tracestate: Option<String>,
     verbose: Option<bool>,
+    view: Option<bool>,
     silent: Option<bool>,
+    quiet: Option<u8>,
+    show_cookies: Option<bool>,
+    show_headers: Option<String>,
+    report_interval: Option<String>,
+    warmup: Option<String>,
+    watch: Option<String>,
+    changes_only: Option<bool>,
+    show_diff: Option<bool>,
+    until: Option<String>,
+    poll: Option<String>,
+    max_wait: Option<String>,
+    meta_format: Option<String>,
     retry: Option<u32>,
+    retry_if_body: Option<String>,
+    rps: Option<u32>,
     retry_delay: Option<f64>,
     json: Option<String>,
     json_filter: Option<String>,
+    transforms: Option<Vec<Transform>>,
+    output_template: Option<String>,
+    each: Option<String>,
+    expect_body_file: Option<String>,
+    export_shell: Option<Vec<String>>,
+    update_golden: Option<bool>,
     form_data: Option<String>,
     form: Option<Vec<String>>,
+    url_file: Option<String>,
+    globoff: Option<bool>,
+    fail_fast: Option<bool>,
+    keep_going: Option<bool>,
+    max_time_assert: Option<String>,
+    max_format_size: Option<String>,
+    max_time: Option<String>,
+    warn_size: Option<String>,
+    yes: Option<bool>,
+    ndjson: Option<String>,
+    ndjson_response: Option<bool>,
+    open: Option<bool>,
+    no_pager: Option<bool>,
+    no_progress: Option<bool>,
+    hide_headers: Option<String>,
+    hsts_file: Option<String>,
+    no_hsts: Option<bool>,
+    no_input: Option<bool>,
+    params: Option<Vec<String>>,
+    post301: Option<bool>,
+    post302: Option<bool>,
+    post303: Option<bool>,
+    vars: Option<HashMap<String, String>>,
+    secret_vars: Option<Vec<String>>,
+    batch_size: Option<usize>,
+    concurrency: Option<usize>,
     cookies: Option<Vec<String>>,
+    cookies_from: Option<String>,
+    data_binary: Option<String>,
+    dns_cache: Option<String>,
     output: Option<String>,
+    output_append: Option<bool>,
+    output_order: Option<String>,
+    output_rotate: Option<String>,
+    summary_format: Option<String>,
+    format: Option<String>,
     dry_run: Option<bool>,
+    dump_certs: Option<String>,
+    duration: Option<String>,
+    api_key: Option<String>,
+    api_key_in: Option<String>,
+    assert: Option<AssertConfig>,
+    assert_header: Option<Vec<String>>,
+    assert_header_regex: Option<Vec<String>>,
+    attempt_timeout: Option<String>,
+    aws_sigv4: Option<String>,
     basic_auth: Option<BasicAuthConfig>,
+    bearer_token: Option<String>,
+    bench_output: Option<String>,
+    cert: Option<String>,
+    cert_type: Option<String>,
+    jwt: Option<JwtConfig>,
+    key: Option<String>,
+    oauth2: Option<OAuth2Config>,
+    #[cfg(feature = "otel")]
+    otel_endpoint: Option<String>,
     proxy: Option<ProxyConfig>,
+    query: Option<BTreeMap<String, String>>,
+    range: Option<String>,
+    remote_name: Option<bool>,
+    yaml: Option<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
+            api_key: None,
+            api_key_in: None,
+            assert: None,
+            assert_header: None,
+            assert_header_regex: None,
+            attempt_timeout: None,
+            aws_sigv4: None,
             basic_auth: None,
+            bearer_token: None,
+            bench_output: None,
+            batch_size: None,
+            cert: None,
+            cert_type: None,
+            changes_only: false,
+            concurrency: None,
+            connect_retry: DEFAULT_CONNECT_RETRY_COUNT,
+            connect_timeout: None,
+            content_type: None,
             cookies: None,
+            cookies_from: None,
+            data_binary: None,
+            dns_cache: None,
             dry_run: false,
+            dump_certs: None,
+            duration: None,
+            each: None,
+            expect_body_file: None,
+            explicit_settings: ExplicitSettings::default(),
+            export_shell: None,
+            fail_fast: false,
+            force_token_refresh: false,
             form_data: None,
             form: None,
+            format: None,
+            globoff: false,
             headers: None,
+            hide_headers: None,
+            hosts: None,
+            hsts_file: None,
             json: None,
             json_filter: None,
+            jwt: None,
+            key: None,
+            keep_going: false,
+            max_format_size: None,
+            max_time: None,
+            max_time_assert: None,
+            max_wait: None,
+            meta_format: None,
             method: DEFAULT_METHOD.to_string(),
+            ndjson: None,
+            ndjson_response: false,
+            no_hsts: false,
+            no_input: false,
+            no_pager: false,
+            no_progress: false,
+            oauth2: None,
+            #[cfg(feature = "otel")]
+            otel_endpoint: None,
+            open: false,
             output: None,
+            output_append: false,
+            output_order: None,
+            output_rotate: None,
+            output_template: None,
+            params: None,
+            poll: None,
+            post301: false,
+            post302: false,
+            post303: false,
             pretty_json: false,
             proxy: None,
+            proxy_rules: None,
+            query: None,
+            quiet: 0,
+            range: None,
+            remote_name: false,
+            report_interval: None,
             retry: DEFAULT_RETRY_COUNT,
+            retry_if_body: None,
+            rps: None,
             retry_delay: DEFAULT_RETRY_DELAY,
-            silent: false,
+            secret_vars: None,
+            show_cookies: false,
+            show_diff: false,
+            show_headers: None,
+            summary_format: None,
+            tcp_keepalive: None,
+            tcp_nodelay: None,
             timeout: DEFAULT_TIMEOUT_SECS,
             timing: false,
+            timing_waterfall: false,
+            tls_keylog: None,
+            traceparent: None,
+            tracestate: None,
+            transforms: None,
+            until: None,
+            update_golden: false,
             url: String::new(),
+            url_file: None,
+            vars: None,
             verbose: false,
+            view: false,
+            warmup: None,
+            warn_size: None,
+            watch: None,
+            yaml: None,
+            yes: false,
         }
     }
 }
 
+/// 受信したレスポンスのステータスとヘッダー
 #[derive(Debug)]
-struct ResponseInfo {
+pub struct ResponseInfo {
     status: reqwest::StatusCode,
     version: reqwest::Version,
     headers: reqwest::header::HeaderMap,
+    url: Url,
+    /// 受信したバイト列（テキストとして扱えないContent-Typeの場合のみ）
+    body_bytes: Option<Vec<u8>>,
+    /// 受信しながら1行ずつ表示したNDJSONのボディ（表示済み）
+    body_streamed: bool,
 }
 
+/// レスポンス受信までの所要時間と試行の履歴
 #[derive(Debug)]
-struct TimingInfo {
-    response_time: Duration,
-    body_read_time: Duration,
-    total_time: Duration,
+pub struct TimingInfo {
+    pub response_time: Duration,
+    pub body_read_time: Duration,
+    pub total_time: Duration,
+    pub attempts: Vec<AttemptInfo>,
+    /// 段階ごとの内訳（--timing-waterfall の場合のみ）
+    pub phases: Option<PhaseTimings>,
+}
+
+/// 最後の試行の段階ごとの所要時間
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseTimings {
+    pub dns: Duration,
+    pub connect: Duration,
+    pub tls: Duration,
+    pub wait: Duration,
+    pub download: Duration,
+    /// 既存の接続を再利用した（名前解決・接続・TLSは0）
+    pub reused_connection: bool,
 }
 
+/// 1回の試行の結果
 #[derive(Debug)]
+pub struct AttemptInfo {
+    pub number: u32,
+    pub outcome: String,
+    pub duration: Duration,
+    pub delay: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct SetCookieInfo {
+    name: String,
+    value: String,
+    domain: Option<String>,
+    path: Option<String>,
+    expires: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<String>,
+}
+
+/// 複数のリクエストで共有するクッキーのJar
+type SharedCookieJar = Arc<Jar>;
+
+/// 複数のリクエストで共有するHTTPクライアントと署名・オブザーバー
+struct ClientContext {
+    client: Client,
+    default_headers: reqwest::header::HeaderMap,
+    certificate_chain: CertificateChain,
+    phase_recorder: Option<PhaseRecorder>,
+    connection_stats: ConnectionStats,
+    cookie_jar: Option<SharedCookieJar>,
+    signers: Vec<Box<dyn RequestSigner>>,
+    observers: Arc<[Box<dyn ResponseObserver>]>,
+}
+
 struct RequestContext {
     client: Client,
     request: reqwest::blocking::Request,
     default_headers: reqwest::header::HeaderMap,
+    certificate_chain: CertificateChain,
+    phase_recorder: Option<PhaseRecorder>,
+    connection_stats: ConnectionStats,
+    observers: Arc<[Box<dyn ResponseObserver>]>,
+}
+
+/// 送信して受信したレスポンス
+struct FetchedResponse {
+    request_url: Url,
+    certificate_chain: CertificateChain,
+    observers: Arc<[Box<dyn ResponseObserver>]>,
+    response_info: ResponseInfo,
+    response_body: String,
+    timing_info: TimingInfo,
+}
+
+/// 詳細出力（-v）の試行・リトライ・レスポンスヘッダーとSet-Cookieの表示
+struct VerbosePrinter;
+
+/// タイミング情報（--timing）の表示
+struct TimingPrinter;
+
+/// 段階ごとの所要時間（--timing-waterfall）の表示
+struct WaterfallPrinter;
+
+/// ワーカーに渡すリクエスト
+struct RequestJob {
+    index: usize,
+    request_context: RequestContext,
+    config: Config,
+}
+
+/// ワーカーで送信を終えたリクエスト
+struct CompletedRequest {
+    config: Config,
+    request_headers: reqwest::header::HeaderMap,
+    result: Result<FetchedResponse, String>,
+}
+
+/// リクエストの送信方法（pool がない場合は順次送信）と各リクエストの実行結果
+struct RequestDispatcher {
+    pool: Option<WorkerPool>,
+    summaries: Vec<RequestSummary>,
+}
+
+/// 決まった数のワーカースレッドでリクエストを並列に送信
+struct WorkerPool {
+    job_sender: Option<SyncSender<RequestJob>>,
+    result_receiver: Receiver<(usize, CompletedRequest)>,
+    workers: Vec<thread::JoinHandle<()>>,
+    output: PoolOutput,
+    submitted: usize,
+}
+
+/// 並列送信した結果の出力（ordered の場合は送信順に並べ替える）
+struct PoolOutput {
+    ordered: bool,
+    next_index: usize,
+    pending: BTreeMap<usize, CompletedRequest>,
+    default_headers: reqwest::header::HeaderMap,
 }
 
 impl ResponseInfo {
@@ -177,14 +754,30 @@ impl ResponseInfo {
         status: reqwest::StatusCode,
         version: reqwest::Version,
         headers: reqwest::header::HeaderMap,
+        url: Url,
     ) -> Self {
         Self {
             status,
             version,
             headers,
+            url,
+            body_bytes: None,
+            body_streamed: false,
         }
     }
 
+    /// 画像・PDFなどのバイナリのボディを保持する（ファイルへの保存用）
+    fn with_body_bytes(mut self, body_bytes: Vec<u8>) -> Self {
+        self.body_bytes = Some(body_bytes);
+        self
+    }
+
+    /// ボディを受信しながら表示済み（表示・保存の処理では出力しない）
+    fn with_streamed_body(mut self) -> Self {
+        self.body_streamed = true;
+        self
+    }
+
     pub fn status(&self) -> reqwest::StatusCode {
         self.status
     }
@@ -196,31 +789,110 @@ impl ResponseInfo {
     pub fn headers(&self) -> &reqwest::header::HeaderMap {
         &self.headers
     }
+
+    /// リダイレクト後の最終的なURL
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
 }
 
 impl TimingInfo {
-    pub fn new(response_time: Duration, body_read_time: Duration, total_time: Duration) -> Self {
+    pub fn new(
+        response_time: Duration,
+        body_read_time: Duration,
+        total_time: Duration,
+        attempts: Vec<AttemptInfo>,
+    ) -> Self {
         Self {
             response_time,
             body_read_time,
             total_time,
+            attempts,
+            phases: None,
         }
     }
 }
 
-/// 設定ファイルを読み込んでConfigを作成
+/// 設定ファイルを読み込んでConfigを作成（暗号化された値は復号してから解釈）
 pub fn load_config_file(
     config_path: &str,
     preset_name: Option<&str>,
+    identity: Option<&str>,
+    environment: Option<&str>,
 ) -> Result<Config, Box<dyn Error>> {
-    let mut file = File::open(config_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    let config_file = read_config_file(config_path, identity, preset_name)?;
+    let environment = get_environment(&config_file, environment)?;
+    let preset = get_preset(&config_file, preset_name)?;
+    Ok(create_config(&config_file, preset, environment))
+}
+
+/// 設定ファイルのすべてのプリセットを名前順に読み込む
+pub fn load_config_presets(
+    config_path: &str,
+    identity: Option<&str>,
+    environment: Option<&str>,
+) -> Result<Vec<(String, Config)>, Box<dyn Error>> {
+    let config_file = read_config_file(config_path, identity, None)?;
+    let environment = get_environment(&config_file, environment)?;
+    let mut presets: Vec<(String, Config)> = config_file
+        .preset
+        .iter()
+        .map(|(name, preset)| (name.clone(), create_config(&config_file, preset, environment)))
+        .collect();
+    presets.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-    let config_file: ConfigFile = toml::from_str(&contents)?;
+    Ok(presets)
+}
 
-    let preset = get_preset(&config_file, preset_name)?;
-    Ok(create_config_from_preset(preset))
+/// status サブコマンドで確認するプリセットを読み込む（[status] があればその順、なければ名前順のすべて）
+pub fn load_status_presets(
+    config_path: &str,
+    identity: Option<&str>,
+    environment: Option<&str>,
+) -> Result<Vec<(String, Config)>, Box<dyn Error>> {
+    let config_file = read_config_file(config_path, identity, None)?;
+    let Some(status_group) = &config_file.status else {
+        return load_config_presets(config_path, identity, environment);
+    };
+    let environment = get_environment(&config_file, environment)?;
+
+    status_group
+        .presets
+        .iter()
+        .map(|name| {
+            get_preset(&config_file, Some(name))
+                .map(|preset| (name.clone(), create_config(&config_file, preset, environment)))
+        })
+        .collect()
+}
+
+/// 設定ファイル（include で取り込むファイルを含む）を読み込み、暗号化された値の復号と
+/// キーチェーンの値の置き換えを行う（preset_name を指定した場合はそのプリセットのみ）
+fn read_config_file(
+    config_path: &str,
+    identity: Option<&str>,
+    preset_name: Option<&str>,
+) -> Result<ConfigFile, Box<dyn Error>> {
+    let mut raw_config = read_config_value(config_path)?;
+    decrypt_secrets(&mut raw_config, identity)?;
+    resolve_keychain_refs(&mut raw_config, preset_name)?;
+    Ok(raw_config.try_into()?)
+}
+
+/// --env で指定した環境を取得
+fn get_environment<'a>(
+    config_file: &'a ConfigFile,
+    environment: Option<&str>,
+) -> Result<Option<&'a Environment>, Box<dyn Error>> {
+    environment
+        .map(|name| {
+            config_file
+                .environments
+                .as_ref()
+                .and_then(|environments| environments.get(name))
+                .ok_or_else(|| ERROR_ENVIRONMENT_NOT_FOUND.replace("{}", name).into())
+        })
+        .transpose()
 }
 
 /// プリセットを取得
@@ -232,7 +904,7 @@ fn get_preset<'a>(
         Some(name) => config_file
             .preset
             .get(name)
-            .ok_or_else(|| format!("{}", ERROR_PRESET_NOT_FOUND.replace("{}", name)).into()),
+            .ok_or_else(|| ERROR_PRESET_NOT_FOUND.replace("{}", name).into()),
         None => config_file
             .preset
             .values()
@@ -241,357 +913,2158 @@ fn get_preset<'a>(
     }
 }
 
+/// プリセットと設定ファイル全体の設定（[proxy_rules]・[hosts]）からConfigを作成
+fn create_config(
+    config_file: &ConfigFile,
+    preset: &ConfigPreset,
+    environment: Option<&Environment>,
+) -> Config {
+    let mut config = create_config_from_preset(preset, environment);
+    config.proxy_rules = config_file.proxy_rules.clone();
+    config.hosts = config_file.hosts.clone();
+    config
+}
+
 /// プリセットからConfigを作成
-fn create_config_from_preset(preset: &ConfigPreset) -> Config {
-    Config {
+fn create_config_from_preset(preset: &ConfigPreset, environment: Option<&Environment>) -> Config {
+    let mut config = Config {
+        api_key: preset.api_key.clone(),
+        api_key_in: preset.api_key_in.clone(),
+        assert: preset.assert.clone(),
+        assert_header: preset.assert_header.clone(),
+        assert_header_regex: preset.assert_header_regex.clone(),
+        attempt_timeout: preset.attempt_timeout.clone(),
+        aws_sigv4: preset.aws_sigv4.clone(),
         basic_auth: preset.basic_auth.clone(),
+        bearer_token: preset.bearer_token.clone(),
+        bench_output: preset.bench_output.clone(),
+        batch_size: preset.batch_size,
+        cert: preset.cert.clone(),
+        cert_type: preset.cert_type.clone(),
+        changes_only: preset.changes_only.unwrap_or(false),
+        concurrency: preset.concurrency,
+        connect_retry: preset.connect_retry.unwrap_or(DEFAULT_CONNECT_RETRY_COUNT),
+        connect_timeout: preset.connect_timeout,
+        content_type: preset.content_type.clone(),
         cookies: preset.cookies.clone(),
+        cookies_from: preset.cookies_from.clone(),
+        data_binary: preset.data_binary.clone(),
+        dns_cache: preset.dns_cache.clone(),
         dry_run: preset.dry_run.unwrap_or(false),
+        dump_certs: preset.dump_certs.clone(),
+        duration: preset.duration.clone(),
+        each: preset.each.clone(),
+        expect_body_file: preset.expect_body_file.clone(),
+        explicit_settings: ExplicitSettings {
+            timeout: preset.timeout.is_some(),
+            retry: preset.retry.is_some(),
+            retry_delay: preset.retry_delay.is_some(),
+        },
+        export_shell: preset.export_shell.clone(),
         form_data: preset.form_data.clone(),
         form: preset.form.clone(),
+        format: preset.format.clone(),
+        globoff: preset.globoff.unwrap_or(false),
+        fail_fast: preset.fail_fast.unwrap_or(false),
+        force_token_refresh: false,
+        keep_going: preset.keep_going.unwrap_or(false),
+        max_format_size: preset.max_format_size.clone(),
+        max_time: preset.max_time.clone(),
+        max_time_assert: preset.max_time_assert.clone(),
+        max_wait: preset.max_wait.clone(),
+        meta_format: preset.meta_format.clone(),
         headers: preset.headers.clone(),
+        hide_headers: preset.hide_headers.clone(),
+        hosts: None,
+        hsts_file: preset.hsts_file.clone(),
         json: preset.json.clone(),
         json_filter: preset.json_filter.clone(),
+        jwt: preset.jwt.clone(),
+        key: preset.key.clone(),
         method: preset
             .method
             .clone()
             .unwrap_or_else(|| DEFAULT_METHOD.to_string()),
+        ndjson: preset.ndjson.clone(),
+        ndjson_response: preset.ndjson_response.unwrap_or(false),
+        no_hsts: preset.no_hsts.unwrap_or(false),
+        no_input: preset.no_input.unwrap_or(false),
+        no_pager: preset.no_pager.unwrap_or(false),
+        no_progress: preset.no_progress.unwrap_or(false),
+        oauth2: preset.oauth2.clone(),
+        #[cfg(feature = "otel")]
+        otel_endpoint: preset.otel_endpoint.clone(),
+        open: preset.open.unwrap_or(false),
         output: preset.output.clone(),
+        output_append: preset.output_append.unwrap_or(false),
+        output_order: preset.output_order.clone(),
+        output_rotate: preset.output_rotate.clone(),
+        summary_format: preset.summary_format.clone(),
+        output_template: preset.output_template.clone(),
+        params: preset.params.clone(),
+        poll: preset.poll.clone(),
+        post301: preset.post301.unwrap_or(false),
+        post302: preset.post302.unwrap_or(false),
+        post303: preset.post303.unwrap_or(false),
         pretty_json: preset.pretty_json.unwrap_or(false),
         proxy: preset.proxy.clone(),
+        proxy_rules: None,
+        query: preset.query.clone(),
+        // silent = true は -qq と同じ（以前の設定ファイルとの互換）
+        quiet: preset.quiet.unwrap_or(if preset.silent == Some(true) { QUIET_BODY } else { 0 }),
+        range: preset.range.clone(),
+        remote_name: preset.remote_name.unwrap_or(false),
+        report_interval: preset.report_interval.clone(),
         retry: preset.retry.unwrap_or(DEFAULT_RETRY_COUNT),
+        retry_if_body: preset.retry_if_body.clone(),
+        rps: preset.rps,
         retry_delay: preset.retry_delay.unwrap_or(DEFAULT_RETRY_DELAY),
-        silent: preset.silent.unwrap_or(false),
+        secret_vars: preset.secret_vars.clone(),
+        show_cookies: preset.show_cookies.unwrap_or(false),
+        show_diff: preset.show_diff.unwrap_or(false),
+        show_headers: preset.show_headers.clone(),
+        tcp_keepalive: preset.tcp_keepalive,
+        tcp_nodelay: preset.tcp_nodelay,
         timeout: preset.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
         timing: preset.timing.unwrap_or(false),
+        timing_waterfall: preset.timing_waterfall.unwrap_or(false),
+        tls_keylog: preset.tls_keylog.clone(),
+        traceparent: preset.traceparent.clone(),
+        tracestate: preset.tracestate.clone(),
+        transforms: preset.transforms.clone(),
+        until: preset.until.clone(),
+        update_golden: preset.update_golden.unwrap_or(false),
         url: preset.url.clone().unwrap_or_default(),
+        url_file: preset.url_file.clone(),
+        vars: preset.vars.clone(),
         verbose: preset.verbose.unwrap_or(false),
+        view: preset.view.unwrap_or(false),
+        warmup: preset.warmup.clone(),
+        warn_size: preset.warn_size.clone(),
+        watch: preset.watch.clone(),
+        yaml: preset.yaml.clone(),
+        yes: preset.yes.unwrap_or(false),
+    };
+
+    if let Some(environment) = environment {
+        environment.apply(&mut config);
     }
+
+    config
 }
 
-/// HTTPリクエストを実行
+/// HTTPリクエストを実行（URLリスト・グロブの各URLで同じクライアントを使用）
 pub fn execute_request(config: Config) -> Result<(), Box<dyn Error>> {
-    let request_context = create_request_context(&config)?;
+    execute_request_with(config, default_signers(), default_observers())
+}
+
+/// 指定した署名を順に適用してHTTPリクエストを実行
+pub fn execute_request_with_signers(
+    config: Config,
+    signers: Vec<Box<dyn RequestSigner>>,
+) -> Result<(), Box<dyn Error>> {
+    execute_request_with(config, signers, default_observers())
+}
 
-    display_request_info(&config, &request_context);
+/// 指定した署名とオブザーバーを使用してHTTPリクエストを実行
+pub fn execute_request_with(
+    config: Config,
+    signers: Vec<Box<dyn RequestSigner>>,
+    observers: Vec<Box<dyn ResponseObserver>>,
+) -> Result<(), Box<dyn Error>> {
+    let summary_format = parse_summary_format(config.summary_format.as_deref())?;
+    parse_output_format(config.format.as_deref())?;
+    parse_meta_format(config.meta_format.as_deref())?;
+    config
+        .output_rotate
+        .as_deref()
+        .map(parse_output_rotate)
+        .transpose()?;
+    config.max_time_assert.as_deref().map(parse_duration).transpose()?;
+    config.attempt_timeout.as_deref().map(parse_duration).transpose()?;
+    config.max_time.as_deref().map(parse_duration).transpose()?;
+    config.range.as_deref().map(range_header_value).transpose()?;
+    validate_header_assertions(&config)?;
+    validate_shell_exports(&config)?;
+    validate_aws_sigv4(&config)?;
+    config.retry_if_body.as_deref().map(Condition::parse).transpose()?;
+    config
+        .warn_size
+        .as_deref()
+        .map(|size| parse_size(size, WARN_SIZE_OPTION))
+        .transpose()?;
+    max_format_size(&config)?;
+    if config.timing_waterfall && config.concurrency.is_some_and(|concurrency| concurrency > 1) {
+        return Err(ERROR_WATERFALL_CONCURRENCY.into());
+    }
+    let client_context = create_http_client(&config, signers, observers)?;
+    let mut dispatcher = RequestDispatcher::new(&config, &client_context)?;
+    let run_start = Instant::now();
+
+    let result = dispatch_requests(&client_context, &config, &mut dispatcher)
+        .and_then(|()| dispatcher.finish());
+    let summaries = &dispatcher.summaries;
+
+    // 複数リクエストの実行後に結果をまとめて表示（-q 時は形式の指定がある場合のみ）
+    if summaries.len() > 1 && (shows_meta(&config) || config.summary_format.is_some()) {
+        display_run_summary(
+            summaries,
+            run_start.elapsed(),
+            &client_context.connection_stats.summary(),
+            summary_format,
+        )?;
+    }
+    result?;
+
+    let failed = summaries.iter().filter(|summary| !summary.succeeded()).count();
+    if config.keep_going && failed > 0 {
+        return Err(ERROR_REQUESTS_FAILED
+            .replace("{1}", &failed.to_string())
+            .replace("{2}", &summaries.len().to_string())
+            .into());
+    }
 
-    if config.dry_run {
-        return Ok(());
+    Ok(())
+}
+
+/// URLリスト・グロブを展開して各URLのリクエストを送信
+fn dispatch_requests(
+    client_context: &ClientContext,
+    config: &Config,
+    dispatcher: &mut RequestDispatcher,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(url_source) = url_list_source(config) {
+        return execute_url_list(client_context, config, url_source, dispatcher);
     }
 
-    let (response_info, response_body, timing_info) = execute_request_with_retry(
-        &request_context.client,
-        request_context.request,
-        &config,
-    )?;
+    let glob_urls = if config.globoff {
+        vec![GlobUrl {
+            url: config.url.clone(),
+            values: Vec::new(),
+        }]
+    } else {
+        expand_url_glob(&config.url)?
+    };
 
-    handle_response(response_info, response_body, timing_info, &config)?;
+    for (index, glob_url) in glob_urls.into_iter().enumerate() {
+        if is_interrupted() {
+            break;
+        }
+        let url_config = config_for_url(config, glob_url.url, index + 1, &glob_url.values);
+        execute_single_request(client_context, url_config, dispatcher)?;
+    }
 
     Ok(())
 }
 
-/// HTTPクライアントとリクエストを作成
-fn create_request_context(config: &Config) -> Result<RequestContext, Box<dyn Error>> {
-    let (client, default_headers) = create_http_client(config)?;
-    let request = build_request(&client, config)?;
-
-    Ok(RequestContext {
-        client,
-        request,
-        default_headers,
-    })
+/// URLごとのリクエスト設定（出力ファイル名の #N・{host}・{index} などを置換）
+fn config_for_url(config: &Config, url: String, index: usize, glob_values: &[String]) -> Config {
+    let mut url_config = config.clone();
+    url_config.output = config.output.as_deref().map(|output| {
+        render_output_path(
+            &apply_glob_placeholders(output, glob_values),
+            &url,
+            index,
+            SystemTime::now(),
+        )
+    });
+    url_config.url = url;
+    url_config
 }
 
-/// HTTPクライアントを作成
-fn create_http_client(
-    config: &Config,
-) -> Result<(Client, reqwest::header::HeaderMap), Box<dyn Error>> {
-    let mut client_builder = Client::builder()
-        .timeout(Duration::from_secs(config.timeout))
-        .user_agent(USER_AGENT);
+impl RequestDispatcher {
+    /// 並列数が2以上ならワーカープールを起動（ドライランは常に順次）
+    fn new(config: &Config, client_context: &ClientContext) -> Result<Self, Box<dyn Error>> {
+        let pool = match config.concurrency {
+            Some(concurrency) if concurrency > 1 && !config.dry_run => Some(WorkerPool::start(
+                concurrency,
+                is_ordered_output(config)?,
+                client_context.default_headers.clone(),
+            )),
+            _ => None,
+        };
 
-    let mut default_headers = reqwest::header::HeaderMap::new();
-    default_headers.insert(reqwest::header::USER_AGENT, USER_AGENT.parse().unwrap());
+        Ok(RequestDispatcher {
+            pool,
+            summaries: Vec::new(),
+        })
+    }
 
-    client_builder = setup_proxy(client_builder, config)?;
-    client_builder = setup_cookies(client_builder, config)?;
-    let (client_builder, headers) = setup_default_headers(client_builder, config, default_headers)?;
+    /// リクエストを送信（ワーカープールの場合はキューに追加）
+    fn dispatch(&mut self, request_context: RequestContext, config: &Config) -> Result<(), Box<dyn Error>> {
+        match &mut self.pool {
+            None => send_request(request_context, config, &mut self.summaries),
+            Some(pool) => pool.submit(request_context, config, &mut self.summaries),
+        }
+    }
+
+    /// 送信前に失敗したリクエストを記録
+    fn fail(&mut self, config: &Config, error: String) -> Result<(), Box<dyn Error>> {
+        record_failure(&mut self.summaries, config, error)
+    }
 
-    Ok((client_builder.build()?, headers))
+    /// 送信中のリクエストの完了を待って結果を出力
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.pool.take() {
+            None => Ok(()),
+            Some(pool) => pool.finish(&mut self.summaries),
+        }
+    }
 }
 
-/// プロキシ設定を適用
-fn setup_proxy(
-    mut client_builder: reqwest::blocking::ClientBuilder,
-    config: &Config,
-) -> Result<reqwest::blocking::ClientBuilder, Box<dyn Error>> {
-    if let Some(proxy_config) = &config.proxy {
-        let proxy_url = format!("https://{}:{}", proxy_config.host, proxy_config.port);
-        let mut http_proxy = reqwest::Proxy::http(proxy_url)?;
+impl WorkerPool {
+    /// ワーカースレッドを起動（キューの長さは並列数まで）
+    fn start(concurrency: usize, ordered: bool, default_headers: reqwest::header::HeaderMap) -> Self {
+        let (job_sender, job_receiver) = mpsc::sync_channel::<RequestJob>(concurrency);
+        let (result_sender, result_receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..concurrency)
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+                thread::spawn(move || run_worker(&job_receiver, &result_sender))
+            })
+            .collect();
+
+        WorkerPool {
+            job_sender: Some(job_sender),
+            result_receiver,
+            workers,
+            output: PoolOutput {
+                ordered,
+                next_index: 0,
+                pending: BTreeMap::new(),
+                default_headers,
+            },
+            submitted: 0,
+        }
+    }
 
-        if let (Some(proxy_user), Some(proxy_pass)) = (&proxy_config.user, &proxy_config.pass) {
-            http_proxy = http_proxy.basic_auth(proxy_user, proxy_pass);
+    /// リクエストをキューに追加し、完了済みの結果を出力
+    fn submit(
+        &mut self,
+        request_context: RequestContext,
+        config: &Config,
+        summaries: &mut Vec<RequestSummary>,
+    ) -> Result<(), Box<dyn Error>> {
+        let job = RequestJob {
+            index: self.submitted,
+            request_context,
+            config: config.clone(),
+        };
+        self.submitted += 1;
+
+        self.job_sender
+            .as_ref()
+            .ok_or(ERROR_WORKER_STOPPED)?
+            .send(job)
+            .map_err(|_| ERROR_WORKER_STOPPED)?;
+
+        for (index, completed) in self.result_receiver.try_iter() {
+            self.output.push(index, completed, summaries)?;
         }
 
-        client_builder = client_builder.proxy(http_proxy);
+        Ok(())
     }
 
-    Ok(client_builder)
-}
+    /// キューを閉じて残りの結果をすべて出力
+    fn finish(mut self, summaries: &mut Vec<RequestSummary>) -> Result<(), Box<dyn Error>> {
+        self.job_sender = None;
 
-/// クッキー設定を適用
-fn setup_cookies(
-    mut client_builder: reqwest::blocking::ClientBuilder,
-    config: &Config,
-) -> Result<reqwest::blocking::ClientBuilder, Box<dyn Error>> {
-    if let Some(cookie_list) = &config.cookies {
-        let cookie_jar = Jar::default();
-        let parsed_url = &Url::parse(&config.url)?;
+        for (index, completed) in self.result_receiver.iter() {
+            self.output.push(index, completed, summaries)?;
+        }
 
-        for cookie_str in cookie_list {
-            cookie_jar.add_cookie_str(cookie_str, parsed_url);
+        for worker in self.workers {
+            worker.join().map_err(|_| ERROR_WORKER_STOPPED)?;
         }
 
-        client_builder = client_builder.cookie_provider(Arc::new(cookie_jar));
+        Ok(())
     }
-
-    Ok(client_builder)
 }
 
-/// デフォルトヘッダーを設定
-fn setup_default_headers(
-    mut client_builder: reqwest::blocking::ClientBuilder,
-    config: &Config,
-    mut default_headers: reqwest::header::HeaderMap,
-) -> Result<
-    (
-        reqwest::blocking::ClientBuilder,
-        reqwest::header::HeaderMap,
-    ),
-    Box<dyn Error>,
-> {
-    if let Some(header_list) = &config.headers {
-        let mut header_map = reqwest::header::HeaderMap::new();
-
-        for header_entry in header_list {
-            if let Some((key, value)) = header_entry.split_once(':') {
-                if let Ok(header_name) = HeaderName::from_bytes(key.as_bytes()) {
-                    let header_value = value.trim();
-                    if let Ok(parsed_value) = header_value.parse() {
-                        header_map.insert(header_name.clone(), parsed_value);
-                        default_headers.insert(header_name, header_value.parse().unwrap());
-                    }
-                }
-            }
+impl PoolOutput {
+    /// 完了したリクエストを出力（ordered の場合は前のリクエストが揃うまで保留）
+    fn push(
+        &mut self,
+        index: usize,
+        completed: CompletedRequest,
+        summaries: &mut Vec<RequestSummary>,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.ordered {
+            return self.emit(completed, summaries);
         }
 
-        if !header_map.is_empty() {
-            client_builder = client_builder.default_headers(header_map);
+        self.pending.insert(index, completed);
+        while let Some(completed) = self.pending.remove(&self.next_index) {
+            self.next_index += 1;
+            self.emit(completed, summaries)?;
         }
+
+        Ok(())
     }
 
-    Ok((client_builder, default_headers))
+    /// リクエスト情報とレスポンスを表示
+    fn emit(
+        &self,
+        completed: CompletedRequest,
+        summaries: &mut Vec<RequestSummary>,
+    ) -> Result<(), Box<dyn Error>> {
+        display_request_info(
+            &completed.config,
+            &self.default_headers,
+            &completed.request_headers,
+        );
+        complete_request(completed.result, &completed.config, summaries)
+    }
 }
 
-/// HTTPリクエストを構築
-fn build_request(client: &Client, config: &Config) -> Result<reqwest::blocking::Request, Box<dyn Error>> {
-    let method = Method::from_bytes(config.method.as_bytes())?;
-    let mut request_builder = create_request_builder(client, &method, &config.url)?;
+/// ワーカースレッドの処理（キューが閉じられるまでリクエストを送信）
+fn run_worker(
+    job_receiver: &Mutex<Receiver<RequestJob>>,
+    result_sender: &Sender<(usize, CompletedRequest)>,
+) {
+    loop {
+        let job = match job_receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        let Ok(job) = job else {
+            return;
+        };
 
-    request_builder = apply_authentication(request_builder, config);
-    request_builder = apply_request_body(request_builder, config)?;
+        let request_headers = job.request_context.request.headers().clone();
+        let result = fetch_response(job.request_context, &job.config).map_err(|e| e.to_string());
+        let completed = CompletedRequest {
+            config: job.config,
+            request_headers,
+            result,
+        };
 
-    Ok(request_builder.build()?)
+        if result_sender.send((job.index, completed)).is_err() {
+            return;
+        }
+    }
 }
 
-/// リクエストビルダーを作成
-fn create_request_builder(
-    client: &Client,
-    method: &Method,
-    url: &str,
-) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
-    let request_builder = match *method {
-        Method::GET => client.get(url),
-        Method::POST => client.post(url),
-        Method::PUT => client.put(url),
-        Method::DELETE => client.delete(url),
-        Method::HEAD => client.head(url),
-        Method::PATCH => client.patch(url),
-        _ => return Err(ERROR_UNKNOWN_METHOD.into()),
-    };
-
-    Ok(request_builder)
+/// 並列実行時の出力順序（ordered: 送信順、completed: 完了順）
+fn is_ordered_output(config: &Config) -> Result<bool, Box<dyn Error>> {
+    match config.output_order.as_deref() {
+        None | Some(OUTPUT_ORDER_ORDERED) => Ok(true),
+        Some(OUTPUT_ORDER_COMPLETED) => Ok(false),
+        Some(output_order) => Err(ERROR_INVALID_OUTPUT_ORDER.replace("{}", output_order).into()),
+    }
 }
 
-/// 認証設定を適用
-fn apply_authentication(
-    mut request_builder: reqwest::blocking::RequestBuilder,
-    config: &Config,
-) -> reqwest::blocking::RequestBuilder {
-    if let Some(auth_config) = &config.basic_auth {
-        request_builder = request_builder.basic_auth(&auth_config.user, Some(&auth_config.pass));
+/// URLリストの読み込み元（`--url-file`、または `-u -` の標準入力）
+fn url_list_source(config: &Config) -> Option<&str> {
+    match &config.url_file {
+        Some(url_file) => Some(url_file),
+        None if config.url == STDIN_SOURCE => Some(STDIN_SOURCE),
+        None => None,
     }
-
-    request_builder
 }
 
-/// リクエストボディを適用
-fn apply_request_body(
-    mut request_builder: reqwest::blocking::RequestBuilder,
+/// URLリストを1行ずつ読み込みながら、各URLに同じ設定でリクエストを送信
+fn execute_url_list(
+    client_context: &ClientContext,
     config: &Config,
-) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
-    if let Some(form_data_body) = &config.form_data {
-        request_builder = request_builder
-            .header(CONTENT_TYPE, CONTENT_TYPE_FORM)
-            .body(form_data_body.clone());
-    } else if let Some(form_params) = &config.form {
-        let param_pairs = parse_form_params(form_params);
-        request_builder = request_builder
-            .header(CONTENT_TYPE, CONTENT_TYPE_FORM)
-            .form(&param_pairs);
-    } else if let Some(json_data) = &config.json {
-        request_builder = request_builder
-            .header(CONTENT_TYPE, CONTENT_TYPE_JSON)
-            .json(json_data);
-    }
+    url_source: &str,
+    dispatcher: &mut RequestDispatcher,
+) -> Result<(), Box<dyn Error>> {
+    let reader: Box<dyn BufRead> = if url_source == STDIN_SOURCE {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(url_source)?))
+    };
 
-    Ok(request_builder)
-}
+    let mut index: usize = 0;
 
-/// フォームパラメータを解析
-fn parse_form_params(form_params: &[String]) -> Vec<(String, String)> {
-    form_params
-        .iter()
-        .filter_map(|param| {
-            param
-                .split_once('=')
-                .map(|(key, value)| (key.to_string(), value.to_string()))
-        })
-        .collect()
-}
+    for line in reader.lines() {
+        let line = line?;
+        let url = line.trim();
+        if url.is_empty() || url.starts_with(URL_LIST_COMMENT_PREFIX) {
+            continue;
+        }
 
-/// リクエスト情報を表示
-fn display_request_info(config: &Config, context: &RequestContext) {
-    if !config.verbose {
-        return;
+        if is_interrupted() {
+            break;
+        }
+        index += 1;
+        let url_config = config_for_url(config, url.to_string(), index, &[]);
+        execute_single_request(client_context, url_config, dispatcher)?;
     }
 
-    println!("> {} {}", config.method, config.url);
+    Ok(())
+}
 
-    for (name, value) in &context.default_headers {
-        let display_value = if name == reqwest::header::AUTHORIZATION {
-            BASIC_AUTH_PLACEHOLDER
-        } else {
-            value.to_str().unwrap_or("<binary>")
-        };
-        println!("> {}: {}", name, display_value);
+/// 1つのURLに対してHTTPリクエストを実行
+fn execute_single_request(
+    client_context: &ClientContext,
+    mut config: Config,
+    dispatcher: &mut RequestDispatcher,
+) -> Result<(), Box<dyn Error>> {
+    config.url = match to_ascii_url(&config.url).and_then(|url| merge_query(&url, &config)) {
+        Ok(url) => url,
+        Err(e) => return dispatcher.fail(&config, e.to_string()),
+    };
+    apply_hsts_upgrade(&mut config);
+    apply_host_defaults(&mut config);
+
+    // --rps では同じリクエストを一定の到着レートで送り続ける
+    if config.rps.is_some() && !config.dry_run {
+        add_request_cookies(client_context, &config)?;
+        return run_load_test(
+            &client_context.client,
+            || build_request(client_context, &config),
+            &client_context.connection_stats,
+            &config,
+        );
     }
 
-    for (name, value) in context.request.headers() {
-        if !context.default_headers.contains_key(name) {
-            println!("> {}: {}", name, value.to_str().unwrap_or("<binary>"));
-        }
+    // --watch では同じリクエストを一定間隔で送り続ける
+    if let (Some(interval), false) = (&config.watch, config.dry_run) {
+        add_request_cookies(client_context, &config)?;
+        return watch_request(client_context, &config, parse_duration(interval)?);
     }
 
-    println!();
-}
+    // --until では条件を満たすまで同じリクエストを繰り返す
+    if let (Some(until), false) = (&config.until, config.dry_run) {
+        let condition = Condition::parse(until)?;
+        add_request_cookies(client_context, &config)?;
+        return poll_until(client_context, &config, &condition);
+    }
+
+    if let (Some(ndjson_source), Some(batch_size)) = (&config.ndjson, config.batch_size) {
+        add_request_cookies(client_context, &config)?;
+        return execute_ndjson_batches(client_context, &config, ndjson_source, batch_size, dispatcher);
+    }
+
+    match add_request_cookies(client_context, &config)
+        .and_then(|()| create_request_context(client_context, &config))
+    {
+        Ok(request_context) => dispatcher.dispatch(request_context, &config),
+        Err(e) => dispatcher.fail(&config, e.to_string()),
+    }
+}
+
+/// 指定されたクッキー（クッキーで送るAPIキーを含む）をリクエスト先のURLに対して登録
+fn add_request_cookies(client_context: &ClientContext, config: &Config) -> Result<(), Box<dyn Error>> {
+    let Some(cookie_jar) = &client_context.cookie_jar else {
+        return Ok(());
+    };
+    let parsed_url = Url::parse(&config.url)?;
+
+    for cookie_str in config.cookies.iter().flatten() {
+        cookie_jar.add_cookie_str(cookie_str, &parsed_url);
+    }
+
+    if let Some(api_key) = &config.api_key {
+        if let (API_KEY_LOCATION_COOKIE, name) = parse_api_key_in(config.api_key_in.as_deref())? {
+            cookie_jar.add_cookie_str(&format!("{}={}", name, api_key), &parsed_url);
+        }
+    }
+
+    Ok(())
+}
+
+/// リクエストを送信してレスポンスを処理
+fn send_request(
+    request_context: RequestContext,
+    config: &Config,
+    summaries: &mut Vec<RequestSummary>,
+) -> Result<(), Box<dyn Error>> {
+    display_request_info(
+        config,
+        &request_context.default_headers,
+        request_context.request.headers(),
+    );
+
+    if config.dry_run {
+        // -v では送信内容と一緒に表示済み
+        if !config.verbose && shows_meta(config) {
+            eprintln!(
+                "{}",
+                DRY_RUN_MSG
+                    .replace("{1}", config.method.as_str())
+                    .replace("{2}", &config.url)
+            );
+        }
+        return Ok(());
+    }
+
+    let result = fetch_response(request_context, config).map_err(|e| e.to_string());
+    complete_request(result, config, summaries)
+}
+
+/// 実行結果を記録してレスポンスを処理（--fail-fast では4xx/5xxでもエラーを返す）
+fn complete_request(
+    result: Result<FetchedResponse, String>,
+    config: &Config,
+    summaries: &mut Vec<RequestSummary>,
+) -> Result<(), Box<dyn Error>> {
+    let fetched_response = match result {
+        Ok(fetched_response) => fetched_response,
+        Err(error) => return record_failure(summaries, config, error),
+    };
+
+    let status = fetched_response.response_info.status();
+    let latency_error = check_max_time(config, fetched_response.timing_info.total_time)?;
+    summaries.push(RequestSummary {
+        url: config.url.clone(),
+        status: Some(status.as_u16()),
+        duration: fetched_response.timing_info.total_time,
+        size: fetched_response.response_body.len(),
+        retries: fetched_response.timing_info.attempts.len().saturating_sub(1),
+        error: latency_error.clone(),
+    });
+    process_response(fetched_response, config)?;
+
+    if config.fail_fast && (status.is_client_error() || status.is_server_error()) {
+        return Err(ERROR_REQUEST_FAILED
+            .replace("{1}", &config.url)
+            .replace("{2}", &status.as_u16().to_string())
+            .into());
+    }
+
+    match latency_error {
+        Some(error) => report_failure(config, error),
+        None => Ok(()),
+    }
+}
+
+/// 所要時間が --max-time-assert の上限を超えていればエラーメッセージを返す
+fn check_max_time(config: &Config, total_time: Duration) -> Result<Option<String>, Box<dyn Error>> {
+    let max_time = match &config.max_time_assert {
+        Some(max_time) => max_time,
+        None => return Ok(None),
+    };
+
+    if total_time <= parse_duration(max_time)? {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        ERROR_MAX_TIME_EXCEEDED
+            .replace("{1}", &config.url)
+            .replace("{2}", &format!("{:.2?}", total_time))
+            .replace("{3}", max_time),
+    ))
+}
+
+/// 失敗したリクエストを記録（--keep-going の場合はエラーを表示して続行）
+fn record_failure(
+    summaries: &mut Vec<RequestSummary>,
+    config: &Config,
+    error: String,
+) -> Result<(), Box<dyn Error>> {
+    summaries.push(RequestSummary {
+        url: config.url.clone(),
+        status: None,
+        duration: Duration::ZERO,
+        size: 0,
+        retries: 0,
+        error: Some(error.clone()),
+    });
+
+    report_failure(config, error)
+}
+
+/// リクエストの失敗を通知（--keep-going の場合はエラーを表示して続行）
+fn report_failure(config: &Config, error: String) -> Result<(), Box<dyn Error>> {
+    if !config.keep_going {
+        return Err(error.into());
+    }
+
+    eprintln!(
+        "{}",
+        REQUEST_FAILED_MSG
+            .replace("{1}", &config.url)
+            .replace("{2}", &error)
+    );
+    Ok(())
+}
+
+/// リクエストを送信してレスポンスを受信
+fn fetch_response(
+    request_context: RequestContext,
+    config: &Config,
+) -> Result<FetchedResponse, Box<dyn Error>> {
+    let request_url = request_context.request.url().clone();
+    #[cfg(feature = "otel")]
+    let (traceparent, started) = (
+        request_context.request.headers().get(TRACEPARENT_HEADER).cloned(),
+        SystemTime::now(),
+    );
+
+    let result = execute_request_with_retry(
+        &request_context.client,
+        request_context.request,
+        config,
+        request_context.phase_recorder.as_ref(),
+        &request_context.connection_stats,
+        &request_context.observers,
+    );
+
+    // --otel-endpoint では成功・失敗にかかわらずスパンを送信
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &config.otel_endpoint {
+        let traceparent = traceparent.as_ref().and_then(|value| value.to_str().ok());
+        export_request_span(endpoint, config, traceparent, started, &result);
+    }
+    let (response_info, response_body, timing_info) = result?;
+
+    Ok(FetchedResponse {
+        request_url,
+        certificate_chain: request_context.certificate_chain,
+        observers: request_context.observers,
+        response_info,
+        response_body,
+        timing_info,
+    })
+}
+
+/// --watch の間隔で同じリクエストを繰り返し送信（失敗は表示して続行、--changes-only では変更時のみ出力）
+///
+/// --duration を指定した場合はその時間で終了し、--report-interval ごとに件数・失敗数を集計して表示する。
+fn watch_request(
+    client_context: &ClientContext,
+    config: &Config,
+    interval: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let duration = config.duration.as_deref().map(parse_duration).transpose()?;
+    let report_interval = parse_report_interval(config)?;
+    let mut change_tracker = ChangeTracker::new(config.show_diff);
+    let mut watch_stats = WatchStats::default();
+    let started = Instant::now();
+    let mut last_report = started;
+
+    loop {
+        let iteration_start = Instant::now();
+        let mut failed_status = false;
+        let result = create_request_context(client_context, config).and_then(|request_context| {
+            display_request_info(
+                config,
+                &request_context.default_headers,
+                request_context.request.headers(),
+            );
+            let fetched_response = fetch_response(request_context, config)?;
+            let status = fetched_response.response_info.status;
+            failed_status = status.is_client_error() || status.is_server_error();
+
+            if config.changes_only || config.show_diff {
+                let processed_response = format_body(
+                    &fetched_response.response_info,
+                    &fetched_response.response_body,
+                    config,
+                )?;
+                let changed = change_tracker.record(&processed_response);
+                if config.changes_only && !changed {
+                    return Ok(());
+                }
+            }
+            process_response(fetched_response, config)
+        });
+
+        watch_stats.record(iteration_start.elapsed(), result.is_err() || failed_status);
+        if let Err(e) = result {
+            eprintln!(
+                "{}",
+                REQUEST_FAILED_MSG
+                    .replace("{1}", &config.url)
+                    .replace("{2}", &e.to_string())
+            );
+        }
+        if report_interval.is_some_and(|report_interval| last_report.elapsed() >= report_interval) {
+            watch_stats.report(started.elapsed());
+            last_report = Instant::now();
+        }
+
+        // 次の送信が --duration を過ぎる場合は待たずに終了
+        let wait = interval.saturating_sub(iteration_start.elapsed());
+        if duration.is_some_and(|duration| started.elapsed() + wait >= duration)
+            || !sleep_unless_interrupted(wait)
+        {
+            if report_interval.is_some() {
+                watch_stats.report(started.elapsed());
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// --until の条件を満たすまで --poll の間隔でリクエストを繰り返し、最後のレスポンスだけを出力
+///
+/// 送信の失敗は表示して続行し、--max-wait を超えた場合はエラーを返す。
+fn poll_until(
+    client_context: &ClientContext,
+    config: &Config,
+    condition: &Condition,
+) -> Result<(), Box<dyn Error>> {
+    let interval = parse_duration(config.poll.as_deref().unwrap_or(DEFAULT_POLL_INTERVAL))?;
+    let max_wait = config.max_wait.as_deref().map(parse_duration).transpose()?;
+    let started = Instant::now();
+
+    loop {
+        let iteration_start = Instant::now();
+        let result = create_request_context(client_context, config).and_then(|request_context| {
+            display_request_info(
+                config,
+                &request_context.default_headers,
+                request_context.request.headers(),
+            );
+            fetch_response(request_context, config)
+        });
+
+        let last_result = match result {
+            Ok(fetched_response) => {
+                let response_info = &fetched_response.response_info;
+                if condition.evaluate(
+                    response_info.status.as_u16(),
+                    &response_info.headers,
+                    &fetched_response.response_body,
+                ) {
+                    return process_response(fetched_response, config);
+                }
+                format!("HTTP {}", response_info.status.as_u16())
+            }
+            Err(e) => e.to_string(),
+        };
+
+        let elapsed = started.elapsed();
+        if let Some(max_wait) = max_wait.filter(|max_wait| elapsed >= *max_wait) {
+            return Err(ERROR_UNTIL_TIMED_OUT
+                .replace("{1}", config.until.as_deref().unwrap_or_default())
+                .replace("{2}", &humantime::format_duration(max_wait).to_string())
+                .replace("{3}", &last_result)
+                .into());
+        }
+
+        if shows_meta(config) {
+            eprintln!(
+                "{}",
+                UNTIL_WAITING_MSG
+                    .replace("{1}", &last_result)
+                    .replace("{2}", &format!("{:.1?}", elapsed))
+            );
+        }
+        // 最後の試行が --max-wait の時点になるよう待ち時間を切り詰める
+        let mut delay = interval.saturating_sub(iteration_start.elapsed());
+        if let Some(max_wait) = max_wait {
+            delay = delay.min(max_wait.saturating_sub(elapsed));
+        }
+        if !sleep_unless_interrupted(delay) {
+            return Ok(());
+        }
+    }
+}
+
+/// 受信したレスポンスを処理（HSTSの記録、証明書の保存、表示）
+fn process_response(fetched_response: FetchedResponse, config: &Config) -> Result<(), Box<dyn Error>> {
+    record_hsts(config, &fetched_response.request_url, &fetched_response.response_info);
+
+    if let Ok(chain) = fetched_response.certificate_chain.lock() {
+        if !chain.is_empty() {
+            for observer in fetched_response.observers.iter() {
+                observer.on_certificates(config, &chain);
+            }
+        }
+    }
+
+    if let Some(dump_path) = &config.dump_certs {
+        dump_certificate_chain(&fetched_response.certificate_chain, dump_path, shows_meta(config))?;
+    }
+
+    handle_response(
+        fetched_response.response_info,
+        fetched_response.response_body,
+        fetched_response.timing_info,
+        config,
+        &fetched_response.observers,
+    )
+}
+
+/// HSTSストアの保存先を取得（無効化されている場合はNone）
+fn hsts_path(config: &Config) -> Option<PathBuf> {
+    if config.no_hsts {
+        return None;
+    }
+
+    match &config.hsts_file {
+        Some(hsts_file) => Some(PathBuf::from(hsts_file)),
+        None => default_hsts_path(),
+    }
+}
+
+/// HSTS対象ホストへのリクエストをhttps://に変換
+fn apply_hsts_upgrade(config: &mut Config) {
+    let Some(path) = hsts_path(config) else {
+        return;
+    };
+
+    if let Some(upgraded_url) = upgrade_url(&config.url, &HstsStore::load(&path)) {
+        if shows_verbose_text(config) {
+            eprintln!("{}", HSTS_UPGRADE_MSG.replace("{}", &upgraded_url));
+        }
+        config.url = upgraded_url;
+    }
+}
+
+/// HTTPSレスポンスのStrict-Transport-SecurityヘッダーをHSTSストアに記録
+fn record_hsts(config: &Config, request_url: &Url, response_info: &ResponseInfo) {
+    let Some(path) = hsts_path(config) else {
+        return;
+    };
+
+    let (Some(host), Some(header_value)) = (
+        request_url.host_str(),
+        response_info
+            .headers()
+            .get(reqwest::header::STRICT_TRANSPORT_SECURITY)
+            .and_then(|value| value.to_str().ok()),
+    ) else {
+        return;
+    };
+
+    // 仕様上、HTTPS経由で受信したヘッダーのみ有効
+    if request_url.scheme() != HTTPS_SCHEME {
+        return;
+    }
+
+    if let Err(e) = record_hsts_header(&path, host, header_value) {
+        if shows_verbose_text(config) {
+            eprintln!("{}", HSTS_SAVE_ERROR_MSG.replace("{}", &e.to_string()));
+        }
+    }
+}
+
+/// NDJSONレコードをバッチに分割して繰り返し送信
+fn execute_ndjson_batches(
+    client_context: &ClientContext,
+    config: &Config,
+    ndjson_source: &str,
+    batch_size: usize,
+    dispatcher: &mut RequestDispatcher,
+) -> Result<(), Box<dyn Error>> {
+    let method = Method::from_bytes(config.method.as_bytes())?;
+    let mut reader = BufReader::new(open_body_reader(ndjson_source)?);
+    let mut batch_number: usize = 0;
+
+    while let Some((batch_body, record_count)) = read_ndjson_batch(&mut reader, batch_size)? {
+        batch_number += 1;
+
+        if shows_verbose_text(config) {
+            eprintln!(
+                "{}",
+                NDJSON_BATCH_MSG
+                    .replace("{1}", &batch_number.to_string())
+                    .replace("{2}", &record_count.to_string())
+            );
+        }
+
+        let request = prepare_request_builder(client_context, config, &method)?
+            .header(CONTENT_TYPE, CONTENT_TYPE_NDJSON)
+            .body(batch_body)
+            .build()?;
+        let request = sign_request(request, config, client_context)?;
+
+        let request_context = RequestContext {
+            client: client_context.client.clone(),
+            request,
+            default_headers: client_context.default_headers.clone(),
+            certificate_chain: client_context.certificate_chain.clone(),
+            phase_recorder: client_context.phase_recorder.clone(),
+            connection_stats: client_context.connection_stats.clone(),
+            observers: Arc::clone(&client_context.observers),
+        };
+        dispatcher.dispatch(request_context, config)?;
+    }
+
+    Ok(())
+}
+
+/// NDJSONレコードを最大batch_size件読み込む（空行は無視）
+fn read_ndjson_batch(
+    reader: &mut impl BufRead,
+    batch_size: usize,
+) -> Result<Option<(String, usize)>, Box<dyn Error>> {
+    let mut batch_body = String::new();
+    let mut record_count: usize = 0;
+    let mut line = String::new();
+
+    while record_count < batch_size {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let record = line.trim_end_matches(['\r', '\n']);
+        if record.trim().is_empty() {
+            continue;
+        }
+
+        batch_body.push_str(record);
+        batch_body.push('\n');
+        record_count += 1;
+    }
+
+    if record_count == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some((batch_body, record_count)))
+}
+
+/// 共有クライアントからリクエストを作成
+fn create_request_context(
+    client_context: &ClientContext,
+    config: &Config,
+) -> Result<RequestContext, Box<dyn Error>> {
+    let request = build_request(client_context, config)?;
+
+    Ok(RequestContext {
+        client: client_context.client.clone(),
+        request,
+        default_headers: client_context.default_headers.clone(),
+        certificate_chain: client_context.certificate_chain.clone(),
+        phase_recorder: client_context.phase_recorder.clone(),
+        connection_stats: client_context.connection_stats.clone(),
+        observers: Arc::clone(&client_context.observers),
+    })
+}
+
+/// トークンの取得や cors・audit・wait サブコマンドなど、補助的なリクエスト用のクライアントを作成
+///
+/// プロキシ（[proxy_rules] を含む）とクライアント証明書はリクエストと同じ設定を使う。
+/// --headers・クッキー・リダイレクトなど、送信するリクエスト固有の設定は含めない。
+pub fn create_helper_client(config: &Config) -> Result<Client, Box<dyn Error>> {
+    let client_builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout))
+        .user_agent(USER_AGENT);
+    let client_builder = setup_proxy(client_builder, config)?;
+    let (tls_config, _) =
+        build_recording_tls_config(load_client_identity(config)?, &ConnectionStats::new(false))?;
+
+    Ok(client_builder.use_preconfigured_tls(tls_config).build()?)
+}
+
+/// HTTPクライアントを作成
+fn create_http_client(
+    config: &Config,
+    signers: Vec<Box<dyn RequestSigner>>,
+    observers: Vec<Box<dyn ResponseObserver>>,
+) -> Result<ClientContext, Box<dyn Error>> {
+    let mut client_builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout))
+        .user_agent(USER_AGENT);
+
+    let mut default_headers = reqwest::header::HeaderMap::new();
+    default_headers.insert(reqwest::header::USER_AGENT, USER_AGENT.parse().unwrap());
+
+    // --timing-waterfall・--otel-endpoint では接続の各段階の時刻を記録
+    let phase_recorder = records_phases(config).then(PhaseRecorder::default);
+    if let Some(phase_recorder) = &phase_recorder {
+        client_builder = client_builder.connector_layer(ConnectTimingLayer::new(phase_recorder.clone()));
+    }
+    let connection_stats = ConnectionStats::new(shows_verbose_text(config));
+    client_builder = client_builder.connector_layer(ConnectionCountingLayer::new(connection_stats.clone()));
+
+    client_builder = setup_proxy(client_builder, config)?;
+    let (mut client_builder, cookie_jar) = setup_cookies(client_builder, config)?;
+    client_builder = setup_dns_cache(client_builder, config, phase_recorder.as_ref())?;
+    client_builder = setup_tcp_options(client_builder, config);
+    client_builder = setup_redirect_policy(client_builder, config);
+    let (client_builder, certificate_chain) =
+        setup_tls(client_builder, config, phase_recorder.as_ref(), &connection_stats)?;
+    let (client_builder, headers) = setup_default_headers(client_builder, config, default_headers)?;
+
+    Ok(ClientContext {
+        client: client_builder.build()?,
+        default_headers: headers,
+        certificate_chain,
+        phase_recorder,
+        connection_stats,
+        cookie_jar,
+        signers,
+        observers: observers.into(),
+    })
+}
+
+/// 接続の各段階の時刻を記録するか（--otel-endpoint は記録が混ざらない順次送信の場合のみ）
+fn records_phases(config: &Config) -> bool {
+    #[cfg(feature = "otel")]
+    if config.otel_endpoint.is_some() && config.concurrency.is_none_or(|concurrency| concurrency <= 1) {
+        return true;
+    }
+
+    config.timing_waterfall
+}
+
+/// TLS設定を適用（証明書チェーン・鍵・ハンドシェイクを記録できる設定を使用）
+fn setup_tls(
+    mut client_builder: reqwest::blocking::ClientBuilder,
+    config: &Config,
+    phase_recorder: Option<&PhaseRecorder>,
+    connection_stats: &ConnectionStats,
+) -> Result<(reqwest::blocking::ClientBuilder, CertificateChain), Box<dyn Error>> {
+    let key_log_path = config
+        .tls_keylog
+        .clone()
+        .or_else(|| std::env::var(SSLKEYLOGFILE_ENV).ok())
+        .filter(|path| !path.is_empty());
+
+    let (mut tls_config, certificate_chain) =
+        build_recording_tls_config(load_client_identity(config)?, connection_stats)?;
+    if let Some(key_log_path) = &key_log_path {
+        tls_config.key_log = open_key_log(key_log_path)?;
+    }
+
+    // セッションキャッシュを経由してハンドシェイクの回数（と開始時刻）を記録（再開の判定は検証器で行う）
+    let mut session_store: Arc<dyn rustls::client::ClientSessionStore> = Arc::new(HandshakeCounter::new(
+        Arc::new(rustls::client::ClientSessionMemoryCache::new(TLS_SESSION_CACHE_SIZE)),
+        connection_stats.clone(),
+    ));
+    if let Some(phase_recorder) = phase_recorder {
+        session_store = Arc::new(TlsStartRecorder::new(session_store, phase_recorder.clone()));
+    }
+    tls_config.resumption = rustls::client::Resumption::store(session_store);
+    client_builder = client_builder.use_preconfigured_tls(tls_config);
+
+    Ok((client_builder, certificate_chain))
+}
+
+/// プロキシ設定を適用
+///
+/// [proxy_rules] がある場合は接続先のホストに一致するルールのプロキシを使い、
+/// 一致しないホストには --proxy・プリセットの proxy を使う。
+fn setup_proxy(
+    mut client_builder: reqwest::blocking::ClientBuilder,
+    config: &Config,
+) -> Result<reqwest::blocking::ClientBuilder, Box<dyn Error>> {
+    if let Some(proxy_rules) = &config.proxy_rules {
+        let fallback = config.proxy.as_ref().map(proxy_rules_fallback).transpose()?;
+        return Ok(client_builder.proxy(build_rules_proxy(proxy_rules, fallback)?));
+    }
+
+    if let Some(proxy_config) = &config.proxy {
+        let proxy_url = proxy_url(proxy_config)?;
+        // http・https の両方をプロキシ経由にする（https は CONNECT でトンネリング）
+        let mut proxy = reqwest::Proxy::all(proxy_url)?;
+
+        if let (Some(proxy_user), Some(proxy_pass)) = (&proxy_config.user, &proxy_config.pass) {
+            proxy = proxy.basic_auth(proxy_user, proxy_pass);
+        }
+
+        client_builder = client_builder.proxy(proxy);
+    }
+
+    Ok(client_builder)
+}
+
+/// プロキシのURL（url の指定がなければ host・port から組み立てる）
+fn proxy_url(proxy_config: &ProxyConfig) -> Result<String, Box<dyn Error>> {
+    match &proxy_config.url {
+        Some(url) => Ok(url.clone()),
+        None if proxy_config.host.is_empty() => Err(ERROR_MISSING_PROXY_HOST.into()),
+        None => Ok(format!("{}{}:{}", HTTP_PROXY_SCHEME, proxy_config.host, proxy_config.port)),
+    }
+}
+
+/// [proxy_rules] に一致しないホストで使うプロキシ（認証情報はURLに含める）
+fn proxy_rules_fallback(proxy_config: &ProxyConfig) -> Result<Url, Box<dyn Error>> {
+    let mut url = Url::parse(&proxy_url(proxy_config)?)?;
+    if let (Some(proxy_user), Some(proxy_pass)) = (&proxy_config.user, &proxy_config.pass) {
+        url.set_username(proxy_user)
+            .and_then(|_| url.set_password(Some(proxy_pass)))
+            .map_err(|_| ERROR_MISSING_PROXY_HOST)?;
+    }
+    Ok(url)
+}
+
+/// クッキー設定を適用
+fn setup_cookies(
+    client_builder: reqwest::blocking::ClientBuilder,
+    config: &Config,
+) -> Result<(reqwest::blocking::ClientBuilder, Option<SharedCookieJar>), Box<dyn Error>> {
+    if !uses_cookie_jar(config) {
+        return Ok((client_builder, None));
+    }
+
+    let cookie_jar: SharedCookieJar = Arc::new(Jar::default());
+
+    // --cookies-from のクッキーは送信先に応じてJarが選ぶため、すべて登録しておく
+    if let Some(cookies_path) = &config.cookies_from {
+        let imported = import_cookie_file(&cookie_jar, cookies_path)?;
+        if shows_verbose_text(config) {
+            eprintln!(
+                "{}",
+                COOKIES_IMPORTED_MSG
+                    .replace("{1}", &imported.to_string())
+                    .replace("{2}", cookies_path)
+            );
+        }
+    }
+
+    Ok((
+        client_builder.cookie_provider(Arc::clone(&cookie_jar)),
+        Some(cookie_jar),
+    ))
+}
+
+/// クッキーJarを使うか（--cookies・--cookies-from）
+fn uses_cookie_jar(config: &Config) -> bool {
+    config.cookies.is_some() || config.cookies_from.is_some()
+}
+
+/// TCPソケットオプションと接続タイムアウトを適用
+fn setup_tcp_options(
+    mut client_builder: reqwest::blocking::ClientBuilder,
+    config: &Config,
+) -> reqwest::blocking::ClientBuilder {
+    if let Some(tcp_nodelay) = config.tcp_nodelay {
+        client_builder = client_builder.tcp_nodelay(tcp_nodelay);
+    }
+
+    if let Some(keepalive_secs) = config.tcp_keepalive {
+        client_builder = client_builder.tcp_keepalive(Duration::from_secs(keepalive_secs));
+    }
+
+    if let Some(connect_timeout_secs) = config.connect_timeout {
+        client_builder = client_builder.connect_timeout(Duration::from_secs(connect_timeout_secs));
+    }
+
+    client_builder
+}
+
+/// DNSキャッシュ設定を適用
+fn setup_dns_cache(
+    mut client_builder: reqwest::blocking::ClientBuilder,
+    config: &Config,
+    phase_recorder: Option<&PhaseRecorder>,
+) -> Result<reqwest::blocking::ClientBuilder, Box<dyn Error>> {
+    let caching_resolver = match &config.dns_cache {
+        Some(dns_cache_ttl) => Some(Arc::new(CachingResolver::new(
+            parse_duration(dns_cache_ttl)?,
+            shows_verbose_text(config),
+        ))),
+        None => None,
+    };
+
+    // --timing-waterfall では名前解決の時間を計測するリゾルバで包む
+    match (caching_resolver, phase_recorder) {
+        (caching_resolver, Some(phase_recorder)) => {
+            let inner = caching_resolver.map(|resolver| resolver as Arc<dyn Resolve>);
+            client_builder = client_builder
+                .dns_resolver(Arc::new(TimingResolver::new(inner, phase_recorder.clone())));
+        }
+        (Some(caching_resolver), None) => {
+            client_builder = client_builder.dns_resolver(caching_resolver);
+        }
+        (None, None) => {}
+    }
+
+    Ok(client_builder)
+}
+
+/// 期間指定（`60s`, `800ms`, `10m` など、単位なしは秒）を解析
+pub fn parse_duration(value: &str) -> Result<Duration, Box<dyn Error>> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<f64>() {
+        if seconds.is_finite() && seconds >= 0.0 {
+            return Ok(Duration::from_secs_f64(seconds));
+        }
+    }
+
+    humantime::parse_duration(value).map_err(|e| {
+        ERROR_INVALID_DURATION
+            .replace("{1}", value)
+            .replace("{2}", &e.to_string())
+            .into()
+    })
+}
+
+/// デフォルトヘッダーを設定
+fn setup_default_headers(
+    mut client_builder: reqwest::blocking::ClientBuilder,
+    config: &Config,
+    mut default_headers: reqwest::header::HeaderMap,
+) -> Result<
+    (
+        reqwest::blocking::ClientBuilder,
+        reqwest::header::HeaderMap,
+    ),
+    Box<dyn Error>,
+> {
+    if let Some(header_list) = &config.headers {
+        let mut header_map = reqwest::header::HeaderMap::new();
+
+        for header_entry in header_list {
+            if let Some((key, value)) = header_entry.split_once(':') {
+                if let Ok(header_name) = HeaderName::from_bytes(key.as_bytes()) {
+                    let header_value = value.trim();
+                    if let Ok(parsed_value) = header_value.parse() {
+                        header_map.insert(header_name.clone(), parsed_value);
+                        default_headers.insert(header_name, header_value.parse().unwrap());
+                    }
+                }
+            }
+        }
+
+        if !header_map.is_empty() {
+            client_builder = client_builder.default_headers(header_map);
+        }
+    }
+
+    Ok((client_builder, default_headers))
+}
+
+/// HTTPリクエストを構築
+fn build_request(
+    client_context: &ClientContext,
+    config: &Config,
+) -> Result<reqwest::blocking::Request, Box<dyn Error>> {
+    let method = Method::from_bytes(config.method.as_bytes())?;
+    let request_builder = prepare_request_builder(client_context, config, &method)?;
+    let request_builder = apply_request_body(request_builder, config)?;
+
+    sign_request(request_builder.build()?, config, client_context)
+}
+
+/// ボディ以外（ホストの既定値・認証・トレースコンテキスト・Range）を設定したリクエストビルダー
+fn prepare_request_builder(
+    client_context: &ClientContext,
+    config: &Config,
+    method: &Method,
+) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
+    let mut request_builder = create_request_builder(&client_context.client, method, &config.url)?;
+
+    request_builder = apply_host_request_defaults(request_builder, config);
+    request_builder = apply_authentication(request_builder, config)?;
+    request_builder = apply_trace_context(request_builder, config)?;
+    if let Some(range) = &config.range {
+        request_builder = request_builder.header(RANGE, range_header_value(range)?);
+    }
+
+    Ok(request_builder)
+}
+
+/// 組み立て済みのリクエストに署名を順に適用
+///
+/// クライアントの既定のヘッダー（--headers など）は送信時に追加されるため、
+/// 署名に含まれるよう先にリクエストへ反映しておく（リクエストにある同名のヘッダーが優先）。
+fn sign_request(
+    mut request: reqwest::blocking::Request,
+    config: &Config,
+    client_context: &ClientContext,
+) -> Result<reqwest::blocking::Request, Box<dyn Error>> {
+    for (name, value) in &client_context.default_headers {
+        if !request.headers().contains_key(name) {
+            request.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+
+    client_context
+        .signers
+        .iter()
+        .try_fold(request, |request, signer| signer.sign(request, config))
+}
+
+/// クエリパラメータを反映したURL（URLに同名のパラメータがある場合は置き換える）
+fn merge_query(url: &str, config: &Config) -> Result<String, Box<dyn Error>> {
+    let query = match &config.query {
+        Some(query) if !query.is_empty() => query,
+        _ => return Ok(url.to_string()),
+    };
+
+    let mut url = Url::parse(url)?;
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !query.contains_key(name.as_ref()))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(kept)
+        .extend_pairs(query);
+
+    Ok(url.to_string())
+}
+
+/// リクエストビルダーを作成
+fn create_request_builder(
+    client: &Client,
+    method: &Method,
+    url: &str,
+) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
+    let request_builder = match *method {
+        Method::GET => client.get(url),
+        Method::POST => client.post(url),
+        Method::PUT => client.put(url),
+        Method::DELETE => client.delete(url),
+        Method::HEAD => client.head(url),
+        Method::PATCH => client.patch(url),
+        _ => return Err(ERROR_UNKNOWN_METHOD.into()),
+    };
+
+    Ok(request_builder)
+}
+
+/// 認証設定を適用
+fn apply_authentication(
+    mut request_builder: reqwest::blocking::RequestBuilder,
+    config: &Config,
+) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
+    if let Some(auth_config) = &config.basic_auth {
+        request_builder = request_builder.basic_auth(&auth_config.user, Some(&auth_config.pass));
+    }
+
+    if let Some(api_key) = &config.api_key {
+        request_builder = apply_api_key(request_builder, api_key, config)?;
+    }
+
+    if let Some(bearer_token) = &config.bearer_token {
+        request_builder = request_builder.bearer_auth(bearer_token);
+    }
+
+    if let Some(oauth2_config) = oauth2_token_config(config) {
+        let access_token = resolve_access_token(oauth2_config, config, config.force_token_refresh)?
+            .ok_or(ERROR_NO_OAUTH2_TOKEN)?;
+        request_builder = request_builder.bearer_auth(access_token);
+    }
+
+    Ok(request_builder)
+}
+
+/// OAuth2のアクセストークンを付与する場合の設定
+///
+/// JWTは署名（JwtSigner）で付与するため、OAuth2トークンは[jwt]・--bearer がない場合のみ使用
+fn oauth2_token_config(config: &Config) -> Option<&OAuth2Config> {
+    match (&config.jwt, &config.bearer_token) {
+        (None, None) => config.oauth2.as_ref(),
+        _ => None,
+    }
+}
+
+/// OAuth2のアクセストークンが拒否された（401）リクエストを、新しいトークンで送り直せるようにする
+///
+/// 保存済みのトークンを期限切れにしてから取得し直し（リフレッシュトークン・client_credentials）、
+/// リクエストのAuthorizationヘッダーを置き換える。OAuth2のトークンを付与していない場合や
+/// 新しいトークンを取得できない場合は false を返す。
+pub fn reauthorize_request(
+    request: &mut reqwest::blocking::Request,
+    config: &Config,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(oauth2_config) = oauth2_token_config(config) else {
+        return Ok(false);
+    };
+    let Some(rejected_token) = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+        .map(str::to_string)
+    else {
+        return Ok(false);
+    };
+
+    invalidate_access_token(oauth2_config, &rejected_token)?;
+    match resolve_access_token(oauth2_config, config, false)? {
+        Some(access_token) if access_token != rejected_token => {
+            let mut authorization = HeaderValue::from_str(&format!("{}{}", BEARER_PREFIX, access_token))?;
+            authorization.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION, authorization);
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// APIキーを指定された場所（ヘッダー・クエリ・クッキー）に付与
+fn apply_api_key(
+    request_builder: reqwest::blocking::RequestBuilder,
+    api_key: &str,
+    config: &Config,
+) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
+    // -v・--dry-run の表示で値を伏せるため、ヘッダーは sensitive にする
+    let sensitive_value = |value: &str| -> Result<HeaderValue, Box<dyn Error>> {
+        let mut header_value = HeaderValue::from_str(value)?;
+        header_value.set_sensitive(true);
+        Ok(header_value)
+    };
+    match parse_api_key_in(config.api_key_in.as_deref())? {
+        (API_KEY_LOCATION_HEADER, name) => Ok(request_builder.header(name, sensitive_value(api_key)?)),
+        (API_KEY_LOCATION_QUERY, name) => Ok(request_builder.query(&[(name, api_key)])),
+        // Cookie ヘッダーを指定するとJarのクッキーが送られないため、Jarがある場合は add_request_cookies で登録する
+        (_, _) if uses_cookie_jar(config) => Ok(request_builder),
+        (_, name) => Ok(request_builder.header(COOKIE, sensitive_value(&format!("{}={}", name, api_key))?)),
+    }
+}
+
+/// APIキーの付与先（header:X-Api-Key など）を種類と名前に分解
+fn parse_api_key_in(api_key_in: Option<&str>) -> Result<(&str, &str), Box<dyn Error>> {
+    let location = api_key_in.unwrap_or(DEFAULT_API_KEY_IN);
+    let invalid_location = || ERROR_INVALID_API_KEY_IN.replace("{}", location);
+
+    let (kind, name) = location.split_once(':').ok_or_else(invalid_location)?;
+    let (kind, name) = (kind.trim(), name.trim());
+    let known_kind = [
+        API_KEY_LOCATION_HEADER,
+        API_KEY_LOCATION_QUERY,
+        API_KEY_LOCATION_COOKIE,
+    ]
+    .contains(&kind);
+    if !known_kind || name.is_empty() {
+        return Err(invalid_location().into());
+    }
+
+    Ok((kind, name))
+}
+
+/// リクエストボディを適用
+fn apply_request_body(
+    mut request_builder: reqwest::blocking::RequestBuilder,
+    config: &Config,
+) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
+    if let Some(form_data_body) = &config.form_data {
+        request_builder = request_builder
+            .header(CONTENT_TYPE, CONTENT_TYPE_FORM)
+            .body(form_data_body.clone());
+    } else if let Some(form_params) = &config.form {
+        let param_pairs = parse_form_params(form_params);
+        // 値が @path のフィールドがある場合はファイルを添付する multipart/form-data で送信
+        request_builder = if param_pairs
+            .iter()
+            .any(|(_, value)| value.starts_with(FILE_REFERENCE_PREFIX))
+        {
+            request_builder.multipart(multipart_form(param_pairs)?)
+        } else {
+            request_builder
+                .header(CONTENT_TYPE, CONTENT_TYPE_FORM)
+                .form(&param_pairs)
+        };
+    } else if let Some(json_data) = &config.json {
+        let json_value = parse_json_body(&read_body_source(json_data)?)?;
+        request_builder = request_builder
+            .header(CONTENT_TYPE, CONTENT_TYPE_JSON)
+            .json(&json_value);
+    } else if let Some(yaml_data) = &config.yaml {
+        let json_value = parse_yaml_body(&read_body_source(yaml_data)?)?;
+        request_builder = request_builder
+            .header(CONTENT_TYPE, CONTENT_TYPE_JSON)
+            .json(&json_value);
+    } else if let Some(ndjson_source) = &config.ndjson {
+        request_builder = request_builder
+            .header(CONTENT_TYPE, CONTENT_TYPE_NDJSON)
+            .body(Body::new(open_body_reader(ndjson_source)?));
+    } else if let Some(binary_source) = &config.data_binary {
+        // --content-type を優先し、なければ --headers で指定したContent-Typeを使用
+        if let Some(content_type) = &config.content_type {
+            request_builder = request_builder.header(CONTENT_TYPE, content_type);
+        } else if !has_header(config, CONTENT_TYPE.as_str()) {
+            request_builder = request_builder.header(CONTENT_TYPE, CONTENT_TYPE_OCTET_STREAM);
+        }
+        request_builder = request_builder.body(binary_body(binary_source, !shows_progress(config))?);
+    }
+
+    Ok(request_builder)
+}
+
+/// JSONボディを解析（JSON5/JSONC記法を厳密なJSONに正規化）
+fn parse_json_body(json_data: &str) -> Result<Value, Box<dyn Error>> {
+    json5::from_str::<Value>(json_data)
+        .map_err(|e| ERROR_INVALID_JSON_BODY.replace("{}", &e.to_string()).into())
+}
+
+/// YAMLボディを解析してJSONに変換
+fn parse_yaml_body(yaml_data: &str) -> Result<Value, Box<dyn Error>> {
+    serde_yaml::from_str::<Value>(yaml_data)
+        .map_err(|e| ERROR_INVALID_YAML_BODY.replace("{}", &e.to_string()).into())
+}
+
+/// ボディ指定を読み込む（`@path` の場合はファイルから読み込む）
+fn read_body_source(source: &str) -> Result<String, Box<dyn Error>> {
+    match source.strip_prefix(FILE_REFERENCE_PREFIX) {
+        Some(file_path) => {
+            let mut file = File::open(file_path)?;
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            Ok(contents)
+        }
+        None => Ok(source.to_string()),
+    }
+}
+
+/// ボディ指定をストリームとして開く（`@path` の場合はファイル、`@-` の場合は標準入力を逐次読み込む）
+fn open_body_reader(source: &str) -> Result<Box<dyn Read + Send>, Box<dyn Error>> {
+    match source.strip_prefix(FILE_REFERENCE_PREFIX) {
+        Some(STDIN_SOURCE) => Ok(Box::new(std::io::stdin())),
+        Some(file_path) => Ok(Box::new(File::open(file_path)?)),
+        None => Ok(Box::new(Cursor::new(source.as_bytes().to_vec()))),
+    }
+}
+
+/// バイナリボディを作成（ファイル・標準入力はメモリに読み込まず、送信したバイト数を表示しながら送る）
+fn binary_body(source: &str, silent: bool) -> Result<Body, Box<dyn Error>> {
+    match source.strip_prefix(FILE_REFERENCE_PREFIX) {
+        // 標準入力は長さが分からないためチャンク転送
+        Some(STDIN_SOURCE) => Ok(Body::new(UploadProgress::new(std::io::stdin(), silent))),
+        Some(file_path) => {
+            let file = File::open(file_path)?;
+            let length = file.metadata()?.len();
+            Ok(Body::sized(UploadProgress::new(file, silent), length))
+        }
+        None => Ok(Body::from(source.as_bytes().to_vec())),
+    }
+}
+
+/// multipart/form-data のボディを作成
+///
+/// `@path` のファイルはメモリに読み込まずに送信し、ファイル名とContent-Typeは拡張子から設定する。
+fn multipart_form(param_pairs: Vec<(String, String)>) -> Result<Form, Box<dyn Error>> {
+    param_pairs
+        .into_iter()
+        .try_fold(Form::new(), |form, (name, value)| {
+            match value.strip_prefix(FILE_REFERENCE_PREFIX) {
+                Some(file_path) => form.file(name, file_path).map_err(|e| {
+                    ERROR_FORM_FILE
+                        .replace("{1}", file_path)
+                        .replace("{2}", &e.to_string())
+                        .into()
+                }),
+                None => Ok(form.text(name, value)),
+            }
+        })
+}
+
+/// フォームパラメータを解析
+fn parse_form_params(form_params: &[String]) -> Vec<(String, String)> {
+    form_params
+        .iter()
+        .filter_map(|param| {
+            param
+                .split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// リクエスト情報を表示
+fn display_request_info(
+    config: &Config,
+    default_headers: &reqwest::header::HeaderMap,
+    request_headers: &reqwest::header::HeaderMap,
+) {
+    let json_meta = is_json_meta_format(config);
+    if !(config.verbose || json_meta) || !shows_meta(config) {
+        return;
+    }
+
+    let headers: Vec<(String, String)> = default_headers
+        .iter()
+        .chain(
+            request_headers
+                .iter()
+                .filter(|(name, _)| !default_headers.contains_key(*name)),
+        )
+        .filter(|(name, _)| is_header_visible(config, name.as_str()))
+        .map(|(name, value)| (name.to_string(), header_display_value(name, value)))
+        .collect();
+
+    // --meta-format json では1行のJSONとして表示
+    if json_meta {
+        eprintln!("{}", render_request_meta(config, &headers));
+        return;
+    }
+
+    display_idn_host(&config.url);
+    eprintln!("> {} {}", config.method, config.url);
+    for (name, value) in &headers {
+        eprintln!("> {}: {}", name, value);
+    }
+    eprintln!();
+}
+
+/// 表示用のヘッダー値
+///
+/// Authorizationは認証方式のみ、APIキーのクッキーは名前のみ残し、そのほかの sensitive な値
+/// （APIキーのヘッダー・セッショントークンなど）はすべて伏せる。
+fn header_display_value(name: &HeaderName, value: &HeaderValue) -> String {
+    let text = value.to_str().unwrap_or("<binary>");
+
+    if name == AUTHORIZATION {
+        return match text.split_once(' ') {
+            Some((scheme, _)) => format!("{} {}", scheme, CREDENTIALS_PLACEHOLDER),
+            None => CREDENTIALS_PLACEHOLDER.to_string(),
+        };
+    }
+    if !value.is_sensitive() {
+        return text.to_string();
+    }
+
+    match text.split_once('=').filter(|_| name == COOKIE) {
+        Some((cookie_name, _)) => format!("{}={}", cookie_name, CREDENTIALS_PLACEHOLDER),
+        None => CREDENTIALS_PLACEHOLDER.to_string(),
+    }
+}
 
 /// リトライ機能付きでリクエストを実行
 fn execute_request_with_retry(
     client: &Client,
     request: reqwest::blocking::Request,
     config: &Config,
+    phase_recorder: Option<&PhaseRecorder>,
+    connection_stats: &ConnectionStats,
+    observers: &[Box<dyn ResponseObserver>],
 ) -> Result<(ResponseInfo, String, TimingInfo), Box<dyn Error>> {
     let mut current_attempt: u32 = 0;
-    let max_attempts: u32 = config.retry + 1;
+    let mut max_attempts: u32 = config.retry + 1;
+    let mut connect_retries: u32 = 0;
+    let mut reauthorized = false;
     let overall_start = Instant::now();
+    let mut request = Some(request);
+    let mut attempts: Vec<AttemptInfo> = Vec::new();
+    let retry_condition = config.retry_if_body.as_deref().map(Condition::parse).transpose()?;
+    let per_attempt_timeout = config.attempt_timeout.as_deref().map(parse_duration).transpose()?;
+    let deadline = config.max_time.as_deref().map(parse_duration).transpose()?;
+    // 待機後の次の試行を --max-time 内に始められる場合のみ再試行する
+    let fits_deadline = |delay: Duration| {
+        deadline.is_none_or(|deadline| overall_start.elapsed() + delay < deadline)
+    };
 
     loop {
         current_attempt += 1;
+        // リトライの待機中に中断された場合は再送しない
+        if current_attempt > 1 && is_interrupted() {
+            return Err(ERROR_INTERRUPTED
+                .replace("{}", &(current_attempt - 1).to_string())
+                .into());
+        }
 
-        let retry_request = request.try_clone().ok_or(ERROR_REQUEST_CLONE)?;
+        // 複製できないリクエスト（ストリームボディ）は元のリクエストを一度だけ使用し、再送しない
+        let mut retry_request = match request.as_ref().and_then(|r| r.try_clone()) {
+            Some(cloned_request) => cloned_request,
+            None => request.take().ok_or(ERROR_REQUEST_CLONE)?,
+        };
+        let resendable = request.is_some();
+        if let Some(timeout) =
+            attempt_timeout(per_attempt_timeout, deadline, overall_start.elapsed())
+        {
+            *retry_request.timeout_mut() = Some(timeout);
+        }
 
-        if config.verbose && current_attempt > 1 {
-            println!(
-                "{}",
-                RETRY_ATTEMPT_PREFIX.replace("{}", &current_attempt.saturating_sub(1).to_string())
-            );
+        for observer in observers {
+            observer.on_attempt(config, current_attempt);
         }
 
+        if let Some(phase_recorder) = phase_recorder {
+            phase_recorder.reset();
+        }
         let request_start = Instant::now();
 
-        match client.execute(retry_request) {
-            Ok(response) => {
-                let status = response.status();
+        let result = execute_following_redirects(client, retry_request, config);
+        connection_stats.record_request(result.as_ref());
+        match result {
+            Ok(response) => {
+                let status = response.status();
+
+                // OAuth2のトークンが拒否された場合は取得し直して一度だけ再送（--retry の回数には含めない）
+                if status == reqwest::StatusCode::UNAUTHORIZED && !reauthorized {
+                    if let Some(original_request) = request.as_mut() {
+                        if reauthorize_request(original_request, config)? {
+                            reauthorized = true;
+                            max_attempts += 1;
+                            for observer in observers {
+                                observer.on_retry(config, &RetryReason::Unauthorized, Duration::ZERO);
+                            }
+                            attempts.push(AttemptInfo {
+                                number: current_attempt,
+                                outcome: format!("HTTP {}", status.as_u16()),
+                                duration: request_start.elapsed(),
+                                delay: Some(Duration::ZERO),
+                            });
+                            continue;
+                        }
+                    }
+                }
+
+                let backoff = retry_backoff(config, current_attempt);
+                if should_retry_for_status(status.as_u16())
+                    && resendable
+                    && current_attempt < max_attempts
+                    && fits_deadline(backoff)
+                {
+                    let duration = request_start.elapsed();
+                    let delay = handle_retry_delay(config, backoff, status, observers);
+                    attempts.push(AttemptInfo {
+                        number: current_attempt,
+                        outcome: format!("HTTP {}", status.as_u16()),
+                        duration,
+                        delay: Some(delay),
+                    });
+                    continue;
+                }
+
+                let (response_info, response_body, mut timing_info) = handle_successful_response(
+                    response,
+                    request_start,
+                    overall_start,
+                    current_attempt,
+                    std::mem::take(&mut attempts),
+                    config,
+                    observers,
+                )?;
+
+                // 2xxでもボディが --retry-if-body の条件に一致する場合（処理中など）は再試行
+                let retries_for_body = retry_condition.as_ref().is_some_and(|condition| {
+                    condition.evaluate(status.as_u16(), response_info.headers(), &response_body)
+                });
+                let backoff = retry_backoff(config, current_attempt);
+                // 中断で途中までしか受信していないボディは条件を評価せずにそのまま出力する
+                if retries_for_body
+                    && !is_interrupted()
+                    && resendable
+                    && current_attempt < max_attempts
+                    && fits_deadline(backoff)
+                {
+                    let delay = handle_body_retry_delay(config, backoff, status, observers);
+                    attempts = timing_info.attempts;
+                    if let Some(attempt) = attempts.last_mut() {
+                        attempt.outcome = BODY_RETRY_OUTCOME.replace("{}", &status.as_u16().to_string());
+                        attempt.delay = Some(delay);
+                    }
+                    continue;
+                }
+
+                timing_info.phases = phase_recorder.map(|phase_recorder| {
+                    phase_recorder.phases(timing_info.response_time, timing_info.body_read_time)
+                });
+                return Ok((response_info, response_body, timing_info));
+            }
+            Err(e) => {
+                // 接続エラーは --retry とは別枠で --connect-retry の回数だけ再試行
+                if e.is_connect() && connect_retries < config.connect_retry {
+                    connect_retries += 1;
+                    max_attempts += 1;
+                }
+
+                let backoff = retry_backoff(config, current_attempt);
+                if resendable && current_attempt < max_attempts && fits_deadline(backoff) {
+                    let duration = request_start.elapsed();
+                    let delay = handle_request_error_retry(config, backoff, &e, observers);
+                    attempts.push(AttemptInfo {
+                        number: current_attempt,
+                        outcome: e.to_string(),
+                        duration,
+                        delay: Some(delay),
+                    });
+                    continue;
+                }
+
+                // --max-time を使い切った場合は試行回数とともに報告
+                if let Some(max_time) = config.max_time.as_deref().filter(|_| !fits_deadline(backoff)) {
+                    return Err(ERROR_MAX_TIME_REACHED
+                        .replace("{1}", max_time)
+                        .replace("{2}", &current_attempt.to_string())
+                        .replace("{3}", &e.to_string())
+                        .into());
+                }
+                return Err(e.into());
+            }
+        }
+    }
+}
+
+/// 成功したレスポンスを処理
+fn handle_successful_response(
+    response: reqwest::blocking::Response,
+    request_start: Instant,
+    overall_start: Instant,
+    current_attempt: u32,
+    mut attempts: Vec<AttemptInfo>,
+    config: &Config,
+    observers: &[Box<dyn ResponseObserver>],
+) -> Result<(ResponseInfo, String, TimingInfo), Box<dyn Error>> {
+    let response_received_time = request_start.elapsed();
+
+    let status_code = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let effective_url = response.url().clone();
+
+    check_range_response(config, status_code, &headers, response.content_length())?;
+    if let Some(content_length) = response.content_length() {
+        confirm_large_response(content_length, config)?;
+    }
+
+    let body_start = Instant::now();
+    let body_streamed = streams_ndjson_body(&headers, config);
+    let body_bytes = if body_streamed {
+        stream_ndjson_body(response, config, observers)?
+    } else {
+        read_response_body(response, config, observers)?
+    };
+    let response_body = decode_response_body(&body_bytes, &headers);
+    let body_read_time = body_start.elapsed();
+
+    let total_time = overall_start.elapsed();
+
+    attempts.push(AttemptInfo {
+        number: current_attempt,
+        outcome: format!("HTTP {}", status_code.as_u16()),
+        duration: request_start.elapsed(),
+        delay: None,
+    });
+
+    let content_type = headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    let binary = is_binary_content_type(content_type);
+    let mut response_info = ResponseInfo::new(status_code, version, headers, effective_url);
+    if binary {
+        response_info = response_info.with_body_bytes(body_bytes);
+    }
+    if body_streamed {
+        response_info = response_info.with_streamed_body();
+    }
+    let timing_info =
+        TimingInfo::new(response_received_time, body_read_time, total_time, attempts);
+
+    Ok((response_info, response_body, timing_info))
+}
+
+/// Content-Length が --warn-size を超えるレスポンスを端末に表示する前に確認
+///
+/// 標準出力が端末でない場合や --output で保存する場合は確認しない。
+/// 確認できない（--no-input・標準入力が端末でない）場合は --yes がなければエラーにする。
+fn confirm_large_response(content_length: u64, config: &Config) -> Result<(), Box<dyn Error>> {
+    let Some(warn_size) = config.warn_size.as_deref() else {
+        return Ok(());
+    };
+    let threshold = parse_size(warn_size, WARN_SIZE_OPTION)?;
+    if content_length <= threshold
+        || config.yes
+        || config.output.is_some()
+        || config.remote_name
+        || !shows_body(config)
+        || !std::io::stdout().is_terminal()
+    {
+        return Ok(());
+    }
+
+    if config.no_input || !std::io::stdin().is_terminal() {
+        return Err(ERROR_LARGE_RESPONSE
+            .replace("{1}", &content_length.to_string())
+            .replace("{2}", warn_size)
+            .into());
+    }
+
+    eprint!(
+        "{}",
+        LARGE_RESPONSE_PROMPT
+            .replace("{1}", &content_length.to_string())
+            .replace("{2}", &format!("{:.1}", content_length as f64 / BYTES_PER_MB))
+            .replace("{3}", warn_size)
+    );
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    if CONFIRM_YES.contains(&answer.trim().to_ascii_lowercase().as_str()) {
+        Ok(())
+    } else {
+        Err(ERROR_LARGE_RESPONSE_DECLINED
+            .replace("{}", &content_length.to_string())
+            .into())
+    }
+}
+
+/// 単位付きのサイズ指定（50M など）をバイト数に変換
+fn parse_size(value: &str, option: &str) -> Result<u64, Box<dyn Error>> {
+    parse_byte_size(value).ok_or_else(|| {
+        ERROR_INVALID_SIZE
+            .replace("{1}", option)
+            .replace("{2}", value)
+            .into()
+    })
+}
+
+/// JSONとして解析・整形するボディの上限バイト数（--max-format-size、既定は64M）
+fn max_format_size(config: &Config) -> Result<u64, Box<dyn Error>> {
+    parse_size(
+        config
+            .max_format_size
+            .as_deref()
+            .unwrap_or(DEFAULT_MAX_FORMAT_SIZE),
+        MAX_FORMAT_SIZE_OPTION,
+    )
+}
+
+/// レスポンスボディをチャンクごとに読み込む
+fn read_response_body(
+    mut response: reqwest::blocking::Response,
+    config: &Config,
+    observers: &[Box<dyn ResponseObserver>],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut body = Vec::new();
+    let mut chunk = [0u8; BODY_CHUNK_SIZE];
+    loop {
+        let read = response.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+
+        for observer in observers {
+            observer.on_body_chunk(config, &chunk[..read]);
+        }
+        body.extend_from_slice(&chunk[..read]);
+
+        // Ctrl-C で中断された場合は受信済みの分だけを返す
+        if is_interrupted() {
+            break;
+        }
+    }
+
+    Ok(body)
+}
+
+/// NDJSONのレスポンスを受信しながら1行ずつ表示できるか
+///
+/// ファイルへの保存・--format json・--view など、ボディ全体を受信してから処理する場合や、
+/// ボディでリトライ・変更を判定する場合、並列送信では受信後にまとめて表示する。
+fn streams_ndjson_body(headers: &reqwest::header::HeaderMap, config: &Config) -> bool {
+    is_ndjson_response(headers, config)
+        && shows_body(config)
+        && config.output.is_none()
+        && !config.remote_name
+        && !is_json_format(config)
+        && config.export_shell.is_none()
+        && !config.view
+        && !config.open
+        && config.expect_body_file.is_none()
+        && config.assert.is_none()
+        && config.retry_if_body.is_none()
+        && config.watch.is_none()
+        && config.until.is_none()
+        && config.concurrency.is_none_or(|concurrency| concurrency <= 1)
+}
+
+/// NDJSONのレスポンスを1行ずつ読み込み、整形して表示（行ごとにフラッシュ）
+fn stream_ndjson_body(
+    response: reqwest::blocking::Response,
+    config: &Config,
+    observers: &[Box<dyn ResponseObserver>],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut reader = BufReader::new(response);
+    let mut stdout = std::io::stdout().lock();
+    let mut body = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+
+        for observer in observers {
+            observer.on_body_chunk(config, &line);
+        }
+        body.extend_from_slice(&line);
 
-                if should_retry_for_status(status.as_u16()) && current_attempt < max_attempts {
-                    handle_retry_delay(config, current_attempt, status.as_u16());
-                    continue;
-                }
+        let text = String::from_utf8_lossy(&line);
+        let record = text.trim_end_matches(['\r', '\n']);
+        if !record.trim().is_empty() {
+            writeln!(stdout, "{}", format_response_body(record, config)?)?;
+            stdout.flush()?;
+        }
 
-                return handle_successful_response(response, request_start, overall_start);
-            }
-            Err(e) => {
-                if current_attempt < max_attempts {
-                    handle_request_error_retry(config, current_attempt, &e);
-                    continue;
-                }
-                return Err(e.into());
-            }
+        // Ctrl-C で中断された場合は受信済みの分だけを返す
+        if is_interrupted() {
+            break;
         }
     }
+
+    Ok(body)
 }
 
-/// 成功したレスポンスを処理
-fn handle_successful_response(
-    response: reqwest::blocking::Response,
-    request_start: Instant,
-    overall_start: Instant,
-) -> Result<(ResponseInfo, String, TimingInfo), Box<dyn Error>> {
-    let response_received_time = request_start.elapsed();
+/// レスポンスボディをContent-Typeの文字コード（既定はUTF-8）で文字列に変換
+fn decode_response_body(body: &[u8], headers: &reqwest::header::HeaderMap) -> String {
+    let encoding = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| {
+            content_type
+                .split(';')
+                .find_map(|param| param.trim().strip_prefix(CHARSET_PARAM))
+        })
+        .and_then(|charset| Encoding::for_label(charset.trim_matches('"').as_bytes()))
+        .unwrap_or(UTF_8);
 
-    let status_code = response.status();
-    let version = response.version();
-    let headers = response.headers().clone();
+    let (text, _, _) = encoding.decode(body);
+    text.into_owned()
+}
 
-    let body_start = Instant::now();
-    let response_body = response.text()?;
-    let body_read_time = body_start.elapsed();
+/// 指数バックオフによるリトライの待機時間
+fn retry_backoff(config: &Config, current_attempt: u32) -> Duration {
+    Duration::from_secs_f64(
+        config.retry_delay * RETRY_BACKOFF_MULTIPLIER.powi(current_attempt.saturating_sub(1) as i32),
+    )
+}
 
-    let total_time = overall_start.elapsed();
+/// 1回の試行のタイムアウト（--attempt-timeout と --max-time の残り時間の短い方）
+fn attempt_timeout(
+    per_attempt: Option<Duration>,
+    deadline: Option<Duration>,
+    elapsed: Duration,
+) -> Option<Duration> {
+    let remaining = deadline.map(|deadline| deadline.saturating_sub(elapsed));
+    match (per_attempt, remaining) {
+        (Some(per_attempt), Some(remaining)) => Some(per_attempt.min(remaining)),
+        (per_attempt, remaining) => per_attempt.or(remaining),
+    }
+}
 
-    let response_info = ResponseInfo::new(status_code, version, headers);
-    let timing_info = TimingInfo::new(response_received_time, body_read_time, total_time);
+/// リトライ遅延を処理
+fn handle_retry_delay(
+    config: &Config,
+    backoff_delay: Duration,
+    status: reqwest::StatusCode,
+    observers: &[Box<dyn ResponseObserver>],
+) -> Duration {
+    for observer in observers {
+        observer.on_retry(config, &RetryReason::Status(status), backoff_delay);
+    }
 
-    Ok((response_info, response_body, timing_info))
+    sleep_unless_interrupted(backoff_delay);
+    backoff_delay
 }
 
-/// リトライ遅延を処理
-fn handle_retry_delay(config: &Config, current_attempt: u32, status_code: u16) {
-    if config.verbose {
-        println!(
-            "{}",
-            HTTP_RETRY_MSG.replace("{}", &status_code.to_string())
-        );
+/// ボディの条件によるリトライを処理
+fn handle_body_retry_delay(
+    config: &Config,
+    backoff_delay: Duration,
+    status: reqwest::StatusCode,
+    observers: &[Box<dyn ResponseObserver>],
+) -> Duration {
+    for observer in observers {
+        observer.on_retry(config, &RetryReason::Body(status), backoff_delay);
     }
 
-    let backoff_delay = config.retry_delay
-        * RETRY_BACKOFF_MULTIPLIER.powi(current_attempt.saturating_sub(1) as i32);
-    thread::sleep(Duration::from_secs_f64(backoff_delay));
+    sleep_unless_interrupted(backoff_delay);
+    backoff_delay
 }
 
 /// リクエストエラーのリトライを処理
-fn handle_request_error_retry(config: &Config, current_attempt: u32, error: &reqwest::Error) {
-    if config.verbose {
-        println!(
-            "{}",
-            REQUEST_ERROR_RETRY_MSG.replace("{}", &error.to_string())
-        );
+fn handle_request_error_retry(
+    config: &Config,
+    backoff_delay: Duration,
+    error: &reqwest::Error,
+    observers: &[Box<dyn ResponseObserver>],
+) -> Duration {
+    for observer in observers {
+        observer.on_retry(config, &RetryReason::Error(error), backoff_delay);
     }
 
-    let backoff_delay = config.retry_delay
-        * RETRY_BACKOFF_MULTIPLIER.powi(current_attempt.saturating_sub(1) as i32);
-    thread::sleep(Duration::from_secs_f64(backoff_delay));
+    sleep_unless_interrupted(backoff_delay);
+    backoff_delay
 }
 
 /// ステータスコードによるリトライ判定
@@ -608,23 +3081,234 @@ fn handle_response(
     response_body: String,
     timing_info: TimingInfo,
     config: &Config,
+    observers: &[Box<dyn ResponseObserver>],
 ) -> Result<(), Box<dyn Error>> {
-    display_response_info(&response_info, config);
-    display_timing_info(&timing_info, response_body.len(), config);
+    for observer in observers {
+        observer.on_response(config, &response_info, &timing_info, &response_body);
+    }
+
+    let interrupted = is_interrupted();
+    // NDJSONを受信しながら表示した場合は出力済み
+    if response_info.body_streamed {
+        if interrupted {
+            report_partial_body(&response_info, &response_body, config, None);
+            return Ok(());
+        }
+        return check_header_assertions(config, response_info.headers());
+    }
+
+    // Ctrl-C で途中までしか受信していないボディは整形せずにそのまま出力する
+    let processed_response = if interrupted {
+        response_body.clone()
+    } else {
+        format_body(&response_info, &response_body, config)?
+    };
+    let output = if config.export_shell.is_some() && !interrupted {
+        render_shell_exports(config, &response_body)?
+    } else if is_json_format(config) {
+        render_result_document(&response_info, &timing_info, &processed_response, config)?
+    } else {
+        processed_response.clone()
+    };
+    let content_type = response_info
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    // 画像・PDFなどは整形せずに受信したバイト列のまま保存する
+    let body_bytes = response_info
+        .body_bytes
+        .as_deref()
+        .filter(|_| !is_json_format(config) && config.export_shell.is_none());
+    output_response(
+        &output,
+        body_bytes,
+        config,
+        content_type,
+        response_info.status().as_u16(),
+    )?;
+
+    if interrupted {
+        report_partial_body(&response_info, &response_body, config, content_type);
+        return Ok(());
+    }
 
-    let processed_response = format_response_body(&response_body, config)?;
-    output_response(&processed_response, config)?;
+    check_header_assertions(config, response_info.headers())?;
+
+    // フィルタ・変換後のボディを期待値ファイルと比較（--update-golden の場合は更新）
+    if let Some(golden_path) = &config.expect_body_file {
+        if config.update_golden {
+            update_golden_file(&processed_response, golden_path)?;
+        } else {
+            compare_golden_file(&processed_response, golden_path)?;
+        }
+    }
 
     Ok(())
 }
 
+/// 中断で途中までしか受信していないボディのサイズと保存先を表示
+fn report_partial_body(
+    response_info: &ResponseInfo,
+    response_body: &str,
+    config: &Config,
+    content_type: Option<&str>,
+) {
+    let received = response_info
+        .body_bytes
+        .as_ref()
+        .map_or(response_body.len(), Vec::len)
+        .to_string();
+    let message = match output_file_path(config, content_type, response_info.status().as_u16()) {
+        Some((output_file, _)) => PARTIAL_BODY_SAVED_MSG
+            .replace("{1}", &received)
+            .replace("{2}", &output_file),
+        None => PARTIAL_BODY_MSG.replace("{}", &received),
+    };
+    eprintln!("{}", message);
+}
+
+/// レスポンスボディにフィルタ・変換を適用（NDJSONは1行ずつ）
+fn format_body(
+    response_info: &ResponseInfo,
+    response_body: &str,
+    config: &Config,
+) -> Result<String, Box<dyn Error>> {
+    // 巨大なボディはJSONとして解析せずにそのまま出力（解析・整形で数GBを確保しないため）
+    let limit = max_format_size(config)?;
+    if response_body.len() as u64 > limit {
+        let limit_text = config
+            .max_format_size
+            .as_deref()
+            .unwrap_or(DEFAULT_MAX_FORMAT_SIZE);
+        if config.json_filter.is_some()
+            || config.transforms.is_some()
+            || config.output_template.is_some()
+        {
+            return Err(ERROR_FORMAT_SIZE_EXCEEDED
+                .replace("{1}", &response_body.len().to_string())
+                .replace("{2}", limit_text)
+                .into());
+        }
+        if config.pretty_json && shows_meta(config) {
+            eprintln!(
+                "{}",
+                FORMAT_SKIPPED_MSG
+                    .replace("{1}", &response_body.len().to_string())
+                    .replace("{2}", limit_text)
+            );
+        }
+        return Ok(response_body.to_string());
+    }
+
+    if is_ndjson_response(response_info.headers(), config) {
+        format_ndjson_body(response_body, config)
+    } else {
+        format_response_body(response_body, config)
+    }
+}
+
+/// 詳細出力・タイミング・進捗などの補足情報を表示するか（-q・--format json では表示しない）
+pub fn shows_meta(config: &Config) -> bool {
+    config.quiet < QUIET_META && !is_json_format(config)
+}
+
+/// レスポンスボディを表示するか（-qq では表示しない）
+pub fn shows_body(config: &Config) -> bool {
+    config.quiet < QUIET_BODY
+}
+
+/// -v のテキストによる詳細表示を行うか（-q・--format json・--meta-format json では表示しない）
+pub(crate) fn shows_verbose_text(config: &Config) -> bool {
+    config.verbose && shows_meta(config) && !is_json_meta_format(config)
+}
+
+/// 送信中の進捗表示を行うか（--no-progress では進捗表示のみ行わない）
+pub fn shows_progress(config: &Config) -> bool {
+    !config.no_progress && shows_meta(config)
+}
+
+/// CLIで使用するオブザーバーの一覧（詳細出力・タイミング情報の表示）
+pub fn default_observers() -> Vec<Box<dyn ResponseObserver>> {
+    vec![
+        Box::new(VerbosePrinter),
+        Box::new(TimingPrinter),
+        Box::new(WaterfallPrinter),
+    ]
+}
+
+impl ResponseObserver for VerbosePrinter {
+    fn on_attempt(&self, config: &Config, attempt: u32) {
+        if shows_verbose_text(config) && attempt > 1 {
+            eprintln!(
+                "{}",
+                RETRY_ATTEMPT_PREFIX.replace("{}", &attempt.saturating_sub(1).to_string())
+            );
+        }
+    }
+
+    fn on_retry(&self, config: &Config, reason: &RetryReason, _delay: Duration) {
+        if !shows_verbose_text(config) {
+            return;
+        }
+
+        match reason {
+            RetryReason::Status(status) => eprintln!(
+                "{}",
+                HTTP_RETRY_MSG.replace("{}", &status.as_u16().to_string())
+            ),
+            RetryReason::Error(error) => eprintln!(
+                "{}",
+                REQUEST_ERROR_RETRY_MSG.replace("{}", &error.to_string())
+            ),
+            RetryReason::Body(status) => eprintln!(
+                "{}",
+                BODY_RETRY_MSG.replace("{}", &status.as_u16().to_string())
+            ),
+            RetryReason::Unauthorized => eprintln!("{}", UNAUTHORIZED_RETRY_MSG),
+        }
+    }
+
+    fn on_response(&self, config: &Config, response: &ResponseInfo, timing: &TimingInfo, _body: &str) {
+        // JSON形式ではステータス・ヘッダーをドキュメントに含める
+        if !shows_meta(config) {
+            return;
+        }
+
+        // --meta-format json ではリトライ・タイミングも含めて1行のJSONとして表示
+        if is_json_meta_format(config) {
+            eprintln!("{}", render_response_meta(response, timing, config));
+            return;
+        }
+        display_response_info(response, config);
+    }
+}
+
+impl ResponseObserver for WaterfallPrinter {
+    fn on_response(&self, config: &Config, _response: &ResponseInfo, timing: &TimingInfo, _body: &str) {
+        if config.timing_waterfall && shows_meta(config) {
+            display_timing_waterfall(timing);
+        }
+    }
+}
+
+impl ResponseObserver for TimingPrinter {
+    fn on_response(&self, config: &Config, _response: &ResponseInfo, timing: &TimingInfo, body: &str) {
+        if shows_meta(config) && !is_json_meta_format(config) {
+            display_timing_info(timing, body.len(), config);
+        }
+    }
+}
+
 /// レスポンス情報を表示
 fn display_response_info(response_info: &ResponseInfo, config: &Config) {
     if !config.verbose {
+        if config.show_cookies {
+            display_set_cookies(response_info);
+        }
         return;
     }
 
-    println!(
+    eprintln!(
         "< {:?} {} {}",
         response_info.version(),
         response_info.status().as_u16(),
@@ -635,10 +3319,100 @@ fn display_response_info(response_info: &ResponseInfo, config: &Config) {
     );
 
     for (name, value) in response_info.headers() {
-        println!("< {}: {}", name, value.to_str().unwrap_or("<binary>"));
+        // Set-Cookieは後で表形式で表示
+        if name == reqwest::header::SET_COOKIE || !is_header_visible(config, name.as_str()) {
+            continue;
+        }
+        eprintln!("< {}: {}", name, value.to_str().unwrap_or("<binary>"));
+    }
+
+    eprintln!();
+
+    if config.show_cookies || is_header_visible(config, reqwest::header::SET_COOKIE.as_str()) {
+        display_set_cookies(response_info);
+    }
+}
+
+/// Set-Cookieヘッダーを解析して表形式で表示
+fn display_set_cookies(response_info: &ResponseInfo) {
+    let cookies: Vec<SetCookieInfo> = response_info
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(parse_set_cookie)
+        .collect();
+
+    if cookies.is_empty() {
+        return;
+    }
+
+    eprintln!("{}", COOKIES_HEADER);
+    eprintln!(
+        "{:<20} {:<24} {:<20} {:<10} {:<30} Flags",
+        "Name", "Value", "Domain", "Path", "Expires"
+    );
+
+    for cookie in &cookies {
+        let mut flags: Vec<String> = Vec::new();
+        if cookie.secure {
+            flags.push("Secure".to_string());
+        }
+        if cookie.http_only {
+            flags.push("HttpOnly".to_string());
+        }
+        if let Some(same_site) = &cookie.same_site {
+            flags.push(format!("SameSite={}", same_site));
+        }
+
+        eprintln!(
+            "{:<20} {:<24} {:<20} {:<10} {:<30} {}",
+            cookie.name,
+            cookie.value,
+            cookie.domain.as_deref().unwrap_or(EMPTY_VALUE_MARK),
+            cookie.path.as_deref().unwrap_or(EMPTY_VALUE_MARK),
+            cookie.expires.as_deref().unwrap_or(EMPTY_VALUE_MARK),
+            flags.join(" ")
+        );
+    }
+
+    eprintln!();
+}
+
+/// Set-Cookieヘッダーの値を解析
+fn parse_set_cookie(header_value: &str) -> SetCookieInfo {
+    let mut parts = header_value.split(';').map(str::trim);
+    let (name, value) = parts
+        .next()
+        .and_then(|pair| pair.split_once('='))
+        .unwrap_or((header_value, ""));
+
+    let mut cookie = SetCookieInfo {
+        name: name.trim().to_string(),
+        value: value.trim().to_string(),
+        ..Default::default()
+    };
+
+    for attribute in parts {
+        let (key, attribute_value) = match attribute.split_once('=') {
+            Some((key, attribute_value)) => (key.trim(), Some(attribute_value.trim().to_string())),
+            None => (attribute, None),
+        };
+
+        match key.to_ascii_lowercase().as_str() {
+            "domain" => cookie.domain = attribute_value,
+            "path" => cookie.path = attribute_value,
+            "expires" if cookie.expires.is_none() => cookie.expires = attribute_value,
+            // Max-AgeはExpiresより優先される
+            "max-age" => cookie.expires = attribute_value.map(|age| format!("max-age={}", age)),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            "samesite" => cookie.same_site = attribute_value,
+            _ => {}
+        }
     }
 
-    println!();
+    cookie
 }
 
 /// タイミング情報を表示
@@ -647,20 +3421,20 @@ fn display_timing_info(timing_info: &TimingInfo, response_size: usize, config: &
         return;
     }
 
-    println!("{}", TIMING_HEADER);
-    println!(
+    eprintln!("{}", TIMING_HEADER);
+    eprintln!(
         "{}",
         RESPONSE_RECEIVED_MSG.replace("{}", &format!("{:?}", timing_info.response_time))
     );
-    println!(
+    eprintln!(
         "{}",
         BODY_READ_TIME_MSG.replace("{}", &format!("{:?}", timing_info.body_read_time))
     );
-    println!(
+    eprintln!(
         "{}",
         TOTAL_TIME_MSG.replace("{}", &format!("{:?}", timing_info.total_time))
     );
-    println!(
+    eprintln!(
         "{}",
         RESPONSE_SIZE_MSG
             .replace("{1}", &response_size.to_string())
@@ -670,13 +3444,50 @@ fn display_timing_info(timing_info: &TimingInfo, response_size: usize, config: &
     if response_size > 0 && timing_info.total_time.as_secs_f64() > 0.0 {
         let throughput =
             response_size as f64 / timing_info.total_time.as_secs_f64() / BYTES_PER_KB;
-        println!(
+        eprintln!(
             "{}",
             THROUGHPUT_MSG.replace("{}", &format!("{:.2}", throughput))
         );
     }
 
-    println!();
+    eprintln!();
+
+    display_attempts_table(&timing_info.attempts);
+}
+
+/// 試行ごとの結果・所要時間・待機時間を表示（リトライが発生した場合のみ）
+fn display_attempts_table(attempts: &[AttemptInfo]) {
+    if attempts.len() <= 1 {
+        return;
+    }
+
+    eprintln!("{}", ATTEMPTS_HEADER);
+    eprintln!("{:<4} {:<12} {:>12} {:>12}", "#", "Result", "Duration", "Delay");
+
+    for attempt in attempts {
+        let delay = attempt
+            .delay
+            .map(|delay| format!("{:?}", delay))
+            .unwrap_or_else(|| EMPTY_VALUE_MARK.to_string());
+        eprintln!(
+            "{:<4} {:<12} {:>12} {:>12}",
+            attempt.number,
+            attempt.outcome,
+            format!("{:?}", attempt.duration),
+            delay
+        );
+    }
+
+    let transfer_time: Duration = attempts.iter().map(|attempt| attempt.duration).sum();
+    let backoff_time: Duration = attempts.iter().filter_map(|attempt| attempt.delay).sum();
+    eprintln!(
+        "{}",
+        ATTEMPT_SUMMARY_MSG
+            .replace("{1}", &format!("{:?}", transfer_time))
+            .replace("{2}", &format!("{:?}", backoff_time))
+    );
+
+    eprintln!();
 }
 
 /// レスポンスボディをフォーマット
@@ -692,6 +3503,16 @@ fn format_response_body(body: &str, config: &Config) -> Result<String, Box<dyn E
         result = extract_json_path(result, filter_path)?;
     }
 
+    if let Some(transforms) = &config.transforms {
+        for transform in transforms {
+            result = apply_transform(result, transform)?;
+        }
+    }
+
+    if let Some(template) = &config.output_template {
+        return render_output_template(result, template, config.each.as_deref());
+    }
+
     let formatted = if config.pretty_json {
         serde_json::to_string_pretty(&result)?
     } else {
@@ -701,8 +3522,157 @@ fn format_response_body(body: &str, config: &Config) -> Result<String, Box<dyn E
     Ok(formatted)
 }
 
+/// NDJSONレスポンスかどうかを判定
+fn is_ndjson_response(headers: &reqwest::header::HeaderMap, config: &Config) -> bool {
+    if config.ndjson_response {
+        return true;
+    }
+
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(CONTENT_TYPE_NDJSON))
+}
+
+/// NDJSONレスポンスを1行ずつフォーマット
+fn format_ndjson_body(body: &str, config: &Config) -> Result<String, Box<dyn Error>> {
+    let formatted_lines = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| format_response_body(line, config))
+        .collect::<Result<Vec<String>, Box<dyn Error>>>()?;
+
+    Ok(formatted_lines.join("\n"))
+}
+
+/// 変換処理を適用
+fn apply_transform(json: Value, transform: &Transform) -> Result<Value, Box<dyn Error>> {
+    match transform {
+        Transform::Map(path) => match json {
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| extract_json_path(item, path))
+                    .collect::<Result<Vec<Value>, Box<dyn Error>>>()?,
+            )),
+            other => extract_json_path(other, path),
+        },
+        Transform::Sort => match json {
+            Value::Array(mut items) => {
+                items.sort_by(compare_json_values);
+                Ok(Value::Array(items))
+            }
+            other => Ok(other),
+        },
+        Transform::Unique => match json {
+            Value::Array(items) => {
+                let mut unique_items: Vec<Value> = Vec::with_capacity(items.len());
+                for item in items {
+                    if !unique_items.contains(&item) {
+                        unique_items.push(item);
+                    }
+                }
+                Ok(Value::Array(unique_items))
+            }
+            other => Ok(other),
+        },
+    }
+}
+
+/// JSON値を比較（数値は数値として、文字列は辞書順、それ以外は文字列表現で比較）
+fn compare_json_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x
+            .as_f64()
+            .unwrap_or(0.0)
+            .total_cmp(&y.as_f64().unwrap_or(0.0)),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+/// 出力テンプレートを描画（`each` 指定時は配列の要素ごとに描画）
+fn render_output_template(
+    json: Value,
+    template: &str,
+    each_path: Option<&str>,
+) -> Result<String, Box<dyn Error>> {
+    let Some(each_path) = each_path else {
+        return render_template(template, &json);
+    };
+
+    match extract_json_path(json, each_path)? {
+        Value::Array(items) => Ok(items
+            .iter()
+            .map(|item| render_template(template, item))
+            .collect::<Result<Vec<String>, Box<dyn Error>>>()?
+            .join("\n")),
+        Value::Null => Ok(String::new()),
+        other => render_template(template, &other),
+    }
+}
+
+/// テンプレート内の `{{ パス | 関数 }}` を置換
+fn render_template(template: &str, json: &Value) -> Result<String, Box<dyn Error>> {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(open_pos) = rest.find(TEMPLATE_OPEN) {
+        rendered.push_str(&rest[..open_pos]);
+        let after_open = &rest[open_pos + TEMPLATE_OPEN.len()..];
+        let close_pos = after_open.find(TEMPLATE_CLOSE).ok_or(ERROR_UNCLOSED_TEMPLATE)?;
+
+        let value = evaluate_template_expression(&after_open[..close_pos], json)?;
+        rendered.push_str(&template_value_to_string(&value));
+
+        rest = &after_open[close_pos + TEMPLATE_CLOSE.len()..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// テンプレート式（パスとパイプ関数）を評価
+fn evaluate_template_expression(expression: &str, json: &Value) -> Result<Value, Box<dyn Error>> {
+    let mut segments = expression.split(TEMPLATE_PIPE).map(str::trim);
+    let path = segments.next().unwrap_or(JSON_PATH_ROOT);
+    let mut value = extract_json_path(json.clone(), path)?;
+
+    for function in segments {
+        value = apply_template_function(value, function)?;
+    }
+
+    Ok(value)
+}
+
+/// テンプレート関数を適用
+fn apply_template_function(value: Value, function: &str) -> Result<Value, Box<dyn Error>> {
+    match function {
+        "length" => Ok(Value::from(match &value {
+            Value::Array(items) => items.len(),
+            Value::Object(map) => map.len(),
+            Value::String(text) => text.chars().count(),
+            Value::Null => 0,
+            _ => 1,
+        })),
+        "upper" => Ok(Value::String(template_value_to_string(&value).to_uppercase())),
+        "lower" => Ok(Value::String(template_value_to_string(&value).to_lowercase())),
+        _ => Err(ERROR_UNKNOWN_TEMPLATE_FUNCTION.replace("{}", function).into()),
+    }
+}
+
+/// テンプレートに埋め込む文字列へ変換（文字列はクォートなし、nullは空文字）
+fn template_value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
 /// JSONパスを抽出
-fn extract_json_path(mut json: Value, path: &str) -> Result<Value, Box<dyn Error>> {
+pub(crate) fn extract_json_path(mut json: Value, path: &str) -> Result<Value, Box<dyn Error>> {
     let path = path.trim();
 
     if path == JSON_PATH_ROOT {
@@ -742,21 +3712,197 @@ fn process_json_path_part(json: Value, part: &str) -> Result<Value, Box<dyn Erro
     Ok(json.get(part).cloned().unwrap_or(Value::Null))
 }
 
-/// レスポンスを出力
-fn output_response(processed_response: &str, config: &Config) -> Result<(), Box<dyn Error>> {
-    match &config.output {
-        Some(output_file) => save_response_to_file(output_file, processed_response.as_bytes()),
-        None if !config.silent => {
-            println!("{}", processed_response);
+/// レスポンスを出力（body_bytes はバイナリのボディをファイルに保存する場合に使う）
+fn output_response(
+    processed_response: &str,
+    body_bytes: Option<&[u8]>,
+    config: &Config,
+    content_type: Option<&str>,
+    status: u16,
+) -> Result<(), Box<dyn Error>> {
+    let data = body_bytes.unwrap_or(processed_response.as_bytes());
+    if config.open {
+        open_response(data, content_type)?;
+    }
+
+    match output_file_path(config, content_type, status) {
+        Some((output_file, named_from_url)) => {
+            write_output_file(
+                &output_file,
+                data,
+                config.output_append,
+                config
+                    .output_rotate
+                    .as_deref()
+                    .map(parse_output_rotate)
+                    .transpose()?,
+            )?;
+            // ファイル名を自動で決めた場合は保存先を表示
+            if named_from_url && shows_meta(config) {
+                eprintln!(
+                    "{}",
+                    SAVED_FILE_MSG
+                        .replace("{1}", &output_file)
+                        .replace("{2}", &data.len().to_string())
+                );
+            }
             Ok(())
         }
+        None if config.view && shows_body(config) => {
+            match serde_json::from_str::<Value>(processed_response) {
+                Ok(json) => view_json(&json),
+                Err(_) => print_response(processed_response, config),
+            }
+        }
+        None if shows_body(config) && !config.open => print_response(processed_response, config),
         _ => Ok(()),
     }
 }
 
-/// レスポンスをファイルに保存
+/// 出力ファイルのパス
+///
+/// --output にディレクトリを指定した場合や -O の場合は、URLの最後のパス要素と
+/// Content-Type からファイル名を決める（その場合は true を返す）。
+fn output_file_path(config: &Config, content_type: Option<&str>, status: u16) -> Option<(String, bool)> {
+    let Some(output) = &config.output else {
+        return config
+            .remote_name
+            .then(|| (remote_file_name(&config.url, content_type), true));
+    };
+
+    let output = apply_status_placeholder(output, status);
+    if !config.remote_name && !is_output_directory(&output) {
+        return Some((output, false));
+    }
+
+    let file_path = Path::new(&output).join(remote_file_name(&config.url, content_type));
+    Some((file_path.to_string_lossy().into_owned(), true))
+}
+
+/// レスポンスを標準出力に表示（端末の高さを超える場合はページャーを使用）
+fn print_response(processed_response: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    if !config.no_pager && exceeds_terminal_height(processed_response) {
+        if let Some(pager) = pager_command() {
+            return page_response(processed_response, &pager);
+        }
+    }
+
+    println!("{}", processed_response);
+    Ok(())
+}
+
+/// 標準出力が端末で、出力が端末の高さを超えるかどうかを判定
+fn exceeds_terminal_height(text: &str) -> bool {
+    if !std::io::stdout().is_terminal() {
+        return false;
+    }
+
+    match terminal_size::terminal_size() {
+        Some((_, terminal_size::Height(height))) => text.lines().count() >= height as usize,
+        None => false,
+    }
+}
+
+/// ページャーコマンドを取得（`$PAGER` が空の場合はページャーを使用しない）
+fn pager_command() -> Option<String> {
+    let pager = std::env::var(PAGER_ENV).unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    if pager.trim().is_empty() {
+        None
+    } else {
+        Some(pager)
+    }
+}
+
+/// ページャーにレスポンスを渡して表示
+fn page_response(processed_response: &str, pager: &str) -> Result<(), Box<dyn Error>> {
+    let mut pager_parts = pager.split_whitespace();
+    let program = pager_parts.next().unwrap_or_default();
+
+    let mut child = match Command::new(program)
+        .args(pager_parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => {
+            // ページャーを起動できない場合はそのまま出力
+            println!("{}", processed_response);
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // ページャーが途中で終了した場合の書き込みエラーは無視
+        let _ = writeln!(stdin, "{}", processed_response);
+    }
+    child.wait()?;
+
+    Ok(())
+}
+
+/// レスポンスを一時ファイルに書き出してエディタまたはブラウザで開く
+fn open_response(data: &[u8], content_type: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let extension = extension_for_content_type(content_type);
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let file_path = std::env::temp_dir().join(format!(
+        "{}{}-{}.{}",
+        OPEN_FILE_PREFIX,
+        std::process::id(),
+        timestamp,
+        extension
+    ));
+    save_response_to_file(&file_path.to_string_lossy(), data)?;
+
+    match std::env::var(EDITOR_ENV) {
+        Ok(editor) if extension != "html" => {
+            let mut editor_parts = editor.split_whitespace();
+            let program = editor_parts.next().ok_or(ERROR_EMPTY_EDITOR)?;
+            Command::new(program).args(editor_parts).arg(&file_path).status()?;
+        }
+        _ => open::that(&file_path)?,
+    }
+
+    Ok(())
+}
+
+/// レスポンスをファイルに保存（親ディレクトリがなければ作成）
 fn save_response_to_file(file_path: &str, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = Path::new(file_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
     let mut file = File::create(file_path)?;
     file.write_all(data)?;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::default_signers;
+
+    #[test]
+    fn sigv4_signs_configured_headers() {
+        std::env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY");
+        std::env::remove_var("AWS_SESSION_TOKEN");
+
+        let config = Config {
+            url: "https://examplebucket.s3.amazonaws.com/test.txt".to_string(),
+            headers: Some(vec!["x-amz-acl: private".to_string()]),
+            aws_sigv4: Some("us-east-1/s3".to_string()),
+            ..Config::default()
+        };
+        let client_context = create_http_client(&config, default_signers(), Vec::new()).unwrap();
+        let request = build_request(&client_context, &config).unwrap();
+
+        assert_eq!(request.headers()["x-amz-acl"], "private");
+        let authorization = request.headers()[AUTHORIZATION].to_str().unwrap();
+        assert!(
+            authorization.contains("SignedHeaders=host;x-amz-acl;x-amz-content-sha256;x-amz-date,"),
+            "{}",
+            authorization
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockCipher, BlockDecryptMut, KeyInit, KeyIvInit};
+use md5::{Digest, Md5};
+use p12_keystore::error::Error as Pkcs12Error;
+use p12_keystore::KeyStore;
+use pkcs8::EncryptedPrivateKeyInfo;
+use rustls::pki_types::{
+    CertificateDer, PrivateKeyDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer, PrivateSec1KeyDer,
+};
+use std::error::Error;
+
+// PEM
+const PEM_BEGIN_PREFIX: &str = "-----BEGIN ";
+const PEM_END_PREFIX: &str = "-----END ";
+const PEM_BOUNDARY_SUFFIX: &str = "-----";
+const PEM_PRIVATE_KEY_SUFFIX: &str = "PRIVATE KEY";
+const PEM_LABEL_ENCRYPTED_PKCS8: &str = "ENCRYPTED PRIVATE KEY";
+const PEM_LABEL_RSA: &str = "RSA PRIVATE KEY";
+const PEM_LABEL_EC: &str = "EC PRIVATE KEY";
+
+// 従来形式（OpenSSL）の暗号化ヘッダー
+const PEM_HEADER_PROC_TYPE_ENCRYPTED: &str = "Proc-Type: 4,ENCRYPTED";
+const PEM_HEADER_DEK_INFO: &str = "DEK-Info:";
+const PEM_DEK_INFO_SEPARATOR: char = ',';
+const PEM_SALT_LEN: usize = 8;
+const CIPHER_AES_128_CBC: &str = "AES-128-CBC";
+const CIPHER_AES_192_CBC: &str = "AES-192-CBC";
+const CIPHER_AES_256_CBC: &str = "AES-256-CBC";
+const CIPHER_DES_EDE3_CBC: &str = "DES-EDE3-CBC";
+
+// PKCS#12
+const PKCS12_EMPTY_PASSWORD: &str = "";
+
+// 表示メッセージ
+const PASSPHRASE_PROMPT_MSG: &str = "Enter passphrase for {}: ";
+
+// エラーメッセージ
+const ERROR_INVALID_ENCRYPTED_KEY: &str = "Invalid encrypted private key in {1}: {2}";
+const ERROR_UNSUPPORTED_KEY_CIPHER: &str =
+    "Unsupported private key encryption '{1}' in {2}. Use AES-128-CBC, AES-192-CBC, AES-256-CBC or DES-EDE3-CBC.";
+const ERROR_DECRYPT_KEY: &str = "Failed to decrypt the private key in {} (wrong passphrase?)";
+const ERROR_INVALID_PKCS12: &str = "Invalid PKCS#12 file {1}: {2}";
+const ERROR_PKCS12_PASSPHRASE: &str = "Failed to open PKCS#12 file {} (wrong passphrase?)";
+const ERROR_PKCS12_NO_PRIVATE_KEY: &str =
+    "No private key with a certificate found in PKCS#12 file {}";
+
+/// PEMのブロック（ラベルとヘッダー・本文）
+pub(crate) struct PemBlock<'a> {
+    pub(crate) label: &'a str,
+    pub(crate) body: &'a str,
+}
+
+/// 暗号化された秘密鍵を（パスフレーズを入力して）復号する（暗号化されていない場合はNone）
+///
+/// PKCS#8（ENCRYPTED PRIVATE KEY）と、OpenSSLの従来形式（Proc-Type: 4,ENCRYPTED）に対応。
+pub fn decrypt_private_key(
+    pem: &[u8],
+    path: &str,
+) -> Result<Option<PrivateKeyDer<'static>>, Box<dyn Error>> {
+    let text = String::from_utf8_lossy(pem);
+    let Some(block) = find_private_key_block(&text) else {
+        return Ok(None);
+    };
+
+    if block.label == PEM_LABEL_ENCRYPTED_PKCS8 {
+        let der = decode_base64(block.body, path)?;
+        let info = EncryptedPrivateKeyInfo::try_from(der.as_slice()).map_err(|e| {
+            ERROR_INVALID_ENCRYPTED_KEY
+                .replace("{1}", path)
+                .replace("{2}", &e.to_string())
+        })?;
+        let passphrase = prompt_passphrase(path)?;
+        let document = info
+            .decrypt(passphrase.as_bytes())
+            .map_err(|_| ERROR_DECRYPT_KEY.replace("{}", path))?;
+        let key = PrivatePkcs8KeyDer::from(document.as_bytes().to_vec());
+        return Ok(Some(PrivateKeyDer::Pkcs8(key)));
+    }
+
+    if !block.body.contains(PEM_HEADER_PROC_TYPE_ENCRYPTED) {
+        return Ok(None);
+    }
+    decrypt_legacy_private_key(&block, path).map(Some)
+}
+
+/// PKCS#12（.p12/.pfx）から証明書チェーンと秘密鍵を読み込む
+///
+/// パスフレーズなしで開けない場合のみ、パスフレーズを入力する。
+pub fn load_pkcs12(
+    data: &[u8],
+    path: &str,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), Box<dyn Error>> {
+    let keystore = match KeyStore::from_pkcs12(data, PKCS12_EMPTY_PASSWORD) {
+        Ok(keystore) => keystore,
+        Err(Pkcs12Error::MacError(_)) => {
+            let passphrase = prompt_passphrase(path)?;
+            KeyStore::from_pkcs12(data, &passphrase).map_err(|e| match e {
+                Pkcs12Error::MacError(_) => ERROR_PKCS12_PASSPHRASE.replace("{}", path),
+                e => ERROR_INVALID_PKCS12
+                    .replace("{1}", path)
+                    .replace("{2}", &e.to_string()),
+            })?
+        }
+        Err(e) => {
+            return Err(ERROR_INVALID_PKCS12
+                .replace("{1}", path)
+                .replace("{2}", &e.to_string())
+                .into())
+        }
+    };
+
+    let (_, key_chain) = keystore
+        .private_key_chain()
+        .ok_or_else(|| ERROR_PKCS12_NO_PRIVATE_KEY.replace("{}", path))?;
+    let certificates = key_chain
+        .chain()
+        .iter()
+        .map(|certificate| CertificateDer::from(certificate.as_der().to_vec()))
+        .collect();
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_chain.key().to_vec()));
+
+    Ok((certificates, key))
+}
+
+/// 秘密鍵のパスフレーズを入力（入力内容は表示しない）
+fn prompt_passphrase(path: &str) -> Result<String, Box<dyn Error>> {
+    Ok(rpassword::prompt_password(
+        PASSPHRASE_PROMPT_MSG.replace("{}", path),
+    )?)
+}
+
+/// 最初の秘密鍵のPEMブロックを探す
+pub(crate) fn find_private_key_block(text: &str) -> Option<PemBlock<'_>> {
+    let mut rest = text;
+    while let Some(start) = rest.find(PEM_BEGIN_PREFIX) {
+        let after_begin = &rest[start + PEM_BEGIN_PREFIX.len()..];
+        let label_end = after_begin.find(PEM_BOUNDARY_SUFFIX)?;
+        let label = &after_begin[..label_end];
+        let body_start = &after_begin[label_end + PEM_BOUNDARY_SUFFIX.len()..];
+        let end_marker = format!("{}{}{}", PEM_END_PREFIX, label, PEM_BOUNDARY_SUFFIX);
+        let body_end = body_start.find(&end_marker)?;
+
+        if label.ends_with(PEM_PRIVATE_KEY_SUFFIX) {
+            return Some(PemBlock {
+                label,
+                body: &body_start[..body_end],
+            });
+        }
+        rest = &body_start[body_end + end_marker.len()..];
+    }
+    None
+}
+
+/// OpenSSLの従来形式で暗号化された秘密鍵を復号する
+///
+/// 鍵はEVP_BytesToKey（MD5・1回）でパスフレーズとIVの先頭8バイトから導出する。
+fn decrypt_legacy_private_key(
+    block: &PemBlock<'_>,
+    path: &str,
+) -> Result<PrivateKeyDer<'static>, Box<dyn Error>> {
+    let invalid = |reason: &str| {
+        ERROR_INVALID_ENCRYPTED_KEY
+            .replace("{1}", path)
+            .replace("{2}", reason)
+    };
+
+    // ヘッダーと本文は空行で区切られる
+    let (headers, body) = block
+        .body
+        .trim_start()
+        .split_once("\n\n")
+        .or_else(|| block.body.trim_start().split_once("\r\n\r\n"))
+        .ok_or_else(|| invalid(PEM_HEADER_DEK_INFO))?;
+    let dek_info = headers
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(PEM_HEADER_DEK_INFO))
+        .ok_or_else(|| invalid(PEM_HEADER_DEK_INFO))?;
+    let (cipher, iv_hex) = dek_info
+        .trim()
+        .split_once(PEM_DEK_INFO_SEPARATOR)
+        .ok_or_else(|| invalid(PEM_HEADER_DEK_INFO))?;
+    let iv = decode_hex(iv_hex.trim()).ok_or_else(|| invalid(PEM_HEADER_DEK_INFO))?;
+    if iv.len() < PEM_SALT_LEN {
+        return Err(invalid(PEM_HEADER_DEK_INFO).into());
+    }
+    let encrypted = decode_base64(body, path)?;
+
+    let key_len = match cipher {
+        CIPHER_AES_128_CBC => 16,
+        CIPHER_AES_192_CBC | CIPHER_DES_EDE3_CBC => 24,
+        CIPHER_AES_256_CBC => 32,
+        _ => {
+            return Err(ERROR_UNSUPPORTED_KEY_CIPHER
+                .replace("{1}", cipher)
+                .replace("{2}", path)
+                .into())
+        }
+    };
+    let passphrase = prompt_passphrase(path)?;
+    let key = evp_bytes_to_key(passphrase.as_bytes(), &iv[..PEM_SALT_LEN], key_len);
+
+    let decrypted = match cipher {
+        CIPHER_AES_128_CBC => cbc_decrypt::<aes::Aes128>(&key, &iv, &encrypted),
+        CIPHER_AES_192_CBC => cbc_decrypt::<aes::Aes192>(&key, &iv, &encrypted),
+        CIPHER_AES_256_CBC => cbc_decrypt::<aes::Aes256>(&key, &iv, &encrypted),
+        _ => cbc_decrypt::<des::TdesEde3>(&key, &iv, &encrypted),
+    }
+    .ok_or_else(|| ERROR_DECRYPT_KEY.replace("{}", path))?;
+
+    Ok(match block.label {
+        PEM_LABEL_RSA => PrivateKeyDer::Pkcs1(PrivatePkcs1KeyDer::from(decrypted)),
+        PEM_LABEL_EC => PrivateKeyDer::Sec1(PrivateSec1KeyDer::from(decrypted)),
+        _ => PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(decrypted)),
+    })
+}
+
+/// OpenSSLの EVP_BytesToKey（MD5・繰り返し1回）で鍵を導出する
+fn evp_bytes_to_key(passphrase: &[u8], salt: &[u8], key_len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(key_len);
+    let mut digest = Vec::new();
+    while key.len() < key_len {
+        let mut hasher = Md5::new();
+        hasher.update(&digest);
+        hasher.update(passphrase);
+        hasher.update(salt);
+        digest = hasher.finalize().to_vec();
+        key.extend_from_slice(&digest);
+    }
+    key.truncate(key_len);
+    key
+}
+
+/// CBCモードで復号し、PKCS#7パディングを取り除く（鍵が違う場合はNone）
+fn cbc_decrypt<C>(key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockCipher + BlockDecryptMut + KeyInit,
+{
+    cbc::Decryptor::<C>::new_from_slices(key, iv)
+        .ok()?
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .ok()
+}
+
+/// PEMの本文（改行を含むBase64）をデコード
+fn decode_base64(body: &str, path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let encoded: String = body.split_whitespace().collect();
+    STANDARD.decode(encoded).map_err(|e| {
+        ERROR_INVALID_ENCRYPTED_KEY
+            .replace("{1}", path)
+            .replace("{2}", &e.to_string())
+            .into()
+    })
+}
+
+/// 16進数の文字列をバイト列に変換
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
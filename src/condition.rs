@@ -0,0 +1,372 @@
+use crate::client::extract_json_path;
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::error::Error;
+
+// 関数・変数名
+const OPERAND_STATUS: &str = "status";
+const OPERAND_JSON: &str = "json";
+const OPERAND_HEADER: &str = "header";
+const LITERAL_TRUE: &str = "true";
+const LITERAL_FALSE: &str = "false";
+const LITERAL_NULL: &str = "null";
+
+// エラーメッセージ
+const ERROR_INVALID_CONDITION: &str = "Invalid condition '{1}': {2}";
+const ERROR_UNEXPECTED_TOKEN: &str = "unexpected '{}'";
+const ERROR_UNEXPECTED_END: &str = "unexpected end of condition";
+const ERROR_UNCLOSED_STRING: &str = "unclosed string";
+const ERROR_UNCLOSED_CALL: &str = "missing ')' after {}(";
+const ERROR_UNKNOWN_NAME: &str = "unknown name '{}' (use status, json(.path), header(Name))";
+
+/// 条件式の字句
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Call(String, String),
+    Literal(Value),
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// 比較演算子
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// 比較の対象
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Status,
+    Json(String),
+    Header(String),
+    Literal(Value),
+}
+
+/// 条件式の構文木
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    Truthy(Operand),
+}
+
+/// レスポンスに対する条件（--until・--retry-if-body）
+///
+/// `status`、`json(.path)`、`header(Name)` と文字列・数値・true/false/null を
+/// `==` `!=` `<` `<=` `>` `>=` で比較し、`&&` `||` `!` と括弧で組み合わせる。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    expr: Expr,
+}
+
+impl Condition {
+    /// 条件式を解析
+    pub fn parse(source: &str) -> Result<Self, Box<dyn Error>> {
+        let invalid = |reason: String| -> Box<dyn Error> {
+            ERROR_INVALID_CONDITION
+                .replace("{1}", source)
+                .replace("{2}", &reason)
+                .into()
+        };
+
+        let tokens = tokenize(source).map_err(invalid)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let expr = parser.parse_or().map_err(invalid)?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid(
+                ERROR_UNEXPECTED_TOKEN.replace("{}", &describe_token(token)),
+            ));
+        }
+
+        Ok(Self { expr })
+    }
+
+    /// レスポンスが条件を満たすか（JSONでないボディの json(...) は null として扱う）
+    pub fn evaluate(&self, status: u16, headers: &HeaderMap, body: &str) -> bool {
+        let context = EvaluationContext {
+            status,
+            headers,
+            body: serde_json::from_str::<Value>(body).ok(),
+        };
+        context.evaluate(&self.expr)
+    }
+}
+
+/// 条件式を字句に分割
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Compare(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Compare(CompareOp::Ne), 2),
+            ('<', Some('=')) => (Token::Compare(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Compare(CompareOp::Ge), 2),
+            ('<', _) => (Token::Compare(CompareOp::Lt), 1),
+            ('>', _) => (Token::Compare(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"', _) | ('\'', _) => {
+                let end = chars[index + 1..]
+                    .iter()
+                    .position(|candidate| *candidate == c)
+                    .ok_or(ERROR_UNCLOSED_STRING)?;
+                let text: String = chars[index + 1..index + 1 + end].iter().collect();
+                (Token::Literal(Value::String(text)), end + 2)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' => {
+                let length = chars[index + 1..]
+                    .iter()
+                    .take_while(|candidate| candidate.is_ascii_digit() || **candidate == '.')
+                    .count()
+                    + 1;
+                let text: String = chars[index..index + length].iter().collect();
+                let number = serde_json::from_str::<Value>(&text)
+                    .map_err(|_| ERROR_UNEXPECTED_TOKEN.replace("{}", &text))?;
+                (Token::Literal(number), length)
+            }
+            (c, _) if c.is_ascii_alphabetic() => {
+                let length = chars[index..]
+                    .iter()
+                    .take_while(|candidate| candidate.is_ascii_alphanumeric() || **candidate == '_')
+                    .count();
+                let name: String = chars[index..index + length].iter().collect();
+
+                // json(...)・header(...) の引数は閉じ括弧までをそのまま使う
+                if chars.get(index + length) == Some(&'(') {
+                    let argument_start = index + length + 1;
+                    let end = chars[argument_start..]
+                        .iter()
+                        .position(|candidate| *candidate == ')')
+                        .ok_or_else(|| ERROR_UNCLOSED_CALL.replace("{}", &name))?;
+                    let argument: String = chars[argument_start..argument_start + end]
+                        .iter()
+                        .collect();
+                    (
+                        Token::Call(name, argument.trim().to_string()),
+                        length + end + 2,
+                    )
+                } else {
+                    (Token::Name(name), length)
+                }
+            }
+            (c, _) => return Err(ERROR_UNEXPECTED_TOKEN.replace("{}", &c.to_string())),
+        };
+
+        tokens.push(token);
+        index += length;
+    }
+
+    Ok(tokens)
+}
+
+/// 字句の表示（エラーメッセージ用）
+fn describe_token(token: &Token) -> String {
+    match token {
+        Token::Name(name) => name.clone(),
+        Token::Call(name, argument) => format!("{}({})", name, argument),
+        Token::Literal(value) => value.to_string(),
+        Token::Compare(op) => match op {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+        .to_string(),
+        Token::And => "&&".to_string(),
+        Token::Or => "||".to_string(),
+        Token::Not => "!".to_string(),
+        Token::Open => "(".to_string(),
+        Token::Close => ")".to_string(),
+    }
+}
+
+/// 再帰下降パーサー（優先順位は ! > 比較 > && > ||）
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<&Token, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or(ERROR_UNEXPECTED_END)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.position += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Open) => {
+                self.position += 1;
+                let expr = self.parse_or()?;
+                match self.next()? {
+                    Token::Close => Ok(expr),
+                    token => Err(ERROR_UNEXPECTED_TOKEN.replace("{}", &describe_token(token))),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_operand()?;
+        match self.peek() {
+            Some(Token::Compare(op)) => {
+                let op = *op;
+                self.position += 1;
+                Ok(Expr::Compare(left, op, self.parse_operand()?))
+            }
+            _ => Ok(Expr::Truthy(left)),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        match self.next()? {
+            Token::Name(name) => match name.as_str() {
+                OPERAND_STATUS => Ok(Operand::Status),
+                LITERAL_TRUE => Ok(Operand::Literal(Value::Bool(true))),
+                LITERAL_FALSE => Ok(Operand::Literal(Value::Bool(false))),
+                LITERAL_NULL => Ok(Operand::Literal(Value::Null)),
+                _ => Err(ERROR_UNKNOWN_NAME.replace("{}", name)),
+            },
+            Token::Call(name, argument) => match name.as_str() {
+                OPERAND_JSON => Ok(Operand::Json(argument.clone())),
+                OPERAND_HEADER => Ok(Operand::Header(argument.clone())),
+                _ => Err(ERROR_UNKNOWN_NAME.replace("{}", name)),
+            },
+            Token::Literal(value) => Ok(Operand::Literal(value.clone())),
+            token => Err(ERROR_UNEXPECTED_TOKEN.replace("{}", &describe_token(token))),
+        }
+    }
+}
+
+/// 評価に使うレスポンスの値
+struct EvaluationContext<'a> {
+    status: u16,
+    headers: &'a HeaderMap,
+    body: Option<Value>,
+}
+
+impl EvaluationContext<'_> {
+    fn evaluate(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Or(left, right) => self.evaluate(left) || self.evaluate(right),
+            Expr::And(left, right) => self.evaluate(left) && self.evaluate(right),
+            Expr::Not(inner) => !self.evaluate(inner),
+            Expr::Compare(left, op, right) => {
+                compare(&self.value(left), *op, &self.value(right))
+            }
+            Expr::Truthy(operand) => is_truthy(&self.value(operand)),
+        }
+    }
+
+    fn value(&self, operand: &Operand) -> Value {
+        match operand {
+            Operand::Status => Value::from(self.status),
+            Operand::Json(path) => self
+                .body
+                .clone()
+                .and_then(|body| extract_json_path(body, path).ok())
+                .unwrap_or(Value::Null),
+            Operand::Header(name) => self
+                .headers
+                .get(name.as_str())
+                .map(|value| Value::String(String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .unwrap_or(Value::Null),
+            Operand::Literal(value) => value.clone(),
+        }
+    }
+}
+
+/// 値を比較（数値は数値として、文字列は辞書順で大小を比較）
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left
+            .as_f64()
+            .zip(right.as_f64())
+            .and_then(|(left, right)| left.partial_cmp(&right)),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => (left == right).then_some(Ordering::Equal),
+    };
+
+    match op {
+        CompareOp::Eq => ordering == Some(Ordering::Equal),
+        CompareOp::Ne => ordering != Some(Ordering::Equal),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+/// 単独で書かれた値の真偽（null・false・0・空文字列・空の配列やオブジェクトは偽）
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64().is_some_and(|number| number != 0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
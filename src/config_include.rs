@@ -0,0 +1,141 @@
+use crate::header_filter::wildcard_match;
+use crate::util::home_dir;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 取り込みの指定
+const INCLUDE_KEY: &str = "include";
+const HOME_PREFIX: &str = "~/";
+const WILDCARD_CHARS: [char; 2] = ['*', '?'];
+
+// エラーメッセージ
+const ERROR_INVALID_INCLUDE: &str = "Invalid include in {}. Use include = [\"file.toml\", ...].";
+const ERROR_INCLUDE_NOT_FOUND: &str = "Included config file {1} not found (from {2})";
+const ERROR_INCLUDE_CYCLE: &str = "Config file {} includes itself";
+const ERROR_MISSING_HOME: &str = "Could not expand ~ in include (HOME is not set)";
+
+/// 設定ファイルを読み込み、`include` で指定したファイルを先に取り込んでマージ
+///
+/// 取り込んだファイルを指定順に重ね、最後に読み込んだファイル自身の値で上書きする。
+/// テーブルはキーごとにマージするため、共通のプリセットの一部の値だけを上書きできる。
+pub fn read_config_value(config_path: &str) -> Result<toml::Value, Box<dyn Error>> {
+    let table = read_config_table(Path::new(config_path), &mut Vec::new())?;
+    Ok(toml::Value::Table(table))
+}
+
+/// 1ファイルを読み込み、取り込むファイルを再帰的にマージ（stack は取り込み中のファイル）
+fn read_config_table(path: &Path, stack: &mut Vec<PathBuf>) -> Result<toml::Table, Box<dyn Error>> {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        return Err(ERROR_INCLUDE_CYCLE
+            .replace("{}", &path.display().to_string())
+            .into());
+    }
+
+    let mut table: toml::Table = toml::from_str(&fs::read_to_string(path)?)?;
+    let Some(include) = table.remove(INCLUDE_KEY) else {
+        return Ok(table);
+    };
+
+    stack.push(canonical);
+    let mut merged = read_included_tables(path, &include, stack)?;
+    stack.pop();
+
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// 設定ファイルが `include` で取り込むファイルの値だけをマージして返す（設定ファイル自身の値は含めない）
+///
+/// 設定ファイルを書き換える際に、取り込んだファイルで定義されている値を判定するために使う。
+pub fn read_included_value(config_path: &str) -> Result<toml::Table, Box<dyn Error>> {
+    let path = Path::new(config_path);
+    let table: toml::Table = toml::from_str(&fs::read_to_string(path)?)?;
+    let Some(include) = table.get(INCLUDE_KEY) else {
+        return Ok(toml::Table::new());
+    };
+
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    read_included_tables(path, include, &mut vec![canonical])
+}
+
+/// include に指定したファイルを指定順に読み込んでマージ
+fn read_included_tables(
+    path: &Path,
+    include: &toml::Value,
+    stack: &mut Vec<PathBuf>,
+) -> Result<toml::Table, Box<dyn Error>> {
+    let invalid = || ERROR_INVALID_INCLUDE.replace("{}", &path.display().to_string());
+    let patterns = include
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|pattern| pattern.as_str().ok_or_else(invalid))
+        .collect::<Result<Vec<&str>, String>>()?;
+
+    let mut merged = toml::Table::new();
+    for pattern in patterns {
+        for included in resolve_include(path, pattern)? {
+            merge_tables(&mut merged, read_config_table(&included, stack)?);
+        }
+    }
+    Ok(merged)
+}
+
+/// include の1項目をファイルパスに展開
+///
+/// `~/` はホームディレクトリ、相対パスは取り込み元のファイルの位置から解決する。
+/// ファイル名に `*`・`?` を含む場合は一致するファイルを名前順に返す（一致しなくてもエラーにしない）。
+fn resolve_include(config_path: &Path, pattern: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let path = match pattern.strip_prefix(HOME_PREFIX) {
+        Some(rest) => home_dir().ok_or(ERROR_MISSING_HOME)?.join(rest),
+        None => config_path.parent().unwrap_or(Path::new("")).join(pattern),
+    };
+
+    let file_pattern = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| name.contains(WILDCARD_CHARS));
+    let Some(file_pattern) = file_pattern else {
+        if !path.is_file() {
+            return Err(ERROR_INCLUDE_NOT_FOUND
+                .replace("{1}", &path.display().to_string())
+                .replace("{2}", &config_path.display().to_string())
+                .into());
+        }
+        return Ok(vec![path]);
+    };
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Ok(Vec::new());
+    };
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| wildcard_match(file_pattern, name))
+        })
+        .collect();
+    matches.sort();
+
+    Ok(matches)
+}
+
+/// overlay の値を base に重ねる（両方がテーブルの場合はキーごとに再帰的にマージ）
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use reqwest::blocking::Response;
+use reqwest::header::CONNECTION;
+use rustls::client::{ClientSessionStore, Tls12ClientSessionValue, Tls13ClientSessionValue};
+use rustls::pki_types::ServerName;
+use rustls::NamedGroup;
+use serde::Serialize;
+use std::error::Error;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+// 接続を閉じる指定（Connection: close）
+const CONNECTION_CLOSE: &str = "close";
+
+// サーバーが接続を閉じたことを示すエラー（hyperのIncompleteMessage）
+const CONNECTION_CLOSED_ERROR: &str = "connection closed";
+
+// 表示メッセージ
+const CONNECTION_OPENED_MSG: &str = "* Connection #{} opened";
+const CONNECTION_OPENED_TLS_MSG: &str = "* Connection #{1} opened (TLS {2})";
+const TLS_FULL_HANDSHAKE: &str = "full handshake";
+const TLS_SESSION_RESUMED: &str = "session resumed";
+const CONNECTION_CLOSE_MSG: &str = "* Connection will be closed by the server (Connection: close)";
+const CONNECTION_CLOSED_MSG: &str = "* Connection closed by the server: {}";
+const CONNECTIONS_MSG: &str =
+    "Connections: {1} opened, {2} reused, {3} closed by peer, {4} TLS handshakes ({5} resumed)";
+
+/// 接続の集計
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ConnectionSummary {
+    pub opened: u64,
+    pub reused: u64,
+    pub closed_by_peer: u64,
+    pub handshakes: u64,
+    /// セッションを再開した（サーバー証明書の検証を省略した）TLSハンドシェイク数
+    pub resumed: u64,
+}
+
+#[derive(Debug, Default)]
+struct ConnectionCounters {
+    opened: AtomicU64,
+    handshakes: AtomicU64,
+    full_handshakes: AtomicU64,
+    closed_by_peer: AtomicU64,
+    requests: AtomicU64,
+}
+
+/// コネクションプールの接続数・再利用数・切断数・TLSハンドシェイク数を記録（詳細出力時は都度表示）
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStats {
+    counters: Arc<ConnectionCounters>,
+    verbose: bool,
+}
+
+impl ConnectionStats {
+    pub fn new(verbose: bool) -> Self {
+        Self {
+            counters: Arc::default(),
+            verbose,
+        }
+    }
+
+    /// 送信したリクエストの結果を記録（Connection: close・切断によるエラーはサーバーによる切断として数える）
+    pub fn record_request(&self, result: Result<&Response, &reqwest::Error>) {
+        let closed_by_peer = match result {
+            Ok(response) => {
+                self.counters.requests.fetch_add(1, Ordering::SeqCst);
+                let closing = response
+                    .headers()
+                    .get(CONNECTION)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| value.eq_ignore_ascii_case(CONNECTION_CLOSE));
+                if closing && self.verbose {
+                    eprintln!("{}", CONNECTION_CLOSE_MSG);
+                }
+                closing
+            }
+            Err(e) => {
+                let closed = is_closed_by_peer(e);
+                if closed && self.verbose {
+                    eprintln!("{}", CONNECTION_CLOSED_MSG.replace("{}", &e.to_string()));
+                }
+                closed
+            }
+        };
+        if closed_by_peer {
+            self.counters.closed_by_peer.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// これまでの集計（新しい接続を開かずにレスポンスを受信したリクエストを再利用として数える）
+    pub fn summary(&self) -> ConnectionSummary {
+        let opened = self.counters.opened.load(Ordering::SeqCst);
+        let handshakes = self.counters.handshakes.load(Ordering::SeqCst);
+        ConnectionSummary {
+            opened,
+            reused: self
+                .counters
+                .requests
+                .load(Ordering::SeqCst)
+                .saturating_sub(opened),
+            closed_by_peer: self.counters.closed_by_peer.load(Ordering::SeqCst),
+            handshakes,
+            resumed: handshakes.saturating_sub(self.counters.full_handshakes.load(Ordering::SeqCst)),
+        }
+    }
+
+    /// サーバー証明書を検証したハンドシェイク（セッションを再開しなかったもの）を記録
+    pub fn record_full_handshake(&self) {
+        self.counters.full_handshakes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// TLSハンドシェイク数と、そのうちサーバー証明書を検証した数
+    fn handshake_counts(&self) -> (u64, u64) {
+        (
+            self.counters.handshakes.load(Ordering::SeqCst),
+            self.counters.full_handshakes.load(Ordering::SeqCst),
+        )
+    }
+}
+
+/// エラーの原因をたどり、サーバーが接続を閉じたことによる失敗か判定
+fn is_closed_by_peer(error: &reqwest::Error) -> bool {
+    let mut source: Option<&(dyn Error + 'static)> = Some(error);
+    while let Some(current) = source {
+        if let Some(io_error) = current.downcast_ref::<io::Error>() {
+            if matches!(
+                io_error.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof
+            ) {
+                return true;
+            }
+        }
+        if current.to_string().contains(CONNECTION_CLOSED_ERROR) {
+            return true;
+        }
+        source = current.source();
+    }
+    false
+}
+
+/// 接続の集計を1行で表示
+pub fn display_connection_summary(summary: &ConnectionSummary) {
+    eprintln!(
+        "{}",
+        CONNECTIONS_MSG
+            .replace("{1}", &summary.opened.to_string())
+            .replace("{2}", &summary.reused.to_string())
+            .replace("{3}", &summary.closed_by_peer.to_string())
+            .replace("{4}", &summary.handshakes.to_string())
+            .replace("{5}", &summary.resumed.to_string())
+    );
+}
+
+/// 新しく開いた接続を数えるレイヤー
+#[derive(Clone)]
+pub struct ConnectionCountingLayer {
+    stats: ConnectionStats,
+}
+
+impl ConnectionCountingLayer {
+    pub fn new(stats: ConnectionStats) -> Self {
+        Self { stats }
+    }
+}
+
+impl<S> Layer<S> for ConnectionCountingLayer {
+    type Service = ConnectionCountingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectionCountingService {
+            inner,
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConnectionCountingService<S> {
+    inner: S,
+    stats: ConnectionStats,
+}
+
+impl<S, R> Service<R> for ConnectionCountingService<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let connecting = self.inner.call(request);
+        let stats = self.stats.clone();
+        let handshakes_before = stats.handshake_counts();
+
+        Box::pin(async move {
+            let connection = connecting.await;
+            if connection.is_ok() {
+                let number = stats.counters.opened.fetch_add(1, Ordering::SeqCst) + 1;
+                if stats.verbose {
+                    eprintln!("{}", opened_message(number, handshakes_before, stats.handshake_counts()));
+                }
+            }
+            connection
+        })
+    }
+}
+
+/// 接続を開いたときの表示（接続中に行ったTLSハンドシェイクが再開かどうかを含める）
+///
+/// 同時に複数の接続を開く場合は、ほかの接続のハンドシェイクと区別できないことがある。
+fn opened_message(number: u64, before: (u64, u64), after: (u64, u64)) -> String {
+    if after.0 == before.0 {
+        return CONNECTION_OPENED_MSG.replace("{}", &number.to_string());
+    }
+    let handshake = if after.1 > before.1 {
+        TLS_FULL_HANDSHAKE
+    } else {
+        TLS_SESSION_RESUMED
+    };
+    CONNECTION_OPENED_TLS_MSG
+        .replace("{1}", &number.to_string())
+        .replace("{2}", handshake)
+}
+
+/// ClientHelloの作成時（セッション再開情報の取得時）をTLSハンドシェイクとして数える
+#[derive(Debug)]
+pub struct HandshakeCounter {
+    inner: Arc<dyn ClientSessionStore>,
+    stats: ConnectionStats,
+}
+
+impl HandshakeCounter {
+    pub fn new(inner: Arc<dyn ClientSessionStore>, stats: ConnectionStats) -> Self {
+        Self { inner, stats }
+    }
+}
+
+impl ClientSessionStore for HandshakeCounter {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.inner.set_kx_hint(server_name, group);
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.inner.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.inner.set_tls12_session(server_name, value);
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.inner.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.inner.remove_tls12_session(server_name);
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.inner.insert_tls13_ticket(server_name, value);
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        // ハンドシェイクの開始時に必ず呼ばれる
+        self.stats
+            .counters
+            .handshakes
+            .fetch_add(1, Ordering::SeqCst);
+        self.inner.take_tls13_ticket(server_name)
+    }
+}
@@ -0,0 +1,198 @@
+use crate::util::unix_now;
+use reqwest::cookie::Jar;
+use reqwest::Url;
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+
+// Netscape形式（cookies.txt）
+const NETSCAPE_FIELD_SEPARATOR: char = '\t';
+const NETSCAPE_FIELD_COUNT: usize = 7;
+const NETSCAPE_COMMENT_PREFIX: char = '#';
+const NETSCAPE_HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+const NETSCAPE_TRUE: &str = "TRUE";
+
+// JSON形式（ブラウザ拡張機能のエクスポート）
+const JSON_ARRAY_START: char = '[';
+
+// クッキーの登録
+const DOMAIN_DOT: char = '.';
+const SECURE_SCHEME: &str = "https";
+const INSECURE_SCHEME: &str = "http";
+const DEFAULT_COOKIE_PATH: &str = "/";
+
+// エラーメッセージ
+const ERROR_INVALID_NETSCAPE_LINE: &str =
+    "Invalid cookie at {1}:{2}. Expected 7 tab-separated fields (Netscape cookies.txt).";
+const ERROR_INVALID_COOKIE_JSON: &str = "Invalid cookie JSON in {1}: {2}";
+
+/// ファイルから読み込んだクッキー
+#[derive(Debug)]
+struct ImportedCookie {
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    /// 有効期限（UNIX時間の秒）。None はセッションクッキー
+    expires: Option<u64>,
+    name: String,
+    value: String,
+}
+
+/// ブラウザ拡張機能（EditThisCookie・Cookie-Editor など）がエクスポートするJSONの1要素
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonCookie {
+    domain: String,
+    #[serde(default)]
+    host_only: bool,
+    path: Option<String>,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    http_only: bool,
+    #[serde(default)]
+    session: bool,
+    expiration_date: Option<f64>,
+    name: String,
+    value: String,
+}
+
+/// クッキーファイル（Netscape形式またはJSON）を読み込み、期限切れでないクッキーをJarに登録
+///
+/// 登録件数を返す。
+pub fn import_cookie_file(jar: &Jar, path: &str) -> Result<usize, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let cookies = if contents.trim_start().starts_with(JSON_ARRAY_START) {
+        parse_json_cookies(&contents, path)?
+    } else {
+        parse_netscape_cookies(&contents, path)?
+    };
+
+    let now = unix_now();
+    let mut imported = 0;
+    for cookie in cookies
+        .iter()
+        .filter(|cookie| cookie.expires.is_none_or(|expires| expires > now))
+    {
+        let url = cookie_url(cookie)?;
+        jar.add_cookie_str(&set_cookie_string(cookie, now), &url);
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+/// Netscape形式（domain, subdomains, path, secure, expires, name, value のタブ区切り）を解析
+fn parse_netscape_cookies(
+    contents: &str,
+    path: &str,
+) -> Result<Vec<ImportedCookie>, Box<dyn Error>> {
+    let mut cookies = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        // HttpOnly のクッキーは "#HttpOnly_" を付けたドメインで書き出される
+        let (line, http_only) = match line.strip_prefix(NETSCAPE_HTTP_ONLY_PREFIX) {
+            Some(rest) => (rest, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with(NETSCAPE_COMMENT_PREFIX) {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(NETSCAPE_FIELD_SEPARATOR).collect();
+        let invalid = || {
+            ERROR_INVALID_NETSCAPE_LINE
+                .replace("{1}", path)
+                .replace("{2}", &(index + 1).to_string())
+        };
+        if fields.len() != NETSCAPE_FIELD_COUNT {
+            return Err(invalid().into());
+        }
+        let expires: u64 = fields[4].trim().parse().map_err(|_| invalid())?;
+
+        cookies.push(ImportedCookie {
+            domain: fields[0].to_string(),
+            include_subdomains: fields[1].eq_ignore_ascii_case(NETSCAPE_TRUE),
+            path: fields[2].to_string(),
+            secure: fields[3].eq_ignore_ascii_case(NETSCAPE_TRUE),
+            http_only,
+            // 有効期限0はセッションクッキー
+            expires: (expires > 0).then_some(expires),
+            name: fields[5].to_string(),
+            value: fields[6].to_string(),
+        });
+    }
+
+    Ok(cookies)
+}
+
+/// ブラウザ拡張機能のJSON（クッキーの配列）を解析
+fn parse_json_cookies(contents: &str, path: &str) -> Result<Vec<ImportedCookie>, Box<dyn Error>> {
+    let cookies: Vec<JsonCookie> = serde_json::from_str(contents).map_err(|e| {
+        ERROR_INVALID_COOKIE_JSON
+            .replace("{1}", path)
+            .replace("{2}", &e.to_string())
+    })?;
+
+    Ok(cookies
+        .into_iter()
+        .map(|cookie| ImportedCookie {
+            include_subdomains: !cookie.host_only,
+            domain: cookie.domain,
+            path: cookie
+                .path
+                .unwrap_or_else(|| DEFAULT_COOKIE_PATH.to_string()),
+            secure: cookie.secure,
+            http_only: cookie.http_only,
+            expires: cookie
+                .expiration_date
+                .filter(|_| !cookie.session)
+                .map(|expires| expires as u64),
+            name: cookie.name,
+            value: cookie.value,
+        })
+        .collect())
+}
+
+/// クッキーを登録するときの送信元URL（ドメインとパスからJarが適用範囲を判定する）
+fn cookie_url(cookie: &ImportedCookie) -> Result<Url, Box<dyn Error>> {
+    let scheme = if cookie.secure {
+        SECURE_SCHEME
+    } else {
+        INSECURE_SCHEME
+    };
+    let host = cookie.domain.trim_start_matches(DOMAIN_DOT);
+
+    Ok(Url::parse(&format!(
+        "{}://{}{}",
+        scheme, host, cookie.path
+    ))?)
+}
+
+/// Jarに渡す Set-Cookie 形式の文字列（サブドメインを含む場合のみ Domain 属性を付ける）
+fn set_cookie_string(cookie: &ImportedCookie, now: u64) -> String {
+    let mut attributes = vec![
+        format!("{}={}", cookie.name, cookie.value),
+        format!("Path={}", cookie.path),
+    ];
+
+    if cookie.include_subdomains {
+        attributes.push(format!(
+            "Domain={}",
+            cookie.domain.trim_start_matches(DOMAIN_DOT)
+        ));
+    }
+    if let Some(expires) = cookie.expires {
+        attributes.push(format!("Max-Age={}", expires.saturating_sub(now)));
+    }
+    if cookie.secure {
+        attributes.push("Secure".to_string());
+    }
+    if cookie.http_only {
+        attributes.push("HttpOnly".to_string());
+    }
+
+    attributes.join("; ")
+}
@@ -0,0 +1,176 @@
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+    ORIGIN,
+};
+use reqwest::Method;
+use std::error::Error;
+
+// Access-Control-* の値
+const WILDCARD: &str = "*";
+const ALLOW_CREDENTIALS_TRUE: &str = "true";
+const LIST_SEPARATOR: char = ',';
+
+// 事前リクエストなしで許可されるメソッド
+const SIMPLE_METHODS: [Method; 3] = [Method::GET, Method::HEAD, Method::POST];
+
+// エラーメッセージ
+const ERROR_PREFLIGHT_FAILED: &str = "CORS preflight for {1} would block {2} requests from {3}";
+
+// 表示メッセージ
+const PREFLIGHT_HEADER: &str = "--- CORS Preflight ---";
+const PREFLIGHT_REQUEST_MSG: &str = "OPTIONS {1} (Origin: {2})";
+const CHECK_PASS_MSG: &str = "PASS {1}: {2}";
+const CHECK_FAIL_MSG: &str = "FAIL {1}: {2}";
+const MISSING_HEADER_MSG: &str = "<missing>";
+const STATUS_LABEL: &str = "Status";
+const VERDICT_PASS_MSG: &str = "Verdict: PASS - the browser would send the actual request";
+const VERDICT_FAIL_MSG: &str = "Verdict: FAIL - the browser would block the actual request";
+
+/// 確認する実リクエストの内容
+pub struct CorsRequest<'a> {
+    pub url: &'a str,
+    pub origin: &'a str,
+    pub method: &'a str,
+    pub headers: &'a [String],
+    pub credentials: bool,
+}
+
+/// 1項目の判定結果
+struct CorsCheck {
+    label: String,
+    value: String,
+    passed: bool,
+}
+
+/// プリフライトリクエスト（OPTIONS）を送信し、実リクエストが許可されるかを判定
+pub fn check_cors_preflight(client: &Client, request: &CorsRequest) -> Result<(), Box<dyn Error>> {
+    let method = Method::from_bytes(request.method.to_uppercase().as_bytes())?;
+
+    let mut preflight = client
+        .request(Method::OPTIONS, request.url)
+        .header(ORIGIN, request.origin)
+        .header(ACCESS_CONTROL_REQUEST_METHOD, method.as_str());
+    if !request.headers.is_empty() {
+        preflight = preflight.header(
+            ACCESS_CONTROL_REQUEST_HEADERS,
+            request.headers.join(&LIST_SEPARATOR.to_string()),
+        );
+    }
+    let response = preflight.send()?;
+
+    let checks = evaluate_preflight(&response, request, &method);
+
+    println!("{}", PREFLIGHT_HEADER);
+    println!(
+        "{}",
+        PREFLIGHT_REQUEST_MSG
+            .replace("{1}", request.url)
+            .replace("{2}", request.origin)
+    );
+    for check in &checks {
+        let template = if check.passed {
+            CHECK_PASS_MSG
+        } else {
+            CHECK_FAIL_MSG
+        };
+        println!(
+            "{}",
+            template
+                .replace("{1}", &check.label)
+                .replace("{2}", &check.value)
+        );
+    }
+
+    if checks.iter().all(|check| check.passed) {
+        println!("{}", VERDICT_PASS_MSG);
+        Ok(())
+    } else {
+        println!("{}", VERDICT_FAIL_MSG);
+        Err(ERROR_PREFLIGHT_FAILED
+            .replace("{1}", request.url)
+            .replace("{2}", method.as_str())
+            .replace("{3}", request.origin)
+            .into())
+    }
+}
+
+/// プリフライトのレスポンスを判定
+fn evaluate_preflight(
+    response: &Response,
+    request: &CorsRequest,
+    method: &Method,
+) -> Vec<CorsCheck> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let status = response.status();
+    let mut checks = vec![CorsCheck {
+        label: STATUS_LABEL.to_string(),
+        value: status.to_string(),
+        passed: status.is_success(),
+    }];
+
+    let allow_origin = header(ACCESS_CONTROL_ALLOW_ORIGIN);
+    checks.push(CorsCheck {
+        label: ACCESS_CONTROL_ALLOW_ORIGIN.to_string(),
+        value: allow_origin.unwrap_or(MISSING_HEADER_MSG).to_string(),
+        passed: allow_origin.is_some_and(|origin| {
+            origin == request.origin || (origin == WILDCARD && !request.credentials)
+        }),
+    });
+
+    if request.credentials {
+        let allow_credentials = header(ACCESS_CONTROL_ALLOW_CREDENTIALS);
+        checks.push(CorsCheck {
+            label: ACCESS_CONTROL_ALLOW_CREDENTIALS.to_string(),
+            value: allow_credentials.unwrap_or(MISSING_HEADER_MSG).to_string(),
+            passed: allow_credentials == Some(ALLOW_CREDENTIALS_TRUE),
+        });
+    }
+
+    let allow_methods = header(ACCESS_CONTROL_ALLOW_METHODS);
+    let allowed_methods = split_list(allow_methods);
+    checks.push(CorsCheck {
+        label: ACCESS_CONTROL_ALLOW_METHODS.to_string(),
+        value: allow_methods.unwrap_or(MISSING_HEADER_MSG).to_string(),
+        passed: SIMPLE_METHODS.contains(method)
+            || allowed_methods.iter().any(|allowed| {
+                allowed == method.as_str() || (allowed == WILDCARD && !request.credentials)
+            }),
+    });
+
+    if !request.headers.is_empty() {
+        let allow_headers = header(ACCESS_CONTROL_ALLOW_HEADERS);
+        let allowed_headers = split_list(allow_headers);
+        let wildcard =
+            allowed_headers.iter().any(|allowed| allowed == WILDCARD) && !request.credentials;
+        checks.push(CorsCheck {
+            label: ACCESS_CONTROL_ALLOW_HEADERS.to_string(),
+            value: allow_headers.unwrap_or(MISSING_HEADER_MSG).to_string(),
+            passed: wildcard
+                || request.headers.iter().all(|requested| {
+                    allowed_headers
+                        .iter()
+                        .any(|allowed| allowed.eq_ignore_ascii_case(requested.trim()))
+                }),
+        });
+    }
+
+    checks
+}
+
+/// カンマ区切りのヘッダー値を分割
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(LIST_SEPARATOR)
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
@@ -0,0 +1,99 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// 表示メッセージ
+const DNS_CACHE_HIT_MSG: &str = "* DNS cache hit: {1} ({2})";
+const DNS_RESOLVED_MSG: &str = "* DNS resolved: {1} ({2})";
+
+/// 名前解決の結果をTTLの間キャッシュするリゾルバ
+pub struct CachingResolver {
+    ttl: Duration,
+    verbose: bool,
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+struct CacheEntry {
+    addrs: Vec<SocketAddr>,
+    resolved_at: Instant,
+}
+
+impl CachingResolver {
+    pub fn new(ttl: Duration, verbose: bool) -> Self {
+        Self {
+            ttl,
+            verbose,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 有効期限内のキャッシュを取得
+    fn lookup(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(host)
+            .filter(|entry| entry.resolved_at.elapsed() < self.ttl)
+            .map(|entry| entry.addrs.clone())
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+
+        if let Some(addrs) = self.lookup(&host) {
+            if self.verbose {
+                eprintln!(
+                    "{}",
+                    DNS_CACHE_HIT_MSG
+                        .replace("{1}", &host)
+                        .replace("{2}", &format_addrs(&addrs))
+                );
+            }
+            return Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) });
+        }
+
+        let entries = Arc::clone(&self.entries);
+        let verbose = self.verbose;
+
+        Box::pin(async move {
+            let lookup_host = host.clone();
+            let addrs: Vec<SocketAddr> =
+                tokio::task::spawn_blocking(move || (lookup_host.as_str(), 0).to_socket_addrs())
+                    .await??
+                    .collect();
+
+            if verbose {
+                eprintln!(
+                    "{}",
+                    DNS_RESOLVED_MSG
+                        .replace("{1}", &host)
+                        .replace("{2}", &format_addrs(&addrs))
+                );
+            }
+
+            if let Ok(mut entries) = entries.lock() {
+                entries.insert(
+                    host,
+                    CacheEntry {
+                        addrs: addrs.clone(),
+                        resolved_at: Instant::now(),
+                    },
+                );
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// アドレス一覧を表示用の文字列に変換
+fn format_addrs(addrs: &[SocketAddr]) -> String {
+    addrs
+        .iter()
+        .map(|addr| addr.ip().to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -0,0 +1,77 @@
+use crate::client::Config;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// URLの判定
+const SCHEME_SEPARATOR: &str = "://";
+const PATH_SEPARATOR: char = '/';
+const HEADER_SEPARATOR: char = ':';
+
+/// 接続先ごとの設定（[environments.<名前>]）
+///
+/// `--env` で選んだ環境をプリセットに重ねることで、同じプリセットを dev・staging・prod で共用できる。
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Environment {
+    /// プリセットの相対URL（/users など）の前に付けるURL
+    pub base_url: Option<String>,
+    /// プリセットにない名前のヘッダーを追加
+    pub headers: Option<Vec<String>>,
+    /// `{{変数}}` の値（プリセットの vars より優先）
+    pub variables: Option<HashMap<String, String>>,
+}
+
+impl Environment {
+    /// プリセットから作成した設定に環境の値を重ねる
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(base_url) = &self.base_url {
+            config.url = join_base_url(base_url, &config.url);
+        }
+
+        if let Some(headers) = &self.headers {
+            let preset_headers = config.headers.take().unwrap_or_default();
+            let mut merged: Vec<String> = headers
+                .iter()
+                .filter(|header| {
+                    !preset_headers.iter().any(|preset_header| {
+                        header_name(preset_header).eq_ignore_ascii_case(header_name(header))
+                    })
+                })
+                .cloned()
+                .collect();
+            merged.extend(preset_headers);
+            config.headers = Some(merged);
+        }
+
+        if let Some(variables) = &self.variables {
+            config
+                .vars
+                .get_or_insert_with(Default::default)
+                .extend(variables.clone());
+        }
+    }
+}
+
+/// 相対URLにベースURLを付ける（スキーム付きのURLはそのまま、空の場合はベースURL）
+fn join_base_url(base_url: &str, url: &str) -> String {
+    if url.contains(SCHEME_SEPARATOR) {
+        return url.to_string();
+    }
+    if url.is_empty() {
+        return base_url.to_string();
+    }
+
+    format!(
+        "{}{}{}",
+        base_url.trim_end_matches(PATH_SEPARATOR),
+        PATH_SEPARATOR,
+        url.trim_start_matches(PATH_SEPARATOR)
+    )
+}
+
+/// "Name: value" 形式のヘッダーの名前
+fn header_name(header: &str) -> &str {
+    header
+        .split_once(HEADER_SEPARATOR)
+        .map_or(header, |(name, _)| name)
+        .trim()
+}
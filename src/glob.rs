@@ -0,0 +1,203 @@
+use std::error::Error;
+
+// グロブ記法
+const SET_OPEN: char = '{';
+const SET_CLOSE: char = '}';
+const SET_SEPARATOR: char = ',';
+const RANGE_OPEN: char = '[';
+const RANGE_CLOSE: char = ']';
+const RANGE_SEPARATOR: char = '-';
+const RANGE_STEP_SEPARATOR: char = ':';
+const ESCAPE_CHAR: char = '\\';
+const PLACEHOLDER_PREFIX: char = '#';
+
+// 展開数の上限
+const MAX_GLOB_URLS: usize = 100_000;
+
+// エラーメッセージ
+const ERROR_UNCLOSED_GLOB: &str = "Unclosed '{}' in URL glob";
+const ERROR_INVALID_RANGE: &str = "Invalid range '[{}]' in URL glob";
+const ERROR_TOO_MANY_URLS: &str = "URL glob expands to more than {} URLs";
+
+/// グロブを展開したURLと、各グロブで選ばれた値（#1, #2...に対応）
+#[derive(Debug, Clone)]
+pub struct GlobUrl {
+    pub url: String,
+    pub values: Vec<String>,
+}
+
+enum GlobPart {
+    Literal(String),
+    Choices(Vec<String>),
+}
+
+/// curl形式のURLグロブ（{a,b,c} と [1-50]、[a-z]、[001-100:10]）を展開
+pub fn expand_url_glob(url: &str) -> Result<Vec<GlobUrl>, Box<dyn Error>> {
+    let mut expanded = vec![GlobUrl {
+        url: String::new(),
+        values: Vec::new(),
+    }];
+
+    for part in parse_glob(url)? {
+        match part {
+            GlobPart::Literal(text) => {
+                for glob_url in &mut expanded {
+                    glob_url.url.push_str(&text);
+                }
+            }
+            GlobPart::Choices(choices) => {
+                if expanded.len().saturating_mul(choices.len()) > MAX_GLOB_URLS {
+                    return Err(ERROR_TOO_MANY_URLS
+                        .replace("{}", &MAX_GLOB_URLS.to_string())
+                        .into());
+                }
+
+                expanded = expanded
+                    .iter()
+                    .flat_map(|glob_url| {
+                        choices.iter().map(move |choice| {
+                            let mut values = glob_url.values.clone();
+                            values.push(choice.clone());
+                            GlobUrl {
+                                url: format!("{}{}", glob_url.url, choice),
+                                values,
+                            }
+                        })
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// 文字列中の #1, #2... をグロブで選ばれた値に置換
+pub fn apply_glob_placeholders(template: &str, values: &[String]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != PLACEHOLDER_PREFIX {
+            result.push(c);
+            continue;
+        }
+
+        let mut digits = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+
+        match digits
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| index.checked_sub(1))
+            .and_then(|index| values.get(index))
+        {
+            Some(value) => result.push_str(value),
+            None => {
+                result.push(c);
+                result.push_str(&digits);
+            }
+        }
+    }
+
+    result
+}
+
+/// URLをリテラル部分とグロブ部分に分解（\ でグロブ記号をエスケープ）
+fn parse_glob(url: &str) -> Result<Vec<GlobPart>, Box<dyn Error>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = url.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ESCAPE_CHAR => match chars.next() {
+                Some(escaped) => literal.push(escaped),
+                None => literal.push(c),
+            },
+            SET_OPEN | RANGE_OPEN => {
+                let close = if c == SET_OPEN { SET_CLOSE } else { RANGE_CLOSE };
+                let mut body = String::new();
+                let mut closed = false;
+                for next in chars.by_ref() {
+                    if next == close {
+                        closed = true;
+                        break;
+                    }
+                    body.push(next);
+                }
+                if !closed {
+                    return Err(ERROR_UNCLOSED_GLOB.replace("{}", &c.to_string()).into());
+                }
+
+                if !literal.is_empty() {
+                    parts.push(GlobPart::Literal(std::mem::take(&mut literal)));
+                }
+                let choices = if c == SET_OPEN {
+                    body.split(SET_SEPARATOR).map(str::to_string).collect()
+                } else {
+                    expand_range(&body)?
+                };
+                parts.push(GlobPart::Choices(choices));
+            }
+            _ => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        parts.push(GlobPart::Literal(literal));
+    }
+
+    Ok(parts)
+}
+
+/// 範囲指定（1-50、01-10、a-z、1-100:10）を値の一覧に展開
+fn expand_range(body: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let invalid_range = || ERROR_INVALID_RANGE.replace("{}", body);
+
+    let (range, step) = match body.split_once(RANGE_STEP_SEPARATOR) {
+        Some((range, step)) => (range, step.parse::<usize>().map_err(|_| invalid_range())?),
+        None => (body, 1),
+    };
+    if step == 0 {
+        return Err(invalid_range().into());
+    }
+    let (start, end) = range.split_once(RANGE_SEPARATOR).ok_or_else(invalid_range)?;
+
+    if let (Ok(start_number), Ok(end_number)) = (start.parse::<u64>(), end.parse::<u64>()) {
+        if start_number > end_number {
+            return Err(invalid_range().into());
+        }
+        if (end_number - start_number) / step as u64 >= MAX_GLOB_URLS as u64 {
+            return Err(ERROR_TOO_MANY_URLS
+                .replace("{}", &MAX_GLOB_URLS.to_string())
+                .into());
+        }
+        let width = if start.starts_with('0') { start.len() } else { 0 };
+        return Ok((start_number..=end_number)
+            .step_by(step)
+            .map(|number| format!("{:0width$}", number, width = width))
+            .collect());
+    }
+
+    let mut start_chars = start.chars();
+    let mut end_chars = end.chars();
+    match (
+        start_chars.next(),
+        start_chars.next(),
+        end_chars.next(),
+        end_chars.next(),
+    ) {
+        (Some(first), None, Some(last), None)
+            if first.is_ascii_alphabetic() && last.is_ascii_alphabetic() && first <= last =>
+        {
+            Ok((first..=last)
+                .step_by(step)
+                .map(|letter| letter.to_string())
+                .collect())
+        }
+        _ => Err(invalid_range().into()),
+    }
+}
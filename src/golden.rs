@@ -0,0 +1,146 @@
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// 差分表示
+const DIFF_CONTEXT_LINES: usize = 3;
+const DIFF_ACTUAL_LABEL: &str = "response";
+const DIFF_GAP_MARK: &str = "...";
+
+// エラーメッセージ
+const ERROR_GOLDEN_MISMATCH: &str = "Response body does not match {}";
+const ERROR_READ_GOLDEN: &str = "Failed to read {1}: {2}. Use --update-golden to create it.";
+
+// 表示メッセージ
+const GOLDEN_UPDATED_MSG: &str = "* Updated golden file {}";
+
+/// 差分の1行
+#[derive(Debug, PartialEq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// レスポンスボディを期待値ファイルと比較（JSONの場合は整形してから比較）
+pub fn compare_golden_file(body: &str, golden_path: &str) -> Result<(), Box<dyn Error>> {
+    let expected = fs::read_to_string(golden_path).map_err(|e| {
+        ERROR_READ_GOLDEN
+            .replace("{1}", golden_path)
+            .replace("{2}", &e.to_string())
+    })?;
+
+    let expected = normalize_body(&expected);
+    let actual = normalize_body(body);
+    if expected == actual {
+        return Ok(());
+    }
+
+    display_diff(golden_path, DIFF_ACTUAL_LABEL, &expected, &actual);
+    Err(ERROR_GOLDEN_MISMATCH.replace("{}", golden_path).into())
+}
+
+/// 2つのテキストの行単位の差分を標準エラー出力に表示
+pub fn display_diff(old_label: &str, new_label: &str, old: &str, new: &str) {
+    eprintln!("--- {}", old_label);
+    eprintln!("+++ {}", new_label);
+    for line in render_diff(&diff_lines(old, new)) {
+        eprintln!("{}", line);
+    }
+}
+
+/// レスポンスボディで期待値ファイルを更新
+pub fn update_golden_file(body: &str, golden_path: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = Path::new(golden_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    fs::write(golden_path, format!("{}\n", normalize_body(body)))?;
+    eprintln!("{}", GOLDEN_UPDATED_MSG.replace("{}", golden_path));
+    Ok(())
+}
+
+/// 比較用に正規化（JSONは整形、それ以外は末尾の改行を除去）
+pub fn normalize_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| body.to_string()),
+        Err(_) => body.trim_end_matches(['\r', '\n']).to_string(),
+    }
+}
+
+/// 行単位の差分（最長共通部分列）
+fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<DiffLine<'a>> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j]: expected[i..] と actual[j..] の共通部分列の長さ
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < expected.len() && j < actual.len() {
+        if expected[i] == actual[j] {
+            lines.push(DiffLine::Same(expected[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(expected[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(actual[j]));
+            j += 1;
+        }
+    }
+    lines.extend(expected[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(actual[j..].iter().map(|line| DiffLine::Added(line)));
+
+    lines
+}
+
+/// 変更行とその前後の行を表示用に整形（離れた箇所の間は "..." で区切る）
+fn render_diff(lines: &[DiffLine]) -> Vec<String> {
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    let near_change = |index: usize| {
+        changed
+            .iter()
+            .any(|&changed_index| index.abs_diff(changed_index) <= DIFF_CONTEXT_LINES)
+    };
+
+    let mut rendered = Vec::new();
+    let mut skipped = false;
+    for (index, line) in lines.iter().enumerate() {
+        if !near_change(index) {
+            skipped = true;
+            continue;
+        }
+        if skipped && !rendered.is_empty() {
+            rendered.push(DIFF_GAP_MARK.to_string());
+        }
+        skipped = false;
+
+        rendered.push(match line {
+            DiffLine::Same(text) => format!(" {}", text),
+            DiffLine::Removed(text) => format!("-{}", text),
+            DiffLine::Added(text) => format!("+{}", text),
+        });
+    }
+
+    rendered
+}
@@ -0,0 +1,170 @@
+use crate::client::{shows_meta, Config};
+use regex_lite::Regex;
+use reqwest::header::HeaderMap;
+use std::error::Error;
+
+// ヘッダーの区切り
+const HEADER_SEPARATOR: char = ':';
+
+// エラーメッセージ
+const ERROR_INVALID_ASSERT_HEADER: &str = "Invalid {1} '{2}'. Use 'name: value'.";
+const ERROR_INVALID_HEADER_REGEX: &str = "Invalid regex in --assert-header-regex '{1}': {2}";
+const ERROR_HEADER_ASSERTIONS_FAILED: &str = "{1} of {2} header assertions failed for {3}";
+const ERROR_MISSING_HEADER: &str = "header is missing";
+const ERROR_UNEXPECTED_VALUE: &str = "expected {1}, got {2}";
+const ERROR_UNMATCHED_VALUE: &str = "expected to match {1}, got {2}";
+
+// オプション名
+const ASSERT_HEADER_OPTION: &str = "--assert-header";
+const ASSERT_HEADER_REGEX_OPTION: &str = "--assert-header-regex";
+
+// 表示メッセージ
+const CHECK_EQUALS: &str = "header {1} == {2}";
+const CHECK_MATCHES: &str = "header {1} =~ {2}";
+const PASS_MSG: &str = "PASS {}";
+const FAIL_MSG: &str = "FAIL {1}: {2}";
+const VALUE_SEPARATOR: &str = ", ";
+
+/// ヘッダーの期待値
+enum Expected {
+    /// 値が一致する（--assert-header）
+    Equals(String),
+    /// 値が正規表現に一致する（--assert-header-regex）
+    Matches(Regex),
+}
+
+/// 1ヘッダー分のアサーション
+struct HeaderAssertion {
+    name: String,
+    expected: Expected,
+}
+
+impl HeaderAssertion {
+    /// チェック名（header content-type == application/json）
+    fn label(&self) -> String {
+        match &self.expected {
+            Expected::Equals(value) => CHECK_EQUALS
+                .replace("{1}", &self.name)
+                .replace("{2}", value),
+            Expected::Matches(regex) => CHECK_MATCHES
+                .replace("{1}", &self.name)
+                .replace("{2}", regex.as_str()),
+        }
+    }
+
+    /// 同じ名前のヘッダーのいずれかが期待値を満たすか（満たさない場合は理由を返す）
+    fn check(&self, headers: &HeaderMap) -> Option<String> {
+        let values: Vec<String> = headers
+            .get_all(self.name.as_str())
+            .iter()
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+            .collect();
+        if values.is_empty() {
+            return Some(ERROR_MISSING_HEADER.to_string());
+        }
+
+        let (satisfied, template, expected) = match &self.expected {
+            Expected::Equals(expected) => (
+                values.iter().any(|value| value == expected),
+                ERROR_UNEXPECTED_VALUE,
+                expected.as_str(),
+            ),
+            Expected::Matches(regex) => (
+                values.iter().any(|value| regex.is_match(value)),
+                ERROR_UNMATCHED_VALUE,
+                regex.as_str(),
+            ),
+        };
+
+        (!satisfied).then(|| {
+            template
+                .replace("{1}", expected)
+                .replace("{2}", &values.join(VALUE_SEPARATOR))
+        })
+    }
+}
+
+/// --assert-header・--assert-header-regex の指定を検証
+pub fn validate_header_assertions(config: &Config) -> Result<(), Box<dyn Error>> {
+    parse_header_assertions(config).map(|_| ())
+}
+
+/// レスポンスヘッダーのアサーションを評価し、結果を標準エラー出力に表示
+///
+/// 1つでも失敗した場合はエラーを返す。
+pub fn check_header_assertions(config: &Config, headers: &HeaderMap) -> Result<(), Box<dyn Error>> {
+    let assertions = parse_header_assertions(config)?;
+    if assertions.is_empty() {
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for assertion in &assertions {
+        match assertion.check(headers) {
+            None if shows_meta(config) => {
+                eprintln!("{}", PASS_MSG.replace("{}", &assertion.label()))
+            }
+            None => {}
+            Some(failure) => {
+                failed += 1;
+                eprintln!(
+                    "{}",
+                    FAIL_MSG
+                        .replace("{1}", &assertion.label())
+                        .replace("{2}", &failure)
+                );
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(ERROR_HEADER_ASSERTIONS_FAILED
+            .replace("{1}", &failed.to_string())
+            .replace("{2}", &assertions.len().to_string())
+            .replace("{3}", &config.url)
+            .into());
+    }
+
+    Ok(())
+}
+
+/// 設定のアサーションを解析（完全一致、正規表現の順）
+fn parse_header_assertions(config: &Config) -> Result<Vec<HeaderAssertion>, Box<dyn Error>> {
+    let mut assertions = Vec::new();
+
+    for header in config.assert_header.iter().flatten() {
+        let (name, value) = split_header(header, ASSERT_HEADER_OPTION)?;
+        assertions.push(HeaderAssertion {
+            name,
+            expected: Expected::Equals(value.to_string()),
+        });
+    }
+
+    for header in config.assert_header_regex.iter().flatten() {
+        let (name, pattern) = split_header(header, ASSERT_HEADER_REGEX_OPTION)?;
+        let regex = Regex::new(pattern).map_err(|e| {
+            ERROR_INVALID_HEADER_REGEX
+                .replace("{1}", header)
+                .replace("{2}", &e.to_string())
+        })?;
+        assertions.push(HeaderAssertion {
+            name,
+            expected: Expected::Matches(regex),
+        });
+    }
+
+    Ok(assertions)
+}
+
+/// "name: value" を名前（小文字）と値に分割
+fn split_header<'a>(header: &'a str, option: &str) -> Result<(String, &'a str), Box<dyn Error>> {
+    match header.split_once(HEADER_SEPARATOR) {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_ascii_lowercase(), value.trim()))
+        }
+        _ => Err(ERROR_INVALID_ASSERT_HEADER
+            .replace("{1}", option)
+            .replace("{2}", header)
+            .into()),
+    }
+}
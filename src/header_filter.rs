@@ -0,0 +1,60 @@
+use crate::client::Config;
+
+// パターンの区切り
+const PATTERN_SEPARATOR: char = ',';
+
+/// ヘッダーを詳細出力に表示するか（--show-headers に一致し、--hide-headers に一致しない）
+pub fn is_header_visible(config: &Config, name: &str) -> bool {
+    let shown = match patterns(config.show_headers.as_deref()) {
+        show if show.is_empty() => true,
+        show => show.iter().any(|pattern| wildcard_match(pattern, name)),
+    };
+
+    shown
+        && !patterns(config.hide_headers.as_deref())
+            .iter()
+            .any(|pattern| wildcard_match(pattern, name))
+}
+
+/// カンマ区切りのパターンを分割
+fn patterns(value: Option<&str>) -> Vec<&str> {
+    value
+        .unwrap_or_default()
+        .split(PATTERN_SEPARATOR)
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+/// `*`（任意の文字列）と `?`（任意の1文字）を使ったパターンに一致するか（大文字小文字を区別しない）
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_ascii_lowercase().chars().collect();
+    let text: Vec<char> = text.to_ascii_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // 直前の `*` の位置と、そこから照合を再開するテキストの位置
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, resume)) => {
+                    p = star + 1;
+                    t = resume + 1;
+                    backtrack = Some((star, resume + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,155 @@
+use crate::client::{BasicAuthConfig, Config};
+use crate::header_filter::wildcard_match;
+use reqwest::blocking::RequestBuilder;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// ヘッダーの区切り
+const HEADER_SEPARATOR: char = ':';
+
+/// 接続先のホストごとの既定値（[hosts."api.example.com"]）
+///
+/// 同じAPIに向けたプリセットごとに認証やヘッダーを繰り返し書かなくて済むようにする。
+/// プリセット・コマンドラインで指定した値があればそちらを優先する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostDefaults {
+    /// 同じ名前のヘッダーを指定していない場合に追加
+    pub headers: Option<Vec<String>>,
+    pub basic_auth: Option<BasicAuthConfig>,
+    pub bearer_token: Option<String>,
+    pub api_key: Option<String>,
+    pub api_key_in: Option<String>,
+    /// タイムアウト（秒）
+    pub timeout: Option<u64>,
+    pub retry: Option<u32>,
+    pub retry_delay: Option<f64>,
+}
+
+/// プリセット・コマンドラインで明示的に指定した設定
+///
+/// 既定値と同じ値を指定した場合もホストの既定値で上書きしないよう、値とは別に記録する。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ExplicitSettings {
+    pub timeout: bool,
+    pub retry: bool,
+    pub retry_delay: bool,
+}
+
+/// リクエスト先のホストの既定値のうち、指定されていない設定を補う
+pub fn apply_host_defaults(config: &mut Config) {
+    let Some(defaults) = matching_host_defaults(config).cloned() else {
+        return;
+    };
+
+    if config.basic_auth.is_none() {
+        config.basic_auth = defaults.basic_auth;
+    }
+    if config.bearer_token.is_none() {
+        config.bearer_token = defaults.bearer_token;
+    }
+    if config.api_key.is_none() {
+        config.api_key = defaults.api_key;
+        if config.api_key_in.is_none() {
+            config.api_key_in = defaults.api_key_in;
+        }
+    }
+    let explicit = config.explicit_settings;
+    if let Some(timeout) = defaults.timeout.filter(|_| !explicit.timeout) {
+        config.timeout = timeout;
+    }
+    if let Some(retry) = defaults.retry.filter(|_| !explicit.retry) {
+        config.retry = retry;
+    }
+    if let Some(retry_delay) = defaults.retry_delay.filter(|_| !explicit.retry_delay) {
+        config.retry_delay = retry_delay;
+    }
+}
+
+/// ホストの既定のヘッダーとタイムアウトをリクエストに付与
+///
+/// クライアントはすべてのリクエストで共有するため、ホストごとの値はリクエスト単位で設定する。
+pub fn apply_host_request_defaults(
+    mut request_builder: RequestBuilder,
+    config: &Config,
+) -> RequestBuilder {
+    let Some(defaults) = matching_host_defaults(config) else {
+        return request_builder;
+    };
+
+    for header in defaults.headers.iter().flatten() {
+        let Some((name, value)) = header.split_once(HEADER_SEPARATOR) else {
+            continue;
+        };
+        if !has_header(config, name.trim()) {
+            request_builder = request_builder.header(name.trim(), value.trim());
+        }
+    }
+    if defaults.timeout.is_some() {
+        request_builder = request_builder.timeout(Duration::from_secs(config.timeout));
+    }
+
+    request_builder
+}
+
+/// リクエスト先のホストに一致する既定値（複数に一致する場合はパターンが長いもの）
+fn matching_host_defaults(config: &Config) -> Option<&HostDefaults> {
+    let hosts = config.hosts.as_ref()?;
+    let url = Url::parse(&config.url).ok()?;
+    let host = url.host_str()?;
+
+    hosts
+        .iter()
+        .filter(|(pattern, _)| wildcard_match(pattern, host))
+        .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        .map(|(_, defaults)| defaults)
+}
+
+/// --headers・プリセットに同じ名前のヘッダーがあるか（名前は大文字小文字を区別しない）
+pub(crate) fn has_header(config: &Config, name: &str) -> bool {
+    config.headers.iter().flatten().any(|header| {
+        header
+            .split_once(HEADER_SEPARATOR)
+            .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{DEFAULT_RETRY_COUNT, DEFAULT_TIMEOUT_SECS};
+    use std::collections::HashMap;
+
+    fn config_with_host_defaults(config: Config) -> Config {
+        let defaults = HostDefaults {
+            timeout: Some(120),
+            retry: Some(5),
+            ..HostDefaults::default()
+        };
+        Config {
+            url: "https://api.example.com/orders".to_string(),
+            hosts: Some(HashMap::from([("api.example.com".to_string(), defaults)])),
+            ..config
+        }
+    }
+
+    #[test]
+    fn host_defaults_fill_unset_settings() {
+        let mut config = config_with_host_defaults(Config::default());
+        apply_host_defaults(&mut config);
+        assert_eq!(config.timeout, 120);
+        assert_eq!(config.retry, 5);
+    }
+
+    #[test]
+    fn explicit_default_values_override_host_defaults() {
+        let config = Config::builder()
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .retry(DEFAULT_RETRY_COUNT)
+            .build();
+        let mut config = config_with_host_defaults(config);
+        apply_host_defaults(&mut config);
+        assert_eq!(config.timeout, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(config.retry, DEFAULT_RETRY_COUNT);
+    }
+}
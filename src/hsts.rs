@@ -0,0 +1,182 @@
+use crate::util::{home_dir, unix_now};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// 保存先
+const DEFAULT_HSTS_PATH: &str = ".config/rs-w3r/hsts.json";
+const TEMP_FILE_SUFFIX: &str = ".tmp.";
+
+// Strict-Transport-Securityディレクティブ
+const DIRECTIVE_MAX_AGE: &str = "max-age";
+const DIRECTIVE_INCLUDE_SUBDOMAINS: &str = "includesubdomains";
+
+// スキーム
+const SCHEME_HTTP: &str = "http";
+const SCHEME_HTTPS: &str = "https";
+const HTTP_DEFAULT_PORT: u16 = 80;
+
+/// HSTSが有効なホストの一覧
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HstsStore {
+    hosts: HashMap<String, HstsEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HstsEntry {
+    expires_at: u64,
+    include_subdomains: bool,
+}
+
+impl HstsStore {
+    /// ファイルから読み込む（存在しない・壊れている場合は空のストア）
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// ファイルに保存（一時ファイルに書き込んでから置き換え、書き込み途中の内容を読ませない）
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(format!("{}{}", TEMP_FILE_SUFFIX, std::process::id()));
+        let temp_path = PathBuf::from(temp_path);
+        let result = fs::write(&temp_path, serde_json::to_string_pretty(self)?)
+            .and_then(|_| fs::rename(&temp_path, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+
+        Ok(result?)
+    }
+
+    /// ホストがHSTSの対象かどうかを判定（includeSubDomainsの親ドメインも考慮）
+    pub fn is_known_host(&self, host: &str) -> bool {
+        if is_ip_literal(host) {
+            return false;
+        }
+        let host = host.to_ascii_lowercase();
+        let now = unix_now();
+
+        if self
+            .hosts
+            .get(&host)
+            .is_some_and(|entry| entry.expires_at > now)
+        {
+            return true;
+        }
+
+        let mut domain = host.as_str();
+        while let Some((_, parent)) = domain.split_once('.') {
+            if self
+                .hosts
+                .get(parent)
+                .is_some_and(|entry| entry.include_subdomains && entry.expires_at > now)
+            {
+                return true;
+            }
+            domain = parent;
+        }
+
+        false
+    }
+
+    /// Strict-Transport-Securityヘッダーの内容でストアを更新（max-age=0は削除）
+    ///
+    /// IPアドレスのホストは記録しない（RFC 6797 8.1）。
+    pub fn update(&mut self, host: &str, header_value: &str) {
+        if is_ip_literal(host) {
+            return;
+        }
+
+        let mut max_age: Option<u64> = None;
+        let mut include_subdomains = false;
+
+        for directive in header_value.split(';') {
+            let directive = directive.trim();
+            match directive.split_once('=') {
+                Some((name, value)) if name.trim().eq_ignore_ascii_case(DIRECTIVE_MAX_AGE) => {
+                    max_age = value.trim().trim_matches('"').parse().ok();
+                }
+                None if directive.eq_ignore_ascii_case(DIRECTIVE_INCLUDE_SUBDOMAINS) => {
+                    include_subdomains = true;
+                }
+                _ => {}
+            }
+        }
+
+        let host = host.to_ascii_lowercase();
+        match max_age {
+            Some(0) => {
+                self.hosts.remove(&host);
+            }
+            Some(seconds) => {
+                self.hosts.insert(
+                    host,
+                    HstsEntry {
+                        expires_at: unix_now().saturating_add(seconds),
+                        include_subdomains,
+                    },
+                );
+            }
+            None => {}
+        }
+    }
+}
+
+/// Strict-Transport-Securityヘッダーの内容をファイルのストアに記録
+///
+/// 読み込みから保存までを排他し、--parallel などで同時に記録しても更新が失われないようにする。
+pub fn record_hsts_header(
+    path: &Path,
+    host: &str,
+    header_value: &str,
+) -> Result<(), Box<dyn Error>> {
+    static STORE_LOCK: Mutex<()> = Mutex::new(());
+    let _guard = STORE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let mut store = HstsStore::load(path);
+    store.update(host, header_value);
+    store.save(path)
+}
+
+/// HSTSストアの既定の保存先
+pub fn default_hsts_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(DEFAULT_HSTS_PATH))
+}
+
+/// HSTS対象ホストへのhttp://をhttps://に変換（変換しない場合はNone）
+pub fn upgrade_url(url: &str, store: &HstsStore) -> Option<String> {
+    let mut parsed = Url::parse(url).ok()?;
+
+    if parsed.scheme() != SCHEME_HTTP || !store.is_known_host(parsed.host_str()?) {
+        return None;
+    }
+
+    if parsed.port() == Some(HTTP_DEFAULT_PORT) {
+        parsed.set_port(None).ok()?;
+    }
+    parsed.set_scheme(SCHEME_HTTPS).ok()?;
+
+    Some(parsed.to_string())
+}
+
+/// IPアドレス（IPv6は角括弧付きも含む）かどうか
+fn is_ip_literal(host: &str) -> bool {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .is_ok()
+}
@@ -0,0 +1,53 @@
+use reqwest::Url;
+use std::error::Error;
+
+// punycodeのラベルの接頭辞
+const ACE_PREFIX: &str = "xn--";
+
+// エラーメッセージ
+const ERROR_INVALID_IDN_URL: &str = "Invalid URL '{1}': {2}";
+
+// 表示メッセージ
+const IDN_HOST_MSG: &str = "* IDN host: {1} -> {2}";
+
+/// 非ASCIIのホスト名を含むURLをpunycode（IDNA）に変換（ASCIIのURLはそのまま）
+pub fn to_ascii_url(url: &str) -> Result<String, Box<dyn Error>> {
+    if url.is_ascii() {
+        return Ok(url.to_string());
+    }
+
+    let parsed = Url::parse(url).map_err(|e| {
+        ERROR_INVALID_IDN_URL
+            .replace("{1}", url)
+            .replace("{2}", &e.to_string())
+    })?;
+    Ok(parsed.to_string())
+}
+
+/// 国際化ドメイン名のUnicode表記とpunycode表記を表示
+pub fn display_idn_host(url: &str) {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return,
+    };
+    let host = match parsed.host_str() {
+        Some(host) => host,
+        None => return,
+    };
+    if !host
+        .split('.')
+        .any(|label| label.to_ascii_lowercase().starts_with(ACE_PREFIX))
+    {
+        return;
+    }
+
+    let (unicode_host, result) = idna::domain_to_unicode(host);
+    if result.is_ok() && unicode_host != host {
+        eprintln!(
+            "{}",
+            IDN_HOST_MSG
+                .replace("{1}", &unicode_host)
+                .replace("{2}", host)
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Ctrl-C で中断した場合の終了コード（128 + SIGINT）
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+// 待機中に中断を確認する間隔
+const SLEEP_STEP: Duration = Duration::from_millis(50);
+
+// 表示メッセージ
+const INTERRUPTED_MSG: &str =
+    "\n* Interrupted; finishing with what was received so far (press Ctrl-C again to quit immediately)";
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Ctrl-C（SIGINT）のハンドラーを登録
+///
+/// 1回目は中断フラグを立て、受信済みのボディの保存やタイミング・サマリーの表示を済ませてから
+/// 終了できるようにする。応答のないサーバーを待っている場合などは2回目で即座に終了する。
+pub fn install_interrupt_handler() -> Result<(), Box<dyn Error>> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+        eprintln!("{}", INTERRUPTED_MSG);
+    })?;
+    Ok(())
+}
+
+/// Ctrl-C で中断されたか
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// 指定時間待つ（途中で中断された場合はすぐに戻り false を返す）
+pub fn sleep_unless_interrupted(duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    while !is_interrupted() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        thread::sleep(remaining.min(SLEEP_STEP));
+    }
+    false
+}
@@ -0,0 +1,302 @@
+use crate::util::prompt_line;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::{stderr, IsTerminal};
+
+// JSONパス（--json-filter の記法）
+const JSON_PATH_ROOT: &str = ".";
+
+// 入力の操作
+const COMMAND_SEARCH: char = '/';
+const COMMAND_COPY: &str = "y";
+const COMMAND_EXPAND_ALL: &str = "e";
+const COMMAND_COLLAPSE_ALL: &str = "c";
+const COMMAND_QUIT: &str = "q";
+
+// クリップボードへのコピー（OSC 52）
+const OSC52_COPY: &str = "\x1b]52;c;{}\x07";
+
+// 表示
+const INDENT: &str = "  ";
+const EXPANDED_MARK: &str = "-";
+const COLLAPSED_MARK: &str = "+";
+const LEAF_MARK: &str = " ";
+const MATCH_MARK: &str = "  *";
+
+// 表示メッセージ
+const NODE_LINE_MSG: &str = "{1}{2} {3} {4}{5}{6}";
+const OBJECT_SUMMARY_MSG: &str = "{{} keys}";
+const ARRAY_SUMMARY_MSG: &str = "[{} items]";
+const VIEWER_PROMPT_MSG: &str =
+    "[N] expand/collapse  /text search  y N copy path  e expand all  c collapse all  q quit: ";
+const JSON_FILTER_MSG: &str = "--json-filter '{}'";
+const COPIED_MSG: &str = "* Copied to the clipboard";
+const NO_MATCHES_MSG: &str = "* No matches for '{}'";
+const UNKNOWN_NODE_MSG: &str = "* No node numbered {}";
+
+/// ツリー上の1ノード（表示中のもの）
+struct VisibleNode<'a> {
+    path: String,
+    key: Option<String>,
+    value: &'a Value,
+    depth: usize,
+}
+
+/// JSONを展開・折りたたみできるツリーとして対話的に表示
+///
+/// 番号で展開・折りたたみ、/ で検索、y でノードの --json-filter 式を表示してクリップボードへコピーする。
+pub fn view_json(json: &Value) -> Result<(), Box<dyn Error>> {
+    let mut expanded: HashSet<String> = HashSet::from([JSON_PATH_ROOT.to_string()]);
+    let mut matches: HashSet<String> = HashSet::new();
+
+    loop {
+        let nodes = visible_nodes(json, &expanded);
+        display_nodes(&nodes, &expanded, &matches);
+
+        let Some(input) = prompt_line(VIEWER_PROMPT_MSG)? else {
+            return Ok(());
+        };
+        let input = input.trim();
+
+        if let Some(query) = input.strip_prefix(COMMAND_SEARCH) {
+            matches = search(json, JSON_PATH_ROOT, &query.to_lowercase());
+            if matches.is_empty() {
+                eprintln!("{}", NO_MATCHES_MSG.replace("{}", query));
+            }
+            // 一致したノードが見えるように親をすべて展開
+            for path in &matches {
+                expanded.extend(ancestor_paths(json, path));
+            }
+            continue;
+        }
+
+        match input.split_once(' ') {
+            Some((COMMAND_COPY, number)) => match node_at(&nodes, number) {
+                Some(node) => copy_path(&node.path)?,
+                None => eprintln!("{}", UNKNOWN_NODE_MSG.replace("{}", number.trim())),
+            },
+            _ => match input {
+                "" => {}
+                COMMAND_QUIT => return Ok(()),
+                COMMAND_EXPAND_ALL => expanded.extend(container_paths(json, JSON_PATH_ROOT)),
+                COMMAND_COLLAPSE_ALL => expanded.retain(|path| path == JSON_PATH_ROOT),
+                number => match node_at(&nodes, number) {
+                    Some(node) if is_container(node.value) => {
+                        if !expanded.remove(&node.path) {
+                            expanded.insert(node.path.clone());
+                        }
+                    }
+                    Some(_) => {}
+                    None => eprintln!("{}", UNKNOWN_NODE_MSG.replace("{}", number)),
+                },
+            },
+        }
+    }
+}
+
+/// 展開されているノードをたどって表示対象を列挙
+fn visible_nodes<'a>(json: &'a Value, expanded: &HashSet<String>) -> Vec<VisibleNode<'a>> {
+    let mut nodes = Vec::new();
+    collect_visible(
+        json,
+        JSON_PATH_ROOT.to_string(),
+        None,
+        0,
+        expanded,
+        &mut nodes,
+    );
+    nodes
+}
+
+fn collect_visible<'a>(
+    value: &'a Value,
+    path: String,
+    key: Option<String>,
+    depth: usize,
+    expanded: &HashSet<String>,
+    nodes: &mut Vec<VisibleNode<'a>>,
+) {
+    let is_expanded = expanded.contains(&path);
+    nodes.push(VisibleNode {
+        path: path.clone(),
+        key,
+        value,
+        depth,
+    });
+
+    if is_expanded {
+        for (child_key, child_path, child) in children(value, &path) {
+            collect_visible(
+                child,
+                child_path,
+                Some(child_key),
+                depth + 1,
+                expanded,
+                nodes,
+            );
+        }
+    }
+}
+
+/// 子ノードのキー（配列は添字）・パス・値
+fn children<'a>(value: &'a Value, path: &str) -> Vec<(String, String, &'a Value)> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, child)| (key.clone(), child_path(path, key), child))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, child)| (format!("[{}]", index), index_path(path, index), child))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// オブジェクトのキーのパス（.a.b）
+fn child_path(path: &str, key: &str) -> String {
+    if path == JSON_PATH_ROOT {
+        format!(".{}", key)
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// 配列の要素のパス（.items[0]、配列の配列やルートの配列は .[0]）
+fn index_path(path: &str, index: usize) -> String {
+    if path == JSON_PATH_ROOT {
+        format!(".[{}]", index)
+    } else if path.ends_with(']') {
+        format!("{}.[{}]", path, index)
+    } else {
+        format!("{}[{}]", path, index)
+    }
+}
+
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+/// 番号付きでツリーを表示（折りたたまれたノードは要素数のみ）
+fn display_nodes(nodes: &[VisibleNode], expanded: &HashSet<String>, matches: &HashSet<String>) {
+    let width = nodes.len().to_string().len();
+
+    for (index, node) in nodes.iter().enumerate() {
+        let mark = if !is_container(node.value) {
+            LEAF_MARK
+        } else if expanded.contains(&node.path) {
+            EXPANDED_MARK
+        } else {
+            COLLAPSED_MARK
+        };
+        let key = node
+            .key
+            .as_ref()
+            .map(|key| format!("{}: ", key))
+            .unwrap_or_default();
+        let matched = if matches.contains(&node.path) {
+            MATCH_MARK
+        } else {
+            ""
+        };
+
+        println!(
+            "{}",
+            NODE_LINE_MSG
+                .replace("{1}", &format!("{:>width$}", index + 1, width = width))
+                .replace("{2}", &INDENT.repeat(node.depth))
+                .replace("{3}", mark)
+                .replace("{4}", &key)
+                .replace("{5}", &value_summary(node.value))
+                .replace("{6}", matched)
+        );
+    }
+}
+
+/// 値の1行表示（オブジェクト・配列は要素数）
+fn value_summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) => OBJECT_SUMMARY_MSG.replace("{}", &map.len().to_string()),
+        Value::Array(items) => ARRAY_SUMMARY_MSG.replace("{}", &items.len().to_string()),
+        _ => value.to_string(),
+    }
+}
+
+/// キーまたはスカラー値に検索語を含むノードのパス（大文字小文字を区別しない）
+fn search(value: &Value, path: &str, query: &str) -> HashSet<String> {
+    let mut found = HashSet::new();
+    if query.is_empty() {
+        return found;
+    }
+
+    for (key, child_path, child) in children(value, path) {
+        let scalar_matches =
+            !is_container(child) && value_summary(child).to_lowercase().contains(query);
+        if key.to_lowercase().contains(query) || scalar_matches {
+            found.insert(child_path.clone());
+        }
+        found.extend(search(child, &child_path, query));
+    }
+    found
+}
+
+/// 指定したパスのノードに至るまでの親ノードのパス
+fn ancestor_paths(value: &Value, target: &str) -> Vec<String> {
+    let mut ancestors = Vec::new();
+    find_ancestors(value, JSON_PATH_ROOT, target, &mut ancestors);
+    ancestors
+}
+
+fn find_ancestors(value: &Value, path: &str, target: &str, ancestors: &mut Vec<String>) -> bool {
+    if path == target {
+        return true;
+    }
+
+    for (_, child_path, child) in children(value, path) {
+        if find_ancestors(child, &child_path, target, ancestors) {
+            ancestors.push(path.to_string());
+            return true;
+        }
+    }
+    false
+}
+
+/// すべてのオブジェクト・配列のパス
+fn container_paths(value: &Value, path: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    if is_container(value) {
+        paths.push(path.to_string());
+    }
+    for (_, child_path, child) in children(value, path) {
+        paths.extend(container_paths(child, &child_path));
+    }
+    paths
+}
+
+/// 表示中の番号（1から）からノードを取得
+fn node_at<'a, 'b>(nodes: &'b [VisibleNode<'a>], number: &str) -> Option<&'b VisibleNode<'a>> {
+    number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| nodes.get(number.checked_sub(1)?))
+}
+
+/// ノードの --json-filter 式を表示し、端末ではOSC 52でクリップボードにコピー
+fn copy_path(path: &str) -> Result<(), Box<dyn Error>> {
+    let expression = JSON_FILTER_MSG.replace("{}", path);
+    println!("{}", expression);
+
+    if stderr().is_terminal() {
+        eprint!(
+            "{}",
+            OSC52_COPY.replace("{}", &STANDARD.encode(&expression))
+        );
+        eprintln!("{}", COPIED_MSG);
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::client::parse_duration;
+use crate::client_key::find_private_key_block;
+use crate::util::unix_now;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use pkcs8::der::asn1::AnyRef;
+use pkcs8::der::Encode;
+use pkcs8::{AlgorithmIdentifierRef, ObjectIdentifier, PrivateKeyInfo};
+use ring::hmac;
+use ring::rand::SystemRandom;
+use ring::signature::{
+    EcdsaKeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING, RSA_PKCS1_SHA256,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+
+// 署名アルゴリズム
+const ALGORITHM_HS256: &str = "HS256";
+const ALGORITHM_RS256: &str = "RS256";
+const ALGORITHM_ES256: &str = "ES256";
+
+// ヘッダー・クレーム
+const TOKEN_TYPE: &str = "JWT";
+const HEADER_ALGORITHM: &str = "alg";
+const HEADER_TYPE: &str = "typ";
+const HEADER_KEY_ID: &str = "kid";
+const CLAIM_ISSUED_AT: &str = "iat";
+const CLAIM_EXPIRES_AT: &str = "exp";
+
+// デフォルト値
+const DEFAULT_JWT_TTL_SECS: u64 = 300;
+
+// PEM
+const PEM_LABEL_SEC1: &str = "EC PRIVATE KEY";
+
+// SEC1形式の鍵をPKCS#8に包むためのOID（id-ecPublicKey・P-256）
+const OID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const OID_PRIME256V1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+
+// エラーメッセージ
+const ERROR_UNSUPPORTED_ALGORITHM: &str = "Unsupported JWT algorithm: {} (expected HS256, RS256 or ES256)";
+const ERROR_INVALID_PEM: &str = "Invalid PEM key file: {}";
+const ERROR_NO_PRIVATE_KEY: &str = "No PRIVATE KEY block found in the PEM key file";
+const ERROR_INVALID_KEY: &str = "Invalid {1} key: {2}";
+const ERROR_SIGNING_FAILED: &str = "Failed to sign JWT";
+
+/// JWT認証の設定（プリセットの[jwt]セクション）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtConfig {
+    pub algorithm: String,
+    pub key_file: String,
+    pub key_id: Option<String>,
+    pub ttl: Option<String>,
+    pub claims: Option<HashMap<String, Value>>,
+}
+
+/// 設定に従って署名済みJWTを発行（iat/expは発行時刻から設定）
+pub fn mint_jwt(config: &JwtConfig) -> Result<String, Box<dyn Error>> {
+    let algorithm = config.algorithm.to_ascii_uppercase();
+    let ttl = match &config.ttl {
+        Some(ttl) => parse_duration(ttl)?,
+        None => Duration::from_secs(DEFAULT_JWT_TTL_SECS),
+    };
+    let issued_at = unix_now();
+
+    let mut header = json!({ HEADER_ALGORITHM: algorithm, HEADER_TYPE: TOKEN_TYPE });
+    if let Some(key_id) = &config.key_id {
+        header[HEADER_KEY_ID] = Value::from(key_id.as_str());
+    }
+
+    let mut claims: Map<String, Value> = config
+        .claims
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    claims.insert(CLAIM_ISSUED_AT.to_string(), Value::from(issued_at));
+    claims.insert(
+        CLAIM_EXPIRES_AT.to_string(),
+        Value::from(issued_at.saturating_add(ttl.as_secs())),
+    );
+
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?),
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims)?)
+    );
+    let key = fs::read(&config.key_file)?;
+    let signature = sign(&algorithm, &key, signing_input.as_bytes())?;
+
+    Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// アルゴリズムに応じて署名（HS256は鍵ファイルの内容を共有鍵として使用）
+fn sign(algorithm: &str, key: &[u8], message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    match algorithm {
+        ALGORITHM_HS256 => {
+            let secret = key.trim_ascii_end();
+            let signing_key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+            Ok(hmac::sign(&signing_key, message).as_ref().to_vec())
+        }
+        ALGORITHM_RS256 => {
+            let (_, der) = decode_pem(key)?;
+            let key_pair = RsaKeyPair::from_pkcs8(&der)
+                .or_else(|_| RsaKeyPair::from_der(&der))
+                .map_err(|e| invalid_key_error(algorithm, &e.to_string()))?;
+            let mut signature = vec![0u8; key_pair.public().modulus_len()];
+            key_pair
+                .sign(&RSA_PKCS1_SHA256, &SystemRandom::new(), message, &mut signature)
+                .map_err(|_| ERROR_SIGNING_FAILED)?;
+            Ok(signature)
+        }
+        ALGORITHM_ES256 => {
+            let (label, der) = decode_pem(key)?;
+            let der = if label == PEM_LABEL_SEC1 {
+                sec1_to_pkcs8(&der)?
+            } else {
+                der
+            };
+            let rng = SystemRandom::new();
+            let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &der, &rng)
+                .map_err(|e| invalid_key_error(algorithm, &e.to_string()))?;
+            let signature = key_pair
+                .sign(&rng, message)
+                .map_err(|_| ERROR_SIGNING_FAILED)?;
+            Ok(signature.as_ref().to_vec())
+        }
+        _ => Err(ERROR_UNSUPPORTED_ALGORITHM.replace("{}", algorithm).into()),
+    }
+}
+
+/// PEM形式の鍵から最初の秘密鍵ブロックを取り出し、ラベルとDERを返す
+///
+/// EC PARAMETERSなど、秘密鍵以外のブロックは読み飛ばす。
+fn decode_pem(pem: &[u8]) -> Result<(String, Vec<u8>), Box<dyn Error>> {
+    let text = String::from_utf8_lossy(pem);
+    let block = find_private_key_block(&text).ok_or(ERROR_NO_PRIVATE_KEY)?;
+    let encoded: String = block.body.split_whitespace().collect();
+
+    let der = STANDARD
+        .decode(encoded)
+        .map_err(|e| ERROR_INVALID_PEM.replace("{}", &e.to_string()))?;
+    Ok((block.label.to_string(), der))
+}
+
+/// SEC1形式（EC PRIVATE KEY）のP-256鍵をPKCS#8に変換
+fn sec1_to_pkcs8(der: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let info = PrivateKeyInfo {
+        algorithm: AlgorithmIdentifierRef {
+            oid: OID_EC_PUBLIC_KEY,
+            parameters: Some(AnyRef::from(&OID_PRIME256V1)),
+        },
+        private_key: der,
+        public_key: None,
+    };
+    Ok(info.to_der()?)
+}
+
+/// 鍵の読み込みエラーを作成
+fn invalid_key_error(algorithm: &str, detail: &str) -> Box<dyn Error> {
+    ERROR_INVALID_KEY
+        .replace("{1}", algorithm)
+        .replace("{2}", detail)
+        .into()
+}
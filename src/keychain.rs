@@ -0,0 +1,154 @@
+use std::error::Error;
+
+// キーチェーンの項目
+#[cfg(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+))]
+const KEYCHAIN_SERVICE: &str = "rs-w3r";
+
+// エラーメッセージ
+const ERROR_KEYCHAIN_LOOKUP: &str = "Failed to read keychain:{1}: {2}";
+const ERROR_KEYCHAIN_NOT_FOUND: &str =
+    "no item named '{}' in the keychain (store it with `rs-w3r secrets set {}`)";
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+)))]
+const ERROR_KEYCHAIN_UNSUPPORTED: &str =
+    "The keychain is supported on macOS (Keychain), Linux (Secret Service) and Windows (Credential Manager) only";
+
+// 表示メッセージ
+#[cfg(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+))]
+const SECRET_PROMPT_MSG: &str = "Value for '{}': ";
+const SECRET_STORED_MSG: &str =
+    "Stored '{}' in the keychain. Reference it as \"keychain:{}\" in the config file.";
+const SECRET_DELETED_MSG: &str = "Deleted '{}' from the keychain";
+
+/// 設定ファイルの "keychain:<name>" に対応する値をOSのキーチェーンから取得
+///
+/// macOS ではキーチェーン、Linux などでは Secret Service、Windows では資格情報マネージャーの
+/// サービス名 rs-w3r の項目を参照する。
+pub fn keychain_lookup(name: &str) -> Result<String, Box<dyn Error>> {
+    lookup_secret(name).map_err(|e| {
+        ERROR_KEYCHAIN_LOOKUP
+            .replace("{1}", name)
+            .replace("{2}", &e.to_string())
+            .into()
+    })
+}
+
+/// キーチェーンに値を保存（値は端末から入力する）
+pub fn keychain_store(name: &str) -> Result<(), Box<dyn Error>> {
+    store_secret(name)?;
+    eprintln!("{}", SECRET_STORED_MSG.replace("{}", name));
+    Ok(())
+}
+
+/// キーチェーンから値を削除
+pub fn keychain_delete(name: &str) -> Result<(), Box<dyn Error>> {
+    delete_secret(name)?;
+    eprintln!("{}", SECRET_DELETED_MSG.replace("{}", name));
+    Ok(())
+}
+
+/// キーチェーンの項目（Windowsの資格情報マネージャーではターゲット名は "<名前>.rs-w3r"）
+#[cfg(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+))]
+fn credential(name: &str) -> Result<keyring::Entry, Box<dyn Error>> {
+    Ok(keyring::Entry::new(KEYCHAIN_SERVICE, name)?)
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+))]
+fn lookup_secret(name: &str) -> Result<String, Box<dyn Error>> {
+    match credential(name)?.get_password() {
+        Ok(secret) => Ok(secret),
+        Err(keyring::Error::NoEntry) => Err(ERROR_KEYCHAIN_NOT_FOUND.replace("{}", name).into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// キーチェーンに保存（値は入力内容を表示せずに入力する）
+#[cfg(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+))]
+fn store_secret(name: &str) -> Result<(), Box<dyn Error>> {
+    let secret = rpassword::prompt_password(SECRET_PROMPT_MSG.replace("{}", name))?;
+    credential(name)?.set_password(&secret)?;
+    Ok(())
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+))]
+fn delete_secret(name: &str) -> Result<(), Box<dyn Error>> {
+    match credential(name)?.delete_credential() {
+        Ok(()) => Ok(()),
+        Err(keyring::Error::NoEntry) => Err(ERROR_KEYCHAIN_NOT_FOUND.replace("{}", name).into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+)))]
+fn lookup_secret(_name: &str) -> Result<String, Box<dyn Error>> {
+    Err(ERROR_KEYCHAIN_UNSUPPORTED.into())
+}
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+)))]
+fn store_secret(_name: &str) -> Result<(), Box<dyn Error>> {
+    Err(ERROR_KEYCHAIN_UNSUPPORTED.into())
+}
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    windows
+)))]
+fn delete_secret(_name: &str) -> Result<(), Box<dyn Error>> {
+    Err(ERROR_KEYCHAIN_UNSUPPORTED.into())
+}
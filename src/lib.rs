@@ -0,0 +1,53 @@
+pub mod assertion;
+pub mod audit;
+pub mod builder;
+mod byte_range;
+pub mod client;
+mod client_key;
+mod condition;
+mod config_include;
+mod connection_stats;
+mod cookie_import;
+pub mod cors;
+mod dns_cache;
+mod environment;
+mod glob;
+mod golden;
+mod header_assertion;
+mod header_filter;
+mod host_defaults;
+mod hsts;
+mod idn;
+pub mod interrupt;
+mod json_viewer;
+pub mod jwt;
+pub mod keychain;
+mod load;
+mod load_output;
+pub mod oauth2;
+pub mod observer;
+#[cfg(feature = "otel")]
+mod otel;
+mod output_file;
+mod output_path;
+mod phase_timing;
+pub mod picker;
+pub mod presign;
+mod proxy_rules;
+mod redirect;
+mod result_document;
+mod secrets;
+mod shell_export;
+pub mod signer;
+pub mod sigv4;
+pub mod status;
+mod summary;
+pub mod test_report;
+mod tls;
+mod trace_context;
+pub mod tui;
+mod upload_progress;
+mod util;
+pub mod variables;
+pub mod wait;
+mod watch;
@@ -0,0 +1,486 @@
+use crate::client::{parse_duration, Config};
+use crate::connection_stats::{display_connection_summary, ConnectionStats, ConnectionSummary};
+use crate::interrupt::sleep_unless_interrupted;
+use crate::load_output::{LatencyRecord, LoadOutput, SampleRecord, SummaryRecord};
+use reqwest::blocking::{Client, Request};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+// 同時に送信中にできるリクエストの上限（超えた到着はスキップして失敗として数える）
+const MAX_IN_FLIGHT: usize = 1024;
+
+// リクエストを作り直して署名・トークンを更新する間隔（JWT・SigV4・OAuth2の期限切れを防ぐ）
+const REQUEST_REBUILD_INTERVAL: Duration = Duration::from_secs(10);
+
+// レイテンシのヒストグラム（マイクロ秒、64未満は1刻み、以降は2の累乗ごとに32分割）
+const EXACT_BUCKETS: u64 = 64;
+const SUB_BUCKET_BITS: u32 = 5;
+const HISTOGRAM_BUCKETS: usize = 1920;
+
+// HTTPステータスコード
+const CLIENT_ERROR_START: u16 = 400;
+
+// エラーメッセージ
+const ERROR_INVALID_RPS: &str = "--rps must be greater than 0.";
+const ERROR_MISSING_DURATION: &str = "--rps requires --duration (e.g. --duration 60s).";
+const ERROR_INVALID_REPORT_INTERVAL: &str = "--report-interval must be greater than 0.";
+const ERROR_INVALID_WARMUP: &str =
+    "Invalid --warmup '{}'. Use a number of requests (e.g. 20) or a duration (e.g. 5s).";
+const ERROR_REQUEST_NOT_REPEATABLE: &str =
+    "The request body is a stream and cannot be resent, so it cannot be used with --rps.";
+const ERROR_HTTP_STATUS: &str = "HTTP {}";
+const ERROR_TOO_MANY_IN_FLIGHT: &str = "skipped: {} requests already in flight";
+
+// 表示メッセージ
+const LOAD_HEADER: &str = "--- Load Test ---";
+const LOAD_TARGET_MSG: &str = "Target: {1} {2} at {3} req/s for {4}";
+const LOAD_WARMUP_MSG: &str = "Warm-up: {} requests (excluded from the results)";
+const LOAD_INTERVAL_MSG: &str =
+    "[{1}] {2} requests ({3} req/s), {4} failed ({5}% errors), p50 {6} / p99 {7}";
+const LOAD_REQUESTS_MSG: &str = "Requests: {1} sent, {2} succeeded, {3} failed ({4}% errors)";
+const LOAD_RATE_MSG: &str = "Achieved rate: {} req/s";
+const LOAD_LATENCY_MSG: &str = "Latency: min {1} / p50 {2} / p90 {3} / p99 {4} / max {5}";
+const LOAD_ERRORS_HEADER: &str = "Errors:";
+const LOAD_ERROR_MSG: &str = "  {1} x {2}";
+
+// 表示するパーセンタイル
+const PERCENTILES: [f64; 3] = [50.0, 90.0, 99.0];
+
+/// 1リクエスト分の結果（所要時間は予定した到着時刻から計測、送信しなかった場合はNone）
+struct LoadSample {
+    scheduled: Instant,
+    latency: Option<Duration>,
+    status: Option<u16>,
+    bytes: Option<usize>,
+    error: Option<String>,
+    warmup: bool,
+}
+
+impl LoadSample {
+    /// 送信しなかった到着（失敗として数える）
+    fn skipped(scheduled: Instant, error: String, warmup: bool) -> Self {
+        Self {
+            scheduled,
+            latency: None,
+            status: None,
+            bytes: None,
+            error: Some(error),
+            warmup,
+        }
+    }
+}
+
+/// 結果の集計（サンプルを保持しないため、長時間の試験でもメモリ使用量が一定）
+struct LoadStats {
+    sent: u64,
+    failed: u64,
+    latency_buckets: Vec<u64>,
+    latency_count: u64,
+    min: Option<Duration>,
+    max: Option<Duration>,
+    errors: BTreeMap<String, u64>,
+}
+
+impl LoadStats {
+    fn new() -> Self {
+        Self {
+            sent: 0,
+            failed: 0,
+            latency_buckets: vec![0; HISTOGRAM_BUCKETS],
+            latency_count: 0,
+            min: None,
+            max: None,
+            errors: BTreeMap::new(),
+        }
+    }
+
+    fn record(&mut self, sample: &LoadSample) {
+        self.sent += 1;
+        if let Some(latency) = sample.latency {
+            self.latency_buckets[bucket_index(latency)] += 1;
+            self.latency_count += 1;
+            self.min = Some(self.min.map_or(latency, |min| min.min(latency)));
+            self.max = Some(self.max.map_or(latency, |max| max.max(latency)));
+        }
+        if let Some(error) = &sample.error {
+            self.failed += 1;
+            *self.errors.entry(error.clone()).or_default() += 1;
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        self.failed as f64 / self.sent.max(1) as f64 * 100.0
+    }
+
+    /// 結果ファイル用のレイテンシの集計（ミリ秒）
+    fn latency_record(&self) -> Option<LatencyRecord> {
+        let millis = |latency: Duration| latency.as_secs_f64() * 1000.0;
+        let (min, max) = (self.min?, self.max?);
+        let [p50, p90, p99] =
+            PERCENTILES.map(|percentile| self.percentile(percentile).unwrap_or(max));
+        Some(LatencyRecord {
+            min: millis(min),
+            p50: millis(p50),
+            p90: millis(p90),
+            p99: millis(p99),
+            max: millis(max),
+        })
+    }
+
+    /// パーセンタイル（バケットの代表値、最小値・最大値の範囲に収める）
+    fn percentile(&self, percentile: f64) -> Option<Duration> {
+        let (min, max) = (self.min?, self.max?);
+        let rank = ((percentile / 100.0 * self.latency_count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.latency_buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(bucket_value(index).clamp(min, max));
+            }
+        }
+        Some(max)
+    }
+}
+
+/// 一定の到着レート（--rps）で --duration の間リクエストを送り続け、結果を集計して表示
+///
+/// 前のリクエストの完了を待たずに予定時刻で送信する（オープンループ）ため、
+/// サーバーが遅くなっても送信レートは下がらない。
+/// 長時間の試験でも認証情報が期限切れにならないよう、一定間隔で build_request から作り直す。
+pub fn run_load_test(
+    client: &Client,
+    mut build_request: impl FnMut() -> Result<Request, Box<dyn Error>>,
+    connection_stats: &ConnectionStats,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let rps = match config.rps {
+        Some(rps) if rps > 0 => rps,
+        _ => return Err(ERROR_INVALID_RPS.into()),
+    };
+    let duration_text = config.duration.as_deref().ok_or(ERROR_MISSING_DURATION)?;
+    let duration = parse_duration(duration_text)?;
+    let report_interval = parse_report_interval(config)?;
+    let warmup = match config.warmup.as_deref() {
+        Some(warmup) => warmup_requests(warmup, rps)?,
+        None => 0,
+    };
+    let mut request = build_request()?;
+    let mut built_at = Instant::now();
+    request.try_clone().ok_or(ERROR_REQUEST_NOT_REPEATABLE)?;
+    let output = config
+        .bench_output
+        .as_deref()
+        .map(LoadOutput::create)
+        .transpose()?;
+
+    eprintln!("{}", LOAD_HEADER);
+    eprintln!(
+        "{}",
+        LOAD_TARGET_MSG
+            .replace("{1}", &config.method)
+            .replace("{2}", &config.url)
+            .replace("{3}", &rps.to_string())
+            .replace("{4}", duration_text)
+    );
+    if warmup > 0 {
+        eprintln!("{}", LOAD_WARMUP_MSG.replace("{}", &warmup.to_string()));
+    }
+
+    let total = ((f64::from(rps) * duration.as_secs_f64()).round() as u64).max(1);
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let (start, wall_start) = (Instant::now(), SystemTime::now());
+    // ウォームアップの到着を先に同じレートで送り、その後から計測する
+    let warmup_time = Duration::from_secs_f64(warmup as f64 / f64::from(rps));
+    let measure_start = start + warmup_time;
+    let mut last_arrival = measure_start;
+    let aggregator = thread::spawn(move || {
+        aggregate(
+            receiver,
+            (measure_start, wall_start + warmup_time),
+            report_interval,
+            output,
+        )
+    });
+
+    for index in 0..warmup + total {
+        let scheduled = start + Duration::from_secs_f64(index as f64 / f64::from(rps));
+        let wait = scheduled.saturating_duration_since(Instant::now());
+        // Ctrl-C で中断された場合は送信をやめ、それまでの結果を集計する
+        if !sleep_unless_interrupted(wait) {
+            break;
+        }
+        last_arrival = Instant::now();
+        let is_warmup = index < warmup;
+
+        if in_flight.load(Ordering::SeqCst) >= MAX_IN_FLIGHT {
+            let error = ERROR_TOO_MANY_IN_FLIGHT.replace("{}", &MAX_IN_FLIGHT.to_string());
+            let _ = sender.send(LoadSample::skipped(scheduled, error, is_warmup));
+            continue;
+        }
+
+        // 作り直せない場合（トークンの取得に失敗など）は、この到着を失敗として数えて次の間隔で再試行
+        if built_at.elapsed() >= REQUEST_REBUILD_INTERVAL {
+            built_at = Instant::now();
+            match build_request() {
+                Ok(rebuilt) => request = rebuilt,
+                Err(e) => {
+                    let _ = sender.send(LoadSample::skipped(scheduled, e.to_string(), is_warmup));
+                    continue;
+                }
+            }
+        }
+
+        let client = client.clone();
+        let request = request.try_clone().ok_or(ERROR_REQUEST_NOT_REPEATABLE)?;
+        let sender = sender.clone();
+        let in_flight = Arc::clone(&in_flight);
+        let connection_stats = connection_stats.clone();
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        thread::spawn(move || {
+            let (status, bytes, error) = send_once(&client, request, &connection_stats);
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            let _ = sender.send(LoadSample {
+                scheduled,
+                latency: Some(scheduled.elapsed()),
+                status,
+                bytes,
+                error,
+                warmup: is_warmup,
+            });
+        });
+    }
+    drop(sender);
+
+    // 送信したすべてのリクエストの完了を待つ
+    let (stats, output) = aggregator
+        .join()
+        .unwrap_or_else(|_| Ok((LoadStats::new(), None)))?;
+    let send_window = last_arrival.saturating_duration_since(measure_start)
+        + Duration::from_secs_f64(1.0 / f64::from(rps));
+
+    let connections = connection_stats.summary();
+    display_load_report(&stats, send_window, &connections);
+    if let Some(output) = output {
+        output.finish(&SummaryRecord {
+            method: &config.method,
+            url: &config.url,
+            rps,
+            duration: duration_text,
+            warmup,
+            sent: stats.sent,
+            succeeded: stats.sent - stats.failed,
+            failed: stats.failed,
+            error_rate: stats.error_rate(),
+            achieved_rate: stats.sent as f64 / send_window.as_secs_f64(),
+            latency_ms: stats.latency_record(),
+            errors: &stats.errors,
+            connections: &connections,
+        })?;
+    }
+    Ok(())
+}
+
+/// 結果を受け取りながら集計し、--report-interval ごとにその区間の集計を表示
+///
+/// --bench-output 指定時は受け取ったサンプルをそのまま書き出す（計測開始時刻は単調時計と実時刻の組）
+fn aggregate(
+    receiver: Receiver<LoadSample>,
+    (start, wall_start): (Instant, SystemTime),
+    report_interval: Option<Duration>,
+    mut output: Option<LoadOutput>,
+) -> io::Result<(LoadStats, Option<LoadOutput>)> {
+    let mut stats = LoadStats::new();
+    let mut interval_stats = LoadStats::new();
+    let mut next_report = report_interval.map(|interval| (start + interval, interval));
+
+    loop {
+        let received = match next_report {
+            Some((at, _)) => receiver.recv_timeout(at.saturating_duration_since(Instant::now())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(sample) if sample.warmup => {}
+            Ok(sample) => {
+                if let Some(output) = &mut output {
+                    output.write_sample(&sample_record(&sample, start, wall_start))?;
+                }
+                stats.record(&sample);
+                if next_report.is_some() {
+                    interval_stats.record(&sample);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        if let Some((at, interval)) = next_report {
+            if Instant::now() >= at {
+                display_interval_summary(&interval_stats, at.duration_since(start), interval);
+                interval_stats = LoadStats::new();
+                next_report = Some((at + interval, interval));
+            }
+        }
+    }
+    Ok((stats, output))
+}
+
+/// 結果ファイルに書き出す形式に変換
+fn sample_record(sample: &LoadSample, start: Instant, wall_start: SystemTime) -> SampleRecord {
+    let offset = sample.scheduled.saturating_duration_since(start);
+    SampleRecord {
+        timestamp: humantime::format_rfc3339_millis(wall_start + offset).to_string(),
+        offset_ms: offset.as_secs_f64() * 1000.0,
+        status: sample.status,
+        latency_ms: sample.latency.map(|latency| latency.as_secs_f64() * 1000.0),
+        bytes: sample.bytes,
+        error: sample.error.clone(),
+    }
+}
+
+/// --warmup の値をリクエスト数に変換（単位のない整数は件数、単位付きは時間×レート）
+fn warmup_requests(warmup: &str, rps: u32) -> Result<u64, Box<dyn Error>> {
+    if let Ok(requests) = warmup.trim().parse::<u64>() {
+        return Ok(requests);
+    }
+    let duration =
+        parse_duration(warmup).map_err(|_| ERROR_INVALID_WARMUP.replace("{}", warmup))?;
+    Ok((f64::from(rps) * duration.as_secs_f64()).round() as u64)
+}
+
+/// リクエストを送信してボディまで受信（ステータスコード・ボディのサイズ・失敗した場合は理由を返す）
+fn send_once(
+    client: &Client,
+    request: Request,
+    connection_stats: &ConnectionStats,
+) -> (Option<u16>, Option<usize>, Option<String>) {
+    let result = client.execute(request);
+    connection_stats.record_request(result.as_ref());
+    match result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            match response.bytes() {
+                Ok(body) if status < CLIENT_ERROR_START => (Some(status), Some(body.len()), None),
+                Ok(body) => (
+                    Some(status),
+                    Some(body.len()),
+                    Some(ERROR_HTTP_STATUS.replace("{}", &status.to_string())),
+                ),
+                Err(e) => (Some(status), None, Some(e.to_string())),
+            }
+        }
+        Err(e) => (None, None, Some(e.to_string())),
+    }
+}
+
+/// --report-interval の間隔（0は不可）
+pub fn parse_report_interval(config: &Config) -> Result<Option<Duration>, Box<dyn Error>> {
+    let report_interval = config
+        .report_interval
+        .as_deref()
+        .map(parse_duration)
+        .transpose()?;
+    if report_interval.is_some_and(|interval| interval.is_zero()) {
+        return Err(ERROR_INVALID_REPORT_INTERVAL.into());
+    }
+    Ok(report_interval)
+}
+
+/// 区間ごとの件数・レート・エラー率・レイテンシを1行で表示
+fn display_interval_summary(stats: &LoadStats, elapsed: Duration, interval: Duration) {
+    let format_latency = |latency: Option<Duration>| {
+        latency.map_or_else(|| "-".to_string(), |latency| format!("{:.2?}", latency))
+    };
+    let elapsed = Duration::from_secs(elapsed.as_secs_f64().round() as u64);
+
+    eprintln!(
+        "{}",
+        LOAD_INTERVAL_MSG
+            .replace("{1}", &humantime::format_duration(elapsed).to_string())
+            .replace("{2}", &stats.sent.to_string())
+            .replace(
+                "{3}",
+                &format!("{:.1}", stats.sent as f64 / interval.as_secs_f64())
+            )
+            .replace("{4}", &stats.failed.to_string())
+            .replace("{5}", &format!("{:.2}", stats.error_rate()))
+            .replace("{6}", &format_latency(stats.percentile(50.0)))
+            .replace("{7}", &format_latency(stats.percentile(99.0)))
+    );
+}
+
+/// 到着レート・エラー率・レイテンシを表示
+fn display_load_report(stats: &LoadStats, send_window: Duration, connections: &ConnectionSummary) {
+    let achieved_rate = stats.sent as f64 / send_window.as_secs_f64();
+
+    eprintln!(
+        "{}",
+        LOAD_REQUESTS_MSG
+            .replace("{1}", &stats.sent.to_string())
+            .replace("{2}", &(stats.sent - stats.failed).to_string())
+            .replace("{3}", &stats.failed.to_string())
+            .replace("{4}", &format!("{:.2}", stats.error_rate()))
+    );
+    eprintln!(
+        "{}",
+        LOAD_RATE_MSG.replace("{}", &format!("{:.1}", achieved_rate))
+    );
+
+    if let (Some(min), Some(max)) = (stats.min, stats.max) {
+        let [p50, p90, p99] =
+            PERCENTILES.map(|percentile| stats.percentile(percentile).unwrap_or(max));
+        eprintln!(
+            "{}",
+            LOAD_LATENCY_MSG
+                .replace("{1}", &format!("{:.2?}", min))
+                .replace("{2}", &format!("{:.2?}", p50))
+                .replace("{3}", &format!("{:.2?}", p90))
+                .replace("{4}", &format!("{:.2?}", p99))
+                .replace("{5}", &format!("{:.2?}", max))
+        );
+    }
+    display_connection_summary(connections);
+
+    if !stats.errors.is_empty() {
+        eprintln!("{}", LOAD_ERRORS_HEADER);
+        for (error, count) in &stats.errors {
+            eprintln!(
+                "{}",
+                LOAD_ERROR_MSG
+                    .replace("{1}", &count.to_string())
+                    .replace("{2}", error)
+            );
+        }
+    }
+}
+
+/// レイテンシが属するバケットの番号
+fn bucket_index(latency: Duration) -> usize {
+    let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+    if micros < EXACT_BUCKETS {
+        return micros as usize;
+    }
+    let shift = micros.ilog2() - SUB_BUCKET_BITS;
+    let index = EXACT_BUCKETS
+        + u64::from(shift - 1) * (1 << SUB_BUCKET_BITS)
+        + ((micros >> shift) - (1 << SUB_BUCKET_BITS));
+    (index as usize).min(HISTOGRAM_BUCKETS - 1)
+}
+
+/// バケットの代表値（範囲の中央）
+fn bucket_value(index: usize) -> Duration {
+    let index = index as u64;
+    if index < EXACT_BUCKETS {
+        return Duration::from_micros(index);
+    }
+    let sub_buckets = 1 << SUB_BUCKET_BITS;
+    let shift = (index - EXACT_BUCKETS) / sub_buckets + 1;
+    let lower = (sub_buckets + (index - EXACT_BUCKETS) % sub_buckets) << shift;
+    Duration::from_micros(lower + (1 << shift) / 2)
+}
@@ -0,0 +1,152 @@
+use crate::connection_stats::ConnectionSummary;
+use crate::summary::csv_field;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// 出力形式（拡張子）
+const EXTENSION_JSON: &str = "json";
+const EXTENSION_CSV: &str = "csv";
+
+// CSV
+const CSV_HEADER: &str = "timestamp,offset_ms,status,latency_ms,bytes,error";
+
+// エラーメッセージ
+const ERROR_INVALID_BENCH_OUTPUT: &str =
+    "Invalid --bench-output '{}'. The file name must end with .json or .csv.";
+const ERROR_BENCH_OUTPUT_CREATE: &str = "Failed to create '{1}': {2}";
+
+/// 結果ファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LoadOutputFormat {
+    Json,
+    Csv,
+}
+
+/// 1リクエスト分の結果（送信予定時刻と計測開始からの経過時間を含む）
+#[derive(Debug, Serialize)]
+pub struct SampleRecord {
+    pub timestamp: String,
+    pub offset_ms: f64,
+    pub status: Option<u16>,
+    pub latency_ms: Option<f64>,
+    pub bytes: Option<usize>,
+    pub error: Option<String>,
+}
+
+/// レイテンシの集計（ミリ秒）
+#[derive(Debug, Serialize)]
+pub struct LatencyRecord {
+    pub min: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// 負荷試験全体の集計
+#[derive(Debug, Serialize)]
+pub struct SummaryRecord<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub rps: u32,
+    pub duration: &'a str,
+    pub warmup: u64,
+    pub sent: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub error_rate: f64,
+    pub achieved_rate: f64,
+    pub latency_ms: Option<LatencyRecord>,
+    pub errors: &'a BTreeMap<String, u64>,
+    pub connections: &'a ConnectionSummary,
+}
+
+/// 負荷試験の結果ファイル（--bench-output）
+///
+/// サンプルは受け取るたびに書き出し、JSONでは最後に集計を追加する。
+pub struct LoadOutput {
+    writer: BufWriter<File>,
+    format: LoadOutputFormat,
+    samples: u64,
+}
+
+impl LoadOutput {
+    /// 拡張子から形式を決めてファイルを作成
+    pub fn create(path: &str) -> Result<Self, Box<dyn Error>> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        let format = match extension.as_deref() {
+            Some(EXTENSION_JSON) => LoadOutputFormat::Json,
+            Some(EXTENSION_CSV) => LoadOutputFormat::Csv,
+            _ => return Err(ERROR_INVALID_BENCH_OUTPUT.replace("{}", path).into()),
+        };
+        let file = File::create(path).map_err(|e| {
+            ERROR_BENCH_OUTPUT_CREATE
+                .replace("{1}", path)
+                .replace("{2}", &e.to_string())
+        })?;
+
+        let mut writer = BufWriter::new(file);
+        match format {
+            LoadOutputFormat::Json => writeln!(writer, "{{\"samples\":[")?,
+            LoadOutputFormat::Csv => writeln!(writer, "{}", CSV_HEADER)?,
+        }
+        Ok(Self {
+            writer,
+            format,
+            samples: 0,
+        })
+    }
+
+    /// 1リクエスト分の結果を書き出す
+    pub fn write_sample(&mut self, sample: &SampleRecord) -> io::Result<()> {
+        match self.format {
+            LoadOutputFormat::Json => {
+                if self.samples > 0 {
+                    writeln!(self.writer, ",")?;
+                }
+                serde_json::to_writer(&mut self.writer, sample)?;
+            }
+            LoadOutputFormat::Csv => writeln!(
+                self.writer,
+                "{},{:.3},{},{},{},{}",
+                sample.timestamp,
+                sample.offset_ms,
+                sample
+                    .status
+                    .map(|status| status.to_string())
+                    .unwrap_or_default(),
+                sample
+                    .latency_ms
+                    .map(|latency| format!("{:.3}", latency))
+                    .unwrap_or_default(),
+                sample
+                    .bytes
+                    .map(|bytes| bytes.to_string())
+                    .unwrap_or_default(),
+                csv_field(sample.error.as_deref().unwrap_or_default())
+            )?,
+        }
+        self.samples += 1;
+        Ok(())
+    }
+
+    /// 集計を書き出してファイルを閉じる（CSVはサンプルのみ）
+    pub fn finish(mut self, summary: &SummaryRecord) -> io::Result<()> {
+        if self.format == LoadOutputFormat::Json {
+            if self.samples > 0 {
+                writeln!(self.writer)?;
+            }
+            write!(self.writer, "],\"summary\":")?;
+            serde_json::to_writer_pretty(&mut self.writer, summary)?;
+            writeln!(self.writer, "}}")?;
+        }
+        self.writer.flush()
+    }
+}
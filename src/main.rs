@@ -1,59 +1,294 @@
-mod client;
-
-use crate::client::{execute_request, load_config_file, BasicAuthConfig, Config, ProxyConfig};
-use clap::{arg, Parser};
+use reqwest::{Method, Url};
+use rs_w3r::assertion::{display_test_results, run_preset_tests};
+use rs_w3r::test_report::{parse_test_report, write_test_report};
+use rs_w3r::audit::{audit_security_headers, DEFAULT_AUDIT_MIN_SCORE};
+use rs_w3r::builder::ConfigBuilder;
+use rs_w3r::client::{
+    create_helper_client, execute_request, load_config_file, parse_duration, shows_meta,
+    BasicAuthConfig, Config, ProxyConfig, Transform, QUIET_BODY,
+};
+use rs_w3r::cors::{check_cors_preflight, CorsRequest};
+use rs_w3r::interrupt::{install_interrupt_handler, is_interrupted, EXIT_INTERRUPTED};
+use rs_w3r::keychain::{keychain_delete, keychain_store};
+use rs_w3r::oauth2::{authorize_device, authorize_login};
+use rs_w3r::picker::pick_preset;
+use rs_w3r::presign::{
+    presign_s3_url, upload_to_presigned, PresignRequest, DEFAULT_PRESIGN_EXPIRES_SECS,
+};
+use rs_w3r::status::{display_status_table, run_status_checks};
+use rs_w3r::tui::run_request_builder;
+use rs_w3r::wait::{wait_for_endpoint, DEFAULT_WAIT_INTERVAL, DEFAULT_WAIT_TIMEOUT};
+use rs_w3r::variables::resolve_variables;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use std::error::Error;
+use std::time::Duration;
 
-use crate::client::{DEFAULT_METHOD, DEFAULT_TIMEOUT_SECS, DEFAULT_RETRY_COUNT, DEFAULT_RETRY_DELAY};
+use rs_w3r::client::{
+    DEFAULT_CONNECT_RETRY_COUNT, DEFAULT_METHOD, DEFAULT_RETRY_COUNT, DEFAULT_RETRY_DELAY,
+    DEFAULT_TIMEOUT_SECS,
+};
 
 // エラーメッセージ定数
-const ERROR_MISSING_URL: &str = "URL is required. Use -u/--url option or specify in config file.";
+const ERROR_MISSING_URL: &str =
+    "URL is required. Use -u/--url or --url-file option or specify in config file.";
+const ERROR_INVALID_BATCH_SIZE: &str = "Batch size must be greater than 0.";
+const ERROR_INVALID_CONCURRENCY: &str = "Concurrency must be greater than 0.";
+const ERROR_CONFLICTING_FAILURE_POLICY: &str = "--fail-fast and --keep-going cannot be used together.";
+const ERROR_INVALID_METHOD: &str = "Invalid HTTP method '{}'.";
+const ERROR_INVALID_RETRY_DELAY: &str = "Retry delay must be a non-negative number of seconds.";
+const ERROR_MISSING_GOLDEN_FILE: &str = "--update-golden requires --expect-body-file.";
+const ERROR_CHANGES_WITHOUT_WATCH: &str = "--changes-only and --show-diff require --watch.";
+const ERROR_POLL_WITHOUT_UNTIL: &str = "--poll and --max-wait require --until.";
+const ERROR_UNTIL_WITH_WATCH: &str = "--until and --watch cannot be used together.";
+const ERROR_RETRY_IF_BODY_WITHOUT_RETRY: &str = "--retry-if-body requires --retry.";
+const ERROR_CONTENT_TYPE_WITHOUT_DATA_BINARY: &str = "--content-type requires --data-binary.";
+const ERROR_DURATION_WITHOUT_MODE: &str = "--duration and --report-interval require --rps or --watch.";
+const ERROR_INVALID_PARAM: &str = "Invalid --param '{}'. Use name=value.";
+const ERROR_INVALID_QUERY: &str = "Invalid --query '{}'. Use name=value, or name= to remove it.";
+const ERROR_MISSING_TEST_CONFIG: &str = "The test command requires a config file. Use -c/--config.";
+const ERROR_MISSING_PICK_CONFIG: &str = "The pick command requires a config file. Use -c/--config.";
+const ERROR_MISSING_STATUS_CONFIG: &str = "The status command requires a config file. Use -c/--config.";
+const ERROR_ENV_WITHOUT_CONFIG: &str = "--env requires a config file. Use -c/--config.";
+const ERROR_MISSING_TUI_CONFIG: &str = "The tui command requires a config file. Use -c/--config.";
+const ERROR_TESTS_FAILED: &str = "{1} of {2} presets failed";
+const ERROR_UNHEALTHY_ENDPOINTS: &str = "{1} of {2} endpoints are unhealthy";
+const ERROR_MISSING_OAUTH2: &str =
+    "OAuth2 is not configured. Add a [preset.<name>.oauth2] section and select it with -c/--config and --preset.";
+const ERROR_UNSUPPORTED_SCHEME: &str = "Unsupported URL scheme '{1}' in {2}. Use http:// or https://.";
+const ERROR_INVALID_URL_CHAR: &str = "URL contains {1} at position {2}: {3}";
+const ERROR_INVALID_URL: &str = "Invalid URL '{1}': {2}";
+
+// URLの検証
+const DEFAULT_URL_SCHEME: &str = "https";
+const SUPPORTED_URL_SCHEMES: [&str; 2] = ["http", "https"];
+const SCHEME_SEPARATOR: &str = "://";
+const STDIN_URL: &str = "-";
+const URL_GLOB_CHARS: [char; 2] = ['[', '{'];
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[arg(long, env = "API_KEY")]
+    api_key: Option<String>,
+
+    #[arg(long)]
+    api_key_in: Option<String>,
+
+    #[arg(long, action = clap::ArgAction::Append)]
+    assert_header: Option<Vec<String>>,
+
+    #[arg(long, action = clap::ArgAction::Append)]
+    assert_header_regex: Option<Vec<String>>,
+
+    #[arg(long)]
+    attempt_timeout: Option<String>,
+
+    #[arg(long)]
+    aws_sigv4: Option<String>,
+
+    #[arg(long)]
+    batch_size: Option<usize>,
+
+    #[arg(long)]
+    bench_output: Option<String>,
+
     #[arg(long, env = "BASIC_USER")]
     basic_user: Option<String>,
 
     #[arg(long, env = "BASIC_PASS")]
     basic_pass: Option<String>,
 
-    #[arg(short, long)]
+    #[arg(long, env = "BEARER_TOKEN")]
+    bearer: Option<String>,
+
+    #[arg(long)]
+    cert: Option<String>,
+
+    #[arg(long)]
+    cert_type: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    changes_only: bool,
+
+    #[arg(long)]
+    concurrency: Option<usize>,
+
+    #[arg(short, long, global = true)]
     config: Option<String>,
 
+    #[arg(long, default_value_t = DEFAULT_CONNECT_RETRY_COUNT)]
+    connect_retry: u32,
+
+    #[arg(long)]
+    connect_timeout: Option<u64>,
+
+    #[arg(long)]
+    content_type: Option<String>,
+
     #[arg(long, action = clap::ArgAction::Append)]
     cookies: Option<Vec<String>>,
 
+    #[arg(long)]
+    cookies_from: Option<String>,
+
+    #[arg(long)]
+    data_binary: Option<String>,
+
+    #[arg(long)]
+    dns_cache: Option<String>,
+
     #[arg(long, default_value_t = false)]
     dry_run: bool,
 
+    #[arg(long)]
+    dump_certs: Option<String>,
+
+    #[arg(long)]
+    duration: Option<String>,
+
+    #[arg(long)]
+    each: Option<String>,
+
+    #[arg(long, global = true)]
+    env: Option<String>,
+
+    #[arg(long)]
+    expect_body_file: Option<String>,
+
+    #[arg(long, num_args = 1.., action = clap::ArgAction::Append)]
+    export_shell: Option<Vec<String>>,
+
+    #[arg(long, default_value_t = false)]
+    fail_fast: bool,
+
+    #[arg(long, default_value_t = false)]
+    force_token_refresh: bool,
+
     #[arg(short, long)]
     form_data: Option<String>,
 
     #[arg(long, action = clap::ArgAction::Append)]
     form: Option<Vec<String>>,
 
+    #[arg(long)]
+    format: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    globoff: bool,
+
     #[arg(long, action = clap::ArgAction::Append)]
     headers: Option<Vec<String>>,
 
+    #[arg(long, global = true, env = "AGE_IDENTITY")]
+    identity: Option<String>,
+
+    #[arg(long)]
+    hide_headers: Option<String>,
+
+    #[arg(long)]
+    hsts_file: Option<String>,
+
     #[arg(short, long)]
     json: Option<String>,
 
     #[arg(long)]
     json_filter: Option<String>,
 
+    #[arg(long)]
+    key: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    keep_going: bool,
+
+    #[arg(long, action = clap::ArgAction::Append)]
+    map: Option<Vec<String>>,
+
+    #[arg(long)]
+    max_format_size: Option<String>,
+
+    #[arg(long)]
+    max_time: Option<String>,
+
+    #[arg(long)]
+    max_time_assert: Option<String>,
+
+    #[arg(long)]
+    max_wait: Option<String>,
+
+    #[arg(long)]
+    meta_format: Option<String>,
+
     #[arg(short, long, default_value = DEFAULT_METHOD)]
     method: String,
 
+    #[arg(long)]
+    ndjson: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    ndjson_response: bool,
+
+    #[arg(long, default_value_t = false)]
+    no_hsts: bool,
+
+    #[arg(long, default_value_t = false)]
+    no_input: bool,
+
+    #[arg(long, default_value_t = false)]
+    no_pager: bool,
+
+    #[arg(long, default_value_t = false)]
+    no_progress: bool,
+
+    #[arg(long, default_value_t = false)]
+    open: bool,
+
+    #[cfg(feature = "otel")]
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otel_endpoint: Option<String>,
+
     #[arg(short, long)]
     output: Option<String>,
 
+    #[arg(long, default_value_t = false)]
+    output_append: bool,
+
+    #[arg(long)]
+    output_order: Option<String>,
+
     #[arg(long)]
+    output_rotate: Option<String>,
+
+    #[arg(long)]
+    output_template: Option<String>,
+
+    #[arg(long, action = clap::ArgAction::Append)]
+    param: Option<Vec<String>>,
+
+    #[arg(long)]
+    poll: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    post301: bool,
+
+    #[arg(long, default_value_t = false)]
+    post302: bool,
+
+    #[arg(long, default_value_t = false)]
+    post303: bool,
+
+    #[arg(long, global = true)]
     preset: Option<String>,
 
     #[arg(long, default_value_t = false)]
     pretty_json: bool,
 
+    #[arg(long, env = "PROXY_URL")]
+    proxy: Option<String>,
+
     #[arg(long, env = "PROXY_HOST")]
     proxy_host: Option<String>,
 
@@ -66,81 +301,600 @@ struct Args {
     #[arg(long, env = "PROXY_PASS")]
     proxy_pass: Option<String>,
 
+    #[arg(long, action = clap::ArgAction::Append)]
+    query: Option<Vec<String>>,
+
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    quiet: u8,
+
+    #[arg(long, allow_hyphen_values = true)]
+    range: Option<String>,
+
+    #[arg(short = 'O', long, default_value_t = false)]
+    remote_name: bool,
+
+    #[arg(long)]
+    report_interval: Option<String>,
+
     #[arg(long, default_value_t = DEFAULT_RETRY_COUNT)]
     retry: u32,
 
+    #[arg(long)]
+    rps: Option<u32>,
+
     #[arg(long, default_value_t = DEFAULT_RETRY_DELAY)]
     retry_delay: f64,
 
-    #[arg(short, long, default_value_t = false)]
+    #[arg(long)]
+    retry_if_body: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    show_cookies: bool,
+
+    #[arg(long, default_value_t = false)]
+    show_diff: bool,
+
+    #[arg(long)]
+    show_headers: Option<String>,
+
+    #[arg(short, long, default_value_t = false, hide = true)]
     silent: bool,
 
+    #[arg(long, default_value_t = false)]
+    sort: bool,
+
+    #[arg(long)]
+    summary_format: Option<String>,
+
+    #[arg(long)]
+    tcp_keepalive: Option<u64>,
+
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    tcp_nodelay: Option<bool>,
+
     #[arg(short, long, default_value_t = DEFAULT_TIMEOUT_SECS)]
     timeout: u64,
 
     #[arg(long, default_value_t = false)]
     timing: bool,
 
+    #[arg(long, default_value_t = false)]
+    timing_waterfall: bool,
+
+    #[arg(long)]
+    tls_keylog: Option<String>,
+
+    #[arg(long, num_args = 0..=1, default_missing_value = "auto")]
+    traceparent: Option<String>,
+
+    #[arg(long)]
+    tracestate: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    unique: bool,
+
+    #[arg(long)]
+    until: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    update_golden: bool,
+
     #[arg(short, long)]
     url: Option<String>,
 
+    #[arg(long)]
+    url_file: Option<String>,
+
     #[arg(short, long, default_value_t = false)]
     verbose: bool,
+
+    #[arg(long, default_value_t = false)]
+    view: bool,
+
+    #[arg(long)]
+    warmup: Option<String>,
+
+    #[arg(long)]
+    warn_size: Option<String>,
+
+    #[arg(long)]
+    watch: Option<String>,
+
+    #[arg(long)]
+    yaml: Option<String>,
+
+    #[arg(short, long, default_value_t = false)]
+    yes: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Commands {
+    #[command(about = "Manage OAuth2 tokens for presets")]
+    Auth {
+        #[command(subcommand)]
+        action: AuthCommand,
+    },
+
+    #[command(about = "Score the response's security headers and cookie flags")]
+    Audit {
+        #[arg(short, long)]
+        url: Option<String>,
+
+        #[arg(long, default_value_t = DEFAULT_AUDIT_MIN_SCORE)]
+        min_score: u8,
+    },
+
+    #[command(about = "Send a CORS preflight and report whether the actual request would be allowed")]
+    Cors {
+        #[arg(short, long)]
+        url: Option<String>,
+
+        #[arg(long)]
+        origin: String,
+
+        #[arg(long, default_value = DEFAULT_METHOD)]
+        request_method: String,
+
+        #[arg(long, value_delimiter = ',')]
+        request_headers: Vec<String>,
+
+        #[arg(long, default_value_t = false)]
+        credentials: bool,
+    },
+
+    #[command(about = "Fuzzy-search the presets in the config file and run the selected one")]
+    Pick {
+        query: Option<String>,
+    },
+
+    #[command(about = "Print a presigned URL for an S3 object (s3://bucket/key)")]
+    Presign {
+        s3_url: String,
+
+        #[arg(long, default_value_t = DEFAULT_PRESIGN_EXPIRES_SECS)]
+        expires: u64,
+
+        #[arg(long, default_value = DEFAULT_METHOD)]
+        method: String,
+
+        #[arg(long)]
+        upload_to_presigned: Option<String>,
+    },
+
+    #[command(about = "Store or delete credentials in the OS keychain (referenced as keychain:<name>)")]
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsCommand,
+    },
+
+    #[command(about = "Check every preset (or the [status] group) in parallel and print a health table")]
+    Status,
+
+    #[command(about = "Run every preset that has [preset.<name>.assert] and report pass/fail")]
+    Test {
+        #[arg(long)]
+        report: Option<String>,
+    },
+
+    #[command(about = "Pick a preset, edit and send it in a full-screen form, and save it back to the config file")]
+    Tui,
+
+    #[command(about = "Wait until the endpoint returns 2xx, retrying connection errors quietly")]
+    Wait {
+        #[arg(short, long)]
+        url: Option<String>,
+
+        #[arg(long, default_value = DEFAULT_WAIT_TIMEOUT)]
+        timeout: String,
+
+        #[arg(long, default_value = DEFAULT_WAIT_INTERVAL)]
+        interval: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum AuthCommand {
+    #[command(about = "Obtain a token with the OAuth2 device authorization grant")]
+    Device,
+
+    #[command(about = "Obtain a token with the OAuth2 authorization code grant and PKCE")]
+    Login,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum SecretsCommand {
+    #[command(about = "Prompt for a value and store it in the keychain")]
+    Set { name: String },
+
+    #[command(about = "Delete a value from the keychain")]
+    Delete { name: String },
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches)?;
+
+    // pick サブコマンドは選択したプリセットを通常のリクエストとして実行
+    if let Some(Commands::Pick { query }) = &args.command {
+        let config_path = args.config.as_deref().ok_or(ERROR_MISSING_PICK_CONFIG)?;
+        let preset = pick_preset(config_path, args.identity.as_deref(), query.as_deref())?;
+        args.preset = Some(preset);
+        args.command = None;
+    }
+
+    let transforms = collect_transforms(&args, &matches);
+    let params = parse_params(args.param.as_deref().unwrap_or_default())?;
 
     // 設定ファイルの読み込み
-    let mut config = load_config_if_specified(&args)?;
+    let config = load_config_if_specified(&args)?;
 
     // コマンドライン引数で設定ファイルの値をオーバーライド
-    apply_args_to_config(&mut config, args);
+    let command = args.command.clone();
+    let mut config = apply_args_to_config(config, &args, &matches)?;
+    apply_transform_config(&mut config, transforms);
+    apply_param_config(&mut config, params);
+
+    // サブコマンドが指定されている場合はそちらを実行
+    if let Some(command) = command {
+        return run_command(command, &config, &args);
+    }
 
-    // URLが設定されていない場合はエラー
-    validate_config(&config)?;
+    // {{var}} 形式の変数を置換（未定義の変数は対話的に入力）
+    resolve_variables(&mut config)?;
 
-    // HTTP リクエスト実行
-    execute_request(config)?;
+    // URLが設定されていない場合・不正な場合はエラー
+    validate_config(&mut config)?;
+
+    // HTTP リクエスト実行（Ctrl-C では受信済みの分を出力してから専用の終了コードで終了）
+    install_interrupt_handler()?;
+    let result = execute_request(config);
+    if is_interrupted() {
+        if let Err(e) = result {
+            eprintln!("Error: {:?}", e);
+        }
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+    result?;
 
     Ok(())
 }
 
+/// サブコマンドを実行
+fn run_command(command: Commands, config: &Config, args: &Args) -> Result<(), Box<dyn Error>> {
+    match command {
+        Commands::Auth {
+            action: AuthCommand::Device,
+        } => {
+            let oauth2_config = config.oauth2.as_ref().ok_or(ERROR_MISSING_OAUTH2)?;
+            authorize_device(oauth2_config, config)
+        }
+        Commands::Auth {
+            action: AuthCommand::Login,
+        } => {
+            let oauth2_config = config.oauth2.as_ref().ok_or(ERROR_MISSING_OAUTH2)?;
+            authorize_login(oauth2_config, config)
+        }
+        Commands::Audit { url, min_score } => {
+            let url = url.unwrap_or_else(|| config.url.clone());
+            if url.is_empty() {
+                return Err(ERROR_MISSING_URL.into());
+            }
+
+            audit_security_headers(&create_helper_client(config)?, &url, min_score)
+        }
+        Commands::Cors {
+            url,
+            origin,
+            request_method,
+            request_headers,
+            credentials,
+        } => {
+            let url = url.unwrap_or_else(|| config.url.clone());
+            if url.is_empty() {
+                return Err(ERROR_MISSING_URL.into());
+            }
+
+            check_cors_preflight(
+                &create_helper_client(config)?,
+                &CorsRequest {
+                    url: &url,
+                    origin: &origin,
+                    method: &request_method,
+                    headers: &request_headers,
+                    credentials,
+                },
+            )
+        }
+        // main で選択したプリセットの実行に置き換え済み
+        Commands::Pick { .. } => Ok(()),
+        Commands::Presign {
+            s3_url,
+            expires,
+            method,
+            upload_to_presigned: upload_file,
+        } => {
+            let method = if upload_file.is_some() {
+                reqwest::Method::PUT.to_string()
+            } else {
+                method
+            };
+            let presigned_url = presign_s3_url(&PresignRequest {
+                s3_url: &s3_url,
+                method: &method,
+                expires,
+            })?;
+
+            match upload_file {
+                Some(file_path) => upload_to_presigned(
+                    &presigned_url,
+                    &file_path,
+                    Duration::from_secs(config.timeout),
+                ),
+                None => {
+                    println!("{}", presigned_url);
+                    Ok(())
+                }
+            }
+        }
+        Commands::Secrets {
+            action: SecretsCommand::Set { name },
+        } => keychain_store(&name),
+        Commands::Secrets {
+            action: SecretsCommand::Delete { name },
+        } => keychain_delete(&name),
+        Commands::Status => {
+            let config_path = args.config.as_deref().ok_or(ERROR_MISSING_STATUS_CONFIG)?;
+            let results = run_status_checks(config_path, args.identity.as_deref(), args.env.as_deref())?;
+            display_status_table(&results);
+
+            let unhealthy = results.iter().filter(|result| !result.healthy()).count();
+            if unhealthy > 0 {
+                return Err(ERROR_UNHEALTHY_ENDPOINTS
+                    .replace("{1}", &unhealthy.to_string())
+                    .replace("{2}", &results.len().to_string())
+                    .into());
+            }
+            Ok(())
+        }
+        Commands::Test { report } => {
+            let config_path = args.config.as_deref().ok_or(ERROR_MISSING_TEST_CONFIG)?;
+            let report = report.as_deref().map(parse_test_report).transpose()?;
+            let results = run_preset_tests(config_path, args.identity.as_deref(), args.env.as_deref())?;
+            if !report.as_ref().is_some_and(|report| report.writes_to_stdout()) {
+                display_test_results(&results);
+            }
+
+            if let Some(report) = &report {
+                write_test_report(report, &results)?;
+            }
+
+            let failed = results.iter().filter(|result| !result.passed()).count();
+            if failed > 0 {
+                return Err(ERROR_TESTS_FAILED
+                    .replace("{1}", &failed.to_string())
+                    .replace("{2}", &results.len().to_string())
+                    .into());
+            }
+            Ok(())
+        }
+        Commands::Tui => {
+            let config_path = args.config.as_deref().ok_or(ERROR_MISSING_TUI_CONFIG)?;
+            run_request_builder(config_path, args.identity.as_deref(), config)
+        }
+        Commands::Wait {
+            url,
+            timeout,
+            interval,
+        } => {
+            let url = url.unwrap_or_else(|| config.url.clone());
+            if url.is_empty() {
+                return Err(ERROR_MISSING_URL.into());
+            }
+
+            wait_for_endpoint(
+                &create_helper_client(config)?,
+                &url,
+                &timeout,
+                &interval,
+                !shows_meta(config),
+            )
+        }
+    }
+}
+
 /// 設定ファイルが指定されている場合に読み込む
 fn load_config_if_specified(args: &Args) -> Result<Config, Box<dyn Error>> {
     match &args.config {
-        Some(config_path) => load_config_file(config_path, args.preset.as_deref()),
+        Some(config_path) => load_config_file(
+            config_path,
+            args.preset.as_deref(),
+            args.identity.as_deref(),
+            args.env.as_deref(),
+        ),
+        None if args.env.is_some() => Err(ERROR_ENV_WITHOUT_CONFIG.into()),
         None => Ok(Config::default()),
     }
 }
 
 /// 設定の有効性を検証
-fn validate_config(config: &Config) -> Result<(), Box<dyn Error>> {
-    if config.url.is_empty() {
+fn validate_config(config: &mut Config) -> Result<(), Box<dyn Error>> {
+    if config.url.is_empty() && config.url_file.is_none() {
         return Err(ERROR_MISSING_URL.into());
     }
+
+    if !config.url.is_empty() && config.url != STDIN_URL {
+        config.url = normalize_url(&config.url, config.globoff)?;
+    }
+
+    if config.batch_size == Some(0) {
+        return Err(ERROR_INVALID_BATCH_SIZE.into());
+    }
+
+    if config.concurrency == Some(0) {
+        return Err(ERROR_INVALID_CONCURRENCY.into());
+    }
+
+    if config.fail_fast && config.keep_going {
+        return Err(ERROR_CONFLICTING_FAILURE_POLICY.into());
+    }
+
+    if config.update_golden && config.expect_body_file.is_none() {
+        return Err(ERROR_MISSING_GOLDEN_FILE.into());
+    }
+
+    if (config.changes_only || config.show_diff) && config.watch.is_none() {
+        return Err(ERROR_CHANGES_WITHOUT_WATCH.into());
+    }
+
+    if (config.poll.is_some() || config.max_wait.is_some()) && config.until.is_none() {
+        return Err(ERROR_POLL_WITHOUT_UNTIL.into());
+    }
+
+    if config.until.is_some() && config.watch.is_some() {
+        return Err(ERROR_UNTIL_WITH_WATCH.into());
+    }
+
+    if config.retry_if_body.is_some() && config.retry == 0 {
+        return Err(ERROR_RETRY_IF_BODY_WITHOUT_RETRY.into());
+    }
+
+    if config.content_type.is_some() && config.data_binary.is_none() {
+        return Err(ERROR_CONTENT_TYPE_WITHOUT_DATA_BINARY.into());
+    }
+
+    if (config.duration.is_some() || config.report_interval.is_some())
+        && config.rps.is_none()
+        && config.watch.is_none()
+    {
+        return Err(ERROR_DURATION_WITHOUT_MODE.into());
+    }
+
     Ok(())
 }
 
+/// URLを検証して正規化（スキームが省略されている場合は https:// を補う）
+fn normalize_url(url: &str, globoff: bool) -> Result<String, Box<dyn Error>> {
+    let url = url.trim();
+
+    if let Some((position, invalid_char)) = url
+        .chars()
+        .enumerate()
+        .find(|(_, c)| c.is_whitespace() || c.is_control() || *c == '\\')
+    {
+        return Err(ERROR_INVALID_URL_CHAR
+            .replace("{1}", &describe_url_char(invalid_char))
+            .replace("{2}", &(position + 1).to_string())
+            .replace("{3}", url)
+            .into());
+    }
+
+    let url = match url_scheme(url) {
+        Some(scheme) if SUPPORTED_URL_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) => {
+            url.to_string()
+        }
+        Some(scheme) => {
+            return Err(ERROR_UNSUPPORTED_SCHEME
+                .replace("{1}", scheme)
+                .replace("{2}", url)
+                .into())
+        }
+        None => format!("{}{}{}", DEFAULT_URL_SCHEME, SCHEME_SEPARATOR, url),
+    };
+
+    // グロブを含むURLは展開後に解釈されるため、ここでは解析しない
+    if globoff || !url.contains(URL_GLOB_CHARS) {
+        Url::parse(&url).map_err(|e| {
+            ERROR_INVALID_URL
+                .replace("{1}", &url)
+                .replace("{2}", &e.to_string())
+        })?;
+    }
+
+    Ok(url)
+}
+
+/// URL先頭のスキーム（"scheme://" の形式でない場合はNone）
+fn url_scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(SCHEME_SEPARATOR)?;
+    let mut chars = scheme.chars();
+    let valid = chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// エラー表示用の文字の説明
+fn describe_url_char(c: char) -> String {
+    match c {
+        ' ' => "a space (use %20)".to_string(),
+        '\t' => "a tab".to_string(),
+        '\\' => "a backslash".to_string(),
+        c if c.is_whitespace() => format!("whitespace U+{:04X}", c as u32),
+        c => format!("control character U+{:04X}", c as u32),
+    }
+}
+
 /// コマンドライン引数を設定に反映
-fn apply_args_to_config(config: &mut Config, args: Args) {
-    apply_auth_config(config, &args);
-    apply_data_config(config, &args);
-    apply_request_config(config, &args);
-    apply_proxy_config(config, &args);
-    apply_output_config(config, &args);
-    apply_retry_config(config, &args);
-    apply_flags(config, &args);
+fn apply_args_to_config(
+    config: Config,
+    args: &Args,
+    matches: &ArgMatches,
+) -> Result<Config, Box<dyn Error>> {
+    let builder = apply_request_config(ConfigBuilder::from(config), args, matches)?;
+    let mut config = apply_retry_config(builder, args, matches)?.build();
+
+    apply_auth_config(&mut config, args);
+    apply_data_config(&mut config, args);
+    apply_header_config(&mut config, args);
+    apply_query_config(&mut config, args)?;
+    apply_file_config(&mut config, args);
+    apply_proxy_config(&mut config, args);
+    apply_load_config(&mut config, args);
+    apply_output_config(&mut config, args);
+    apply_flags(&mut config, args);
+
+    Ok(config)
 }
 
 /// 認証設定の適用
 fn apply_auth_config(config: &mut Config, args: &Args) {
+    if args.force_token_refresh {
+        config.force_token_refresh = true;
+    }
+
+    if let Some(api_key) = &args.api_key {
+        config.api_key = Some(api_key.clone());
+    }
+
+    if let Some(api_key_in) = &args.api_key_in {
+        config.api_key_in = Some(api_key_in.clone());
+    }
+
     if let (Some(basic_user), Some(basic_pass)) = (&args.basic_user, &args.basic_pass) {
         config.basic_auth = Some(BasicAuthConfig {
             user: basic_user.clone(),
             pass: basic_pass.clone(),
         });
     }
+
+    if let Some(bearer) = &args.bearer {
+        config.bearer_token = Some(bearer.clone());
+    }
+
+    if let Some(aws_sigv4) = &args.aws_sigv4 {
+        config.aws_sigv4 = Some(aws_sigv4.clone());
+    }
+
+    if let Some(cert) = &args.cert {
+        config.cert = Some(cert.clone());
+    }
+
+    if let Some(cert_type) = &args.cert_type {
+        config.cert_type = Some(cert_type.clone());
+    }
+
+    if let Some(key) = &args.key {
+        config.key = Some(key.clone());
+    }
 }
 
 /// データ送信設定の適用
@@ -157,17 +911,119 @@ fn apply_data_config(config: &mut Config, args: &Args) {
         config.json = Some(json.clone());
     }
 
+    if let Some(data_binary) = &args.data_binary {
+        config.data_binary = Some(data_binary.clone());
+    }
+
+    if let Some(content_type) = &args.content_type {
+        config.content_type = Some(content_type.clone());
+    }
+
+    if let Some(ndjson) = &args.ndjson {
+        config.ndjson = Some(ndjson.clone());
+    }
+
+    if let Some(batch_size) = args.batch_size {
+        config.batch_size = Some(batch_size);
+    }
+
+    if let Some(yaml) = &args.yaml {
+        config.yaml = Some(yaml.clone());
+    }
+
     if let Some(json_filter) = &args.json_filter {
         config.json_filter = Some(json_filter.clone());
     }
 }
 
 /// リクエスト設定の適用
-fn apply_request_config(config: &mut Config, args: &Args) {
+fn apply_request_config(
+    mut builder: ConfigBuilder,
+    args: &Args,
+    matches: &ArgMatches,
+) -> Result<ConfigBuilder, Box<dyn Error>> {
     if args.method != DEFAULT_METHOD {
-        config.method = args.method.clone();
+        let method = Method::from_bytes(args.method.as_bytes())
+            .map_err(|_| ERROR_INVALID_METHOD.replace("{}", &args.method))?;
+        builder = builder.method(method);
+    }
+
+    if let Some(url) = &args.url {
+        builder = builder.url(url);
     }
 
+    if let Some(concurrency) = args.concurrency {
+        builder = builder.concurrency(concurrency);
+    }
+
+    if is_given(matches, "timeout") {
+        builder = builder.timeout(Duration::from_secs(args.timeout));
+    }
+
+    if let Some(connect_timeout) = args.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+    }
+
+    if let Some(tcp_keepalive) = args.tcp_keepalive {
+        builder = builder.tcp_keepalive(Duration::from_secs(tcp_keepalive));
+    }
+
+    if let Some(tcp_nodelay) = args.tcp_nodelay {
+        builder = builder.tcp_nodelay(tcp_nodelay);
+    }
+
+    Ok(builder)
+}
+
+/// 負荷試験設定の適用
+fn apply_load_config(config: &mut Config, args: &Args) {
+    if let Some(rps) = args.rps {
+        config.rps = Some(rps);
+    }
+
+    if let Some(bench_output) = &args.bench_output {
+        config.bench_output = Some(bench_output.clone());
+    }
+
+    if let Some(duration) = &args.duration {
+        config.duration = Some(duration.clone());
+    }
+
+    if let Some(report_interval) = &args.report_interval {
+        config.report_interval = Some(report_interval.clone());
+    }
+
+    if let Some(warmup) = &args.warmup {
+        config.warmup = Some(warmup.clone());
+    }
+
+    if let Some(watch) = &args.watch {
+        config.watch = Some(watch.clone());
+    }
+
+    if args.changes_only {
+        config.changes_only = true;
+    }
+
+    if args.show_diff {
+        config.show_diff = true;
+    }
+
+    if let Some(until) = &args.until {
+        config.until = Some(until.clone());
+    }
+
+    if let Some(poll) = &args.poll {
+        config.poll = Some(poll.clone());
+    }
+
+    if let Some(max_wait) = &args.max_wait {
+        config.max_wait = Some(max_wait.clone());
+    }
+}
+
+/// ヘッダー・クッキー設定の適用
+fn apply_header_config(config: &mut Config, args: &Args) {
     if let Some(headers) = &args.headers {
         config.headers = Some(headers.clone());
     }
@@ -176,19 +1032,136 @@ fn apply_request_config(config: &mut Config, args: &Args) {
         config.cookies = Some(cookies.clone());
     }
 
-    if let Some(url) = &args.url {
-        config.url = url.clone();
+    if let Some(cookies_from) = &args.cookies_from {
+        config.cookies_from = Some(cookies_from.clone());
+    }
+
+    if let Some(range) = &args.range {
+        config.range = Some(range.clone());
+    }
+
+    if let Some(traceparent) = &args.traceparent {
+        config.traceparent = Some(traceparent.clone());
+    }
+
+    if let Some(tracestate) = &args.tracestate {
+        config.tracestate = Some(tracestate.clone());
+    }
+
+    #[cfg(feature = "otel")]
+    if let Some(otel_endpoint) = &args.otel_endpoint {
+        config.otel_endpoint = Some(otel_endpoint.clone());
+    }
+}
+
+/// クエリパラメータ設定の適用（プリセットの値に上書きし、name= の指定で削除）
+fn apply_query_config(config: &mut Config, args: &Args) -> Result<(), Box<dyn Error>> {
+    for query in args.query.iter().flatten() {
+        let (name, value) = match query.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => (name.trim(), value),
+            _ => return Err(ERROR_INVALID_QUERY.replace("{}", query).into()),
+        };
+
+        let merged = config.query.get_or_insert_with(Default::default);
+        if value.is_empty() {
+            merged.remove(name);
+        } else {
+            merged.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// 読み書きするファイルの設定の適用
+fn apply_file_config(config: &mut Config, args: &Args) {
+    if let Some(hsts_file) = &args.hsts_file {
+        config.hsts_file = Some(hsts_file.clone());
+    }
+
+    if let Some(dns_cache) = &args.dns_cache {
+        config.dns_cache = Some(dns_cache.clone());
+    }
+
+    if let Some(url_file) = &args.url_file {
+        config.url_file = Some(url_file.clone());
+    }
+}
+
+/// 変換処理をコマンドラインでの指定順に収集
+fn collect_transforms(args: &Args, matches: &ArgMatches) -> Vec<Transform> {
+    let mut indexed_transforms: Vec<(usize, Transform)> = Vec::new();
+
+    if let (Some(map_paths), Some(indices)) = (&args.map, matches.indices_of("map")) {
+        for (index, path) in indices.zip(map_paths) {
+            indexed_transforms.push((index, Transform::Map(path.clone())));
+        }
+    }
+
+    if args.sort {
+        if let Some(index) = matches.index_of("sort") {
+            indexed_transforms.push((index, Transform::Sort));
+        }
+    }
+
+    if args.unique {
+        if let Some(index) = matches.index_of("unique") {
+            indexed_transforms.push((index, Transform::Unique));
+        }
+    }
+
+    indexed_transforms.sort_by_key(|(index, _)| *index);
+    indexed_transforms
+        .into_iter()
+        .map(|(_, transform)| transform)
+        .collect()
+}
+
+/// コマンドラインで明示的に指定された引数か（既定値と同じ値の指定も含む）
+fn is_given(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+/// --param name=value を解析
+fn parse_params(params: &[String]) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    params
+        .iter()
+        .map(|param| match param.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.to_string()))
+            }
+            _ => Err(ERROR_INVALID_PARAM.replace("{}", param).into()),
+        })
+        .collect()
+}
+
+/// パラメータを変数の値として適用（設定ファイルの値より優先）
+fn apply_param_config(config: &mut Config, params: Vec<(String, String)>) {
+    if !params.is_empty() {
+        config.vars.get_or_insert_with(Default::default).extend(params);
     }
+}
 
-    if args.timeout != DEFAULT_TIMEOUT_SECS {
-        config.timeout = args.timeout;
+/// 変換処理の適用
+fn apply_transform_config(config: &mut Config, transforms: Vec<Transform>) {
+    if !transforms.is_empty() {
+        config.transforms = Some(transforms);
     }
 }
 
 /// プロキシ設定の適用
 fn apply_proxy_config(config: &mut Config, args: &Args) {
-    if let (Some(proxy_host), Some(proxy_port)) = (&args.proxy_host, &args.proxy_port) {
+    if let Some(proxy) = &args.proxy {
+        config.proxy = Some(ProxyConfig {
+            url: Some(proxy.clone()),
+            host: String::new(),
+            port: String::new(),
+            user: args.proxy_user.clone(),
+            pass: args.proxy_pass.clone(),
+        });
+    } else if let (Some(proxy_host), Some(proxy_port)) = (&args.proxy_host, &args.proxy_port) {
         config.proxy = Some(ProxyConfig {
+            url: None,
             host: proxy_host.clone(),
             port: proxy_port.clone(),
             user: args.proxy_user.clone(),
@@ -202,17 +1175,121 @@ fn apply_output_config(config: &mut Config, args: &Args) {
     if let Some(output) = &args.output {
         config.output = Some(output.clone());
     }
+
+    if args.output_append {
+        config.output_append = true;
+    }
+
+    if args.remote_name {
+        config.remote_name = true;
+    }
+
+    if let Some(output_order) = &args.output_order {
+        config.output_order = Some(output_order.clone());
+    }
+
+    if let Some(output_rotate) = &args.output_rotate {
+        config.output_rotate = Some(output_rotate.clone());
+    }
+
+    if let Some(format) = &args.format {
+        config.format = Some(format.clone());
+    }
+
+    if let Some(meta_format) = &args.meta_format {
+        config.meta_format = Some(meta_format.clone());
+    }
+
+    if let Some(summary_format) = &args.summary_format {
+        config.summary_format = Some(summary_format.clone());
+    }
+
+    if let Some(dump_certs) = &args.dump_certs {
+        config.dump_certs = Some(dump_certs.clone());
+    }
+
+    if let Some(tls_keylog) = &args.tls_keylog {
+        config.tls_keylog = Some(tls_keylog.clone());
+    }
+
+    if let Some(output_template) = &args.output_template {
+        config.output_template = Some(output_template.clone());
+    }
+
+    if let Some(each) = &args.each {
+        config.each = Some(each.clone());
+    }
+
+    if let Some(expect_body_file) = &args.expect_body_file {
+        config.expect_body_file = Some(expect_body_file.clone());
+    }
+
+    if let Some(export_shell) = &args.export_shell {
+        config.export_shell = Some(export_shell.clone());
+    }
+
+    if let Some(max_time_assert) = &args.max_time_assert {
+        config.max_time_assert = Some(max_time_assert.clone());
+    }
+
+    if let Some(assert_header) = &args.assert_header {
+        config.assert_header = Some(assert_header.clone());
+    }
+
+    if let Some(assert_header_regex) = &args.assert_header_regex {
+        config.assert_header_regex = Some(assert_header_regex.clone());
+    }
+
+    if let Some(warn_size) = &args.warn_size {
+        config.warn_size = Some(warn_size.clone());
+    }
+
+    if let Some(max_format_size) = &args.max_format_size {
+        config.max_format_size = Some(max_format_size.clone());
+    }
+
+    if let Some(show_headers) = &args.show_headers {
+        config.show_headers = Some(show_headers.clone());
+    }
+
+    if let Some(hide_headers) = &args.hide_headers {
+        config.hide_headers = Some(hide_headers.clone());
+    }
 }
 
 /// リトライ設定の適用
-fn apply_retry_config(config: &mut Config, args: &Args) {
-    if args.retry != DEFAULT_RETRY_COUNT {
-        config.retry = args.retry;
+fn apply_retry_config(
+    mut builder: ConfigBuilder,
+    args: &Args,
+    matches: &ArgMatches,
+) -> Result<ConfigBuilder, Box<dyn Error>> {
+    if is_given(matches, "retry") {
+        builder = builder.retry(args.retry);
+    }
+
+    if is_given(matches, "retry_delay") {
+        let retry_delay =
+            Duration::try_from_secs_f64(args.retry_delay).map_err(|_| ERROR_INVALID_RETRY_DELAY)?;
+        builder = builder.retry_delay(retry_delay);
     }
 
-    if args.retry_delay != DEFAULT_RETRY_DELAY {
-        config.retry_delay = args.retry_delay;
+    if let Some(attempt_timeout) = &args.attempt_timeout {
+        builder = builder.attempt_timeout(parse_duration(attempt_timeout)?);
     }
+
+    if let Some(max_time) = &args.max_time {
+        builder = builder.max_time(parse_duration(max_time)?);
+    }
+
+    if let Some(retry_if_body) = &args.retry_if_body {
+        builder = builder.retry_if_body(retry_if_body);
+    }
+
+    if args.connect_retry != DEFAULT_CONNECT_RETRY_COUNT {
+        builder = builder.connect_retry(args.connect_retry);
+    }
+
+    Ok(builder)
 }
 
 /// フラグの適用
@@ -221,19 +1298,92 @@ fn apply_flags(config: &mut Config, args: &Args) {
         config.dry_run = true;
     }
 
+    if args.fail_fast {
+        config.fail_fast = true;
+    }
+
+    if args.globoff {
+        config.globoff = true;
+    }
+
+    if args.keep_going {
+        config.keep_going = true;
+    }
+
+    if args.ndjson_response {
+        config.ndjson_response = true;
+    }
+
+    if args.no_hsts {
+        config.no_hsts = true;
+    }
+
+    if args.no_input {
+        config.no_input = true;
+    }
+
+    if args.no_pager {
+        config.no_pager = true;
+    }
+
+    if args.open {
+        config.open = true;
+    }
+
+    if args.post301 {
+        config.post301 = true;
+    }
+
+    if args.post302 {
+        config.post302 = true;
+    }
+
+    if args.post303 {
+        config.post303 = true;
+    }
+
     if args.pretty_json {
         config.pretty_json = true;
     }
 
+    if args.show_cookies {
+        config.show_cookies = true;
+    }
+
+    if args.quiet > 0 {
+        config.quiet = args.quiet;
+    }
+
+    // -s は以前のスクリプトとの互換のため -qq として扱う
     if args.silent {
-        config.silent = true;
+        config.quiet = config.quiet.max(QUIET_BODY);
+    }
+
+    if args.no_progress {
+        config.no_progress = true;
     }
 
     if args.timing {
         config.timing = true;
     }
 
+    if args.timing_waterfall {
+        config.timing_waterfall = true;
+    }
+
+    if args.update_golden {
+        config.update_golden = true;
+    }
+
+    if args.yes {
+        config.yes = true;
+    }
+
     if args.verbose {
         config.verbose = true;
     }
+
+    if args.view {
+        config.view = true;
+    }
 }
\ No newline at end of file
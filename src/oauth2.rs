@@ -0,0 +1,606 @@
+use crate::client::{create_helper_client, Config};
+use crate::util::{home_dir, unix_now};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::blocking::Client;
+use reqwest::header::ACCEPT;
+use reqwest::Url;
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 保存先
+const DEFAULT_TOKEN_PATH: &str = ".config/rs-w3r/tokens.json";
+#[cfg(unix)]
+const TOKEN_FILE_MODE: u32 = 0o600;
+const TEMP_FILE_SUFFIX: &str = ".tmp.";
+
+// リクエストパラメータ
+const PARAM_CLIENT_ID: &str = "client_id";
+const PARAM_CLIENT_SECRET: &str = "client_secret";
+const PARAM_SCOPE: &str = "scope";
+const PARAM_GRANT_TYPE: &str = "grant_type";
+const PARAM_DEVICE_CODE: &str = "device_code";
+const PARAM_REFRESH_TOKEN: &str = "refresh_token";
+const PARAM_RESPONSE_TYPE: &str = "response_type";
+const PARAM_REDIRECT_URI: &str = "redirect_uri";
+const PARAM_STATE: &str = "state";
+const PARAM_CODE: &str = "code";
+const PARAM_CODE_CHALLENGE: &str = "code_challenge";
+const PARAM_CODE_CHALLENGE_METHOD: &str = "code_challenge_method";
+const PARAM_CODE_VERIFIER: &str = "code_verifier";
+const PARAM_ERROR: &str = "error";
+const GRANT_TYPE_DEVICE_CODE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const GRANT_TYPE_AUTHORIZATION_CODE: &str = "authorization_code";
+const GRANT_TYPE_CLIENT_CREDENTIALS: &str = "client_credentials";
+const GRANT_TYPE_REFRESH_TOKEN: &str = "refresh_token";
+const RESPONSE_TYPE_CODE: &str = "code";
+const CODE_CHALLENGE_METHOD_S256: &str = "S256";
+const ACCEPT_JSON: &str = "application/json";
+
+// デバイス認可フロー
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+const SLOW_DOWN_INCREMENT_SECS: u64 = 5;
+const ERROR_CODE_AUTHORIZATION_PENDING: &str = "authorization_pending";
+const ERROR_CODE_SLOW_DOWN: &str = "slow_down";
+
+// 認可コードフロー（PKCE）
+const CODE_VERIFIER_BYTES: usize = 32;
+const STATE_BYTES: usize = 16;
+const CALLBACK_PATH: &str = "/callback";
+const REDIRECT_URI_TEMPLATE: &str = "http://127.0.0.1:{1}{2}";
+const CALLBACK_BASE_URL: &str = "http://127.0.0.1";
+const CALLBACK_SUCCESS_RESPONSE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nConnection: close\r\n\r\nAuthorization complete. You can close this window.\n";
+const CALLBACK_ERROR_RESPONSE: &str = "HTTP/1.1 400 Bad Request\r\nContent-Type: text/plain; charset=utf-8\r\nConnection: close\r\n\r\nAuthorization failed. Return to the terminal for details.\n";
+const CALLBACK_NOT_FOUND_RESPONSE: &str = "HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n";
+
+// クライアントクレデンシャルフロー
+const TOKEN_EXPIRY_MARGIN_SECS: u64 = 30;
+
+// エラーメッセージ
+const ERROR_MISSING_DEVICE_AUTHORIZATION_URL: &str =
+    "device_authorization_url is required in the [oauth2] section of the preset";
+const ERROR_DEVICE_AUTHORIZATION: &str = "Device authorization request failed: {}";
+const ERROR_TOKEN_REQUEST: &str = "Token request failed: {}";
+const ERROR_DEVICE_CODE_EXPIRED: &str = "Device code expired before authorization was completed";
+const ERROR_MISSING_ACCESS_TOKEN: &str = "Token response did not contain an access_token";
+const ERROR_MISSING_TOKEN_PATH: &str = "Could not determine the token store path (HOME is not set)";
+const ERROR_MISSING_AUTHORIZATION_URL: &str =
+    "authorization_url is required in the [oauth2] section of the preset";
+const ERROR_AUTHORIZATION_DENIED: &str = "Authorization failed: {}";
+const ERROR_STATE_MISMATCH: &str = "Authorization callback state did not match the request";
+const ERROR_MISSING_CODE: &str = "Authorization callback did not contain a code";
+const ERROR_RANDOM_GENERATION: &str = "Failed to generate random bytes for PKCE";
+const ERROR_MISSING_CLIENT_SECRET: &str =
+    "client_secret is required in the [oauth2] section of the preset for grant_type = \"client_credentials\"";
+const ERROR_UNSUPPORTED_GRANT_TYPE: &str =
+    "Unsupported grant_type '{}' in the [oauth2] section. Use \"client_credentials\", or omit it and run `rs-w3r auth device` / `rs-w3r auth login`.";
+const ERROR_FORCE_REFRESH_UNAVAILABLE: &str =
+    "--force-token-refresh needs a stored refresh token or grant_type = \"client_credentials\" (run `rs-w3r auth device` or `rs-w3r auth login` again)";
+const ERROR_REFRESH_FAILED: &str =
+    "Failed to refresh the OAuth2 token: {} (run `rs-w3r auth device` or `rs-w3r auth login` again)";
+
+// 表示メッセージ
+const DEVICE_VERIFICATION_MSG: &str = "Open {1} and enter the code: {2}";
+const DEVICE_VERIFICATION_COMPLETE_MSG: &str = "Or open this URL directly: {}";
+const DEVICE_WAITING_MSG: &str = "Waiting for authorization...";
+const TOKEN_SAVED_MSG: &str = "Token saved to {}";
+const BROWSER_OPEN_MSG: &str = "Opening the browser for authorization. If it does not open, visit:\n{}";
+const CALLBACK_WAITING_MSG: &str = "Waiting for the authorization callback on {}...";
+
+/// OAuth2の設定（プリセットの[oauth2]セクション）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuth2Config {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub device_authorization_url: Option<String>,
+    pub authorization_url: Option<String>,
+    pub redirect_port: Option<u16>,
+    pub scopes: Option<Vec<String>>,
+    /// "client_credentials" の場合はリクエスト時にトークンを自動で取得
+    pub grant_type: Option<String>,
+}
+
+/// 取得済みトークンの一覧（token_urlとclient_idの組ごと）
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TokenStore {
+    tokens: HashMap<String, StoredToken>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+impl TokenStore {
+    /// ファイルから読み込む（存在しない・壊れている場合は空のストア）
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// ファイルに保存（Unixでは所有者のみ読み書き可能にする）
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // 所有者のみ読み書きできる一時ファイルに書き込んでから置き換える（書き込み中も他者に読ませない）
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(format!("{}{}", TEMP_FILE_SUFFIX, std::process::id()));
+        let temp_path = PathBuf::from(temp_path);
+        let _ = fs::remove_file(&temp_path);
+        let contents = serde_json::to_string_pretty(self)?;
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(TOKEN_FILE_MODE);
+        }
+        let result = options
+            .open(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(contents.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temp_path, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+
+        Ok(result?)
+    }
+
+    /// 有効期限内のアクセストークンを取得
+    pub fn access_token(&self, oauth2: &OAuth2Config) -> Option<&str> {
+        self.access_token_valid_for(oauth2, 0)
+    }
+
+    /// 指定秒数より先まで有効なアクセストークンを取得
+    fn access_token_valid_for(&self, oauth2: &OAuth2Config, margin_secs: u64) -> Option<&str> {
+        let now = unix_now().saturating_add(margin_secs);
+        self.tokens
+            .get(&token_key(oauth2))
+            .filter(|token| token.expires_at.is_none_or(|expires_at| expires_at > now))
+            .map(|token| token.access_token.as_str())
+    }
+
+    /// 保存済みのリフレッシュトークンを取得
+    fn refresh_token(&self, oauth2: &OAuth2Config) -> Option<&str> {
+        self.tokens
+            .get(&token_key(oauth2))
+            .and_then(|token| token.refresh_token.as_deref())
+    }
+
+    /// 指定のアクセストークンが保存されている場合は期限切れにする（変更した場合は true）
+    fn expire(&mut self, oauth2: &OAuth2Config, access_token: &str) -> bool {
+        match self.tokens.get_mut(&token_key(oauth2)) {
+            Some(token) if token.access_token == access_token => {
+                token.expires_at = Some(0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// トークンレスポンスの内容でストアを更新
+    fn insert(&mut self, oauth2: &OAuth2Config, response: TokenResponse) -> Result<(), Box<dyn Error>> {
+        let access_token = response.access_token.ok_or(ERROR_MISSING_ACCESS_TOKEN)?;
+        self.tokens.insert(
+            token_key(oauth2),
+            StoredToken {
+                access_token,
+                refresh_token: response.refresh_token,
+                expires_at: response
+                    .expires_in
+                    .map(|seconds| unix_now().saturating_add(seconds)),
+            },
+        );
+        Ok(())
+    }
+}
+
+/// トークンストアの既定の保存先
+pub fn default_token_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(DEFAULT_TOKEN_PATH))
+}
+
+/// 保存済みの有効なアクセストークンを取得
+pub fn stored_access_token(oauth2: &OAuth2Config) -> Option<String> {
+    let path = default_token_path()?;
+    TokenStore::load(&path)
+        .access_token(oauth2)
+        .map(|token| token.to_string())
+}
+
+/// リクエストに付与するアクセストークンを取得
+///
+/// 保存済みのトークンが期限切れ間近であれば、リフレッシュトークンで更新してストアに保存する。
+/// grant_type = "client_credentials" の場合は、更新できなければトークンエンドポイントから
+/// 取得し直す。それ以外は `auth device`・`auth login` で保存したトークンを使う。
+/// トークンエンドポイントにはリクエストと同じプロキシ・クライアント証明書で接続する。
+/// force_refresh（--force-token-refresh）の場合は、プロセスで最初の1回だけ有効期限にかかわらず
+/// 更新・再取得する（URLリストや並列実行でリクエストごとに取得し直さないように）。
+pub fn resolve_access_token(
+    oauth2: &OAuth2Config,
+    config: &Config,
+    force_refresh: bool,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let client_credentials = match oauth2.grant_type.as_deref() {
+        None => false,
+        Some(GRANT_TYPE_CLIENT_CREDENTIALS) => true,
+        Some(grant_type) => return Err(ERROR_UNSUPPORTED_GRANT_TYPE.replace("{}", grant_type).into()),
+    };
+
+    static FORCED_REFRESH: AtomicBool = AtomicBool::new(false);
+    let _guard = lock_token_store();
+    let forced = force_refresh && !FORCED_REFRESH.swap(true, Ordering::SeqCst);
+    let path = default_token_path();
+    let mut store = path.as_deref().map(TokenStore::load).unwrap_or_default();
+    if let Some(access_token) = store
+        .access_token_valid_for(oauth2, TOKEN_EXPIRY_MARGIN_SECS)
+        .filter(|_| !forced)
+    {
+        return Ok(Some(access_token.to_string()));
+    }
+
+    let client = create_helper_client(config)?;
+    let token_response = match store.refresh_token(oauth2) {
+        Some(refresh_token) => match refresh_access_token(&client, oauth2, refresh_token) {
+            Ok(token_response) => token_response,
+            Err(_) if client_credentials => client_credentials_token(&client, oauth2)?,
+            // 更新に失敗しても、期限切れ間近のトークンがまだ有効であればそのまま使う
+            Err(e) => match store.access_token(oauth2).filter(|_| !forced) {
+                Some(access_token) => return Ok(Some(access_token.to_string())),
+                None => return Err(ERROR_REFRESH_FAILED.replace("{}", &e.to_string()).into()),
+            },
+        },
+        None if client_credentials => client_credentials_token(&client, oauth2)?,
+        None if forced => return Err(ERROR_FORCE_REFRESH_UNAVAILABLE.into()),
+        None => return Ok(store.access_token(oauth2).map(str::to_string)),
+    };
+    let access_token = token_response
+        .access_token
+        .clone()
+        .ok_or(ERROR_MISSING_ACCESS_TOKEN)?;
+
+    // HOMEがない場合は保存せず、取得したトークンをそのまま使う
+    if let Some(path) = &path {
+        store.insert(oauth2, token_response)?;
+        store.save(path)?;
+    }
+    Ok(Some(access_token))
+}
+
+/// サーバーに拒否された（401）アクセストークンを期限切れとして保存
+///
+/// リフレッシュトークンは残すため、次の `resolve_access_token` で更新（client_credentials では
+/// 再取得）される。並列実行で別のリクエストが更新済みの場合は何もしない。
+pub fn invalidate_access_token(oauth2: &OAuth2Config, rejected_token: &str) -> Result<(), Box<dyn Error>> {
+    let _guard = lock_token_store();
+    let Some(path) = default_token_path() else {
+        return Ok(());
+    };
+    let mut store = TokenStore::load(&path);
+    if store.expire(oauth2, rejected_token) {
+        store.save(&path)?;
+    }
+    Ok(())
+}
+
+/// トークンストアの確認から保存までを排他する（--parallel・負荷テストで同時に取得しないように）
+fn lock_token_store() -> MutexGuard<'static, ()> {
+    static STORE_LOCK: Mutex<()> = Mutex::new(());
+    STORE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// リフレッシュトークン（RFC 6749 6）でアクセストークンを更新
+fn refresh_access_token(
+    client: &Client,
+    oauth2: &OAuth2Config,
+    refresh_token: &str,
+) -> Result<TokenResponse, Box<dyn Error>> {
+    let mut params = vec![
+        (PARAM_GRANT_TYPE, GRANT_TYPE_REFRESH_TOKEN.to_string()),
+        (PARAM_REFRESH_TOKEN, refresh_token.to_string()),
+        (PARAM_CLIENT_ID, oauth2.client_id.clone()),
+    ];
+    if let Some(client_secret) = &oauth2.client_secret {
+        params.push((PARAM_CLIENT_SECRET, client_secret.clone()));
+    }
+
+    let mut token_response = request_token(client, &oauth2.token_url, &params)?;
+    if let Some(error_code) = &token_response.error {
+        return Err(token_error(error_code, &token_response));
+    }
+    // 新しいリフレッシュトークンが発行されない場合は同じものを使い続ける
+    token_response
+        .refresh_token
+        .get_or_insert_with(|| refresh_token.to_string());
+    Ok(token_response)
+}
+
+/// クライアントクレデンシャルフロー（RFC 6749 4.4）でトークンを取得
+fn client_credentials_token(client: &Client, oauth2: &OAuth2Config) -> Result<TokenResponse, Box<dyn Error>> {
+    let client_secret = oauth2
+        .client_secret
+        .clone()
+        .ok_or(ERROR_MISSING_CLIENT_SECRET)?;
+    let mut params = vec![
+        (PARAM_GRANT_TYPE, GRANT_TYPE_CLIENT_CREDENTIALS.to_string()),
+        (PARAM_CLIENT_ID, oauth2.client_id.clone()),
+        (PARAM_CLIENT_SECRET, client_secret),
+    ];
+    if let Some(scopes) = &oauth2.scopes {
+        params.push((PARAM_SCOPE, scopes.join(" ")));
+    }
+
+    let token_response = request_token(client, &oauth2.token_url, &params)?;
+    if let Some(error_code) = &token_response.error {
+        return Err(token_error(error_code, &token_response));
+    }
+    Ok(token_response)
+}
+
+/// デバイス認可フロー（RFC 8628）でトークンを取得して保存
+pub fn authorize_device(oauth2: &OAuth2Config, config: &Config) -> Result<(), Box<dyn Error>> {
+    let device_authorization_url = oauth2
+        .device_authorization_url
+        .as_deref()
+        .ok_or(ERROR_MISSING_DEVICE_AUTHORIZATION_URL)?;
+    let client = create_helper_client(config)?;
+
+    let mut params = vec![(PARAM_CLIENT_ID, oauth2.client_id.clone())];
+    if let Some(scopes) = &oauth2.scopes {
+        params.push((PARAM_SCOPE, scopes.join(" ")));
+    }
+
+    let response = client
+        .post(device_authorization_url)
+        .header(ACCEPT, ACCEPT_JSON)
+        .form(&params)
+        .send()?;
+    if !response.status().is_success() {
+        let message = ERROR_DEVICE_AUTHORIZATION.replace("{}", &response.text()?);
+        return Err(message.into());
+    }
+    let authorization: DeviceAuthorizationResponse = response.json()?;
+
+    println!(
+        "{}",
+        DEVICE_VERIFICATION_MSG
+            .replace("{1}", &authorization.verification_uri)
+            .replace("{2}", &authorization.user_code)
+    );
+    if let Some(verification_uri_complete) = &authorization.verification_uri_complete {
+        println!(
+            "{}",
+            DEVICE_VERIFICATION_COMPLETE_MSG.replace("{}", verification_uri_complete)
+        );
+    }
+    println!("{}", DEVICE_WAITING_MSG);
+
+    let token_response = poll_device_token(&client, oauth2, &authorization)?;
+    save_token(oauth2, token_response)
+}
+
+/// 認可が完了するまでトークンエンドポイントをポーリング
+fn poll_device_token(
+    client: &Client,
+    oauth2: &OAuth2Config,
+    authorization: &DeviceAuthorizationResponse,
+) -> Result<TokenResponse, Box<dyn Error>> {
+    let deadline = Instant::now() + Duration::from_secs(authorization.expires_in);
+    let mut interval = authorization.interval.unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+
+    let mut params = vec![
+        (PARAM_GRANT_TYPE, GRANT_TYPE_DEVICE_CODE.to_string()),
+        (PARAM_DEVICE_CODE, authorization.device_code.clone()),
+        (PARAM_CLIENT_ID, oauth2.client_id.clone()),
+    ];
+    if let Some(client_secret) = &oauth2.client_secret {
+        params.push((PARAM_CLIENT_SECRET, client_secret.clone()));
+    }
+
+    loop {
+        thread::sleep(Duration::from_secs(interval));
+        if Instant::now() >= deadline {
+            return Err(ERROR_DEVICE_CODE_EXPIRED.into());
+        }
+
+        let token_response = request_token(client, &oauth2.token_url, &params)?;
+
+        match token_response.error.as_deref() {
+            None => return Ok(token_response),
+            Some(ERROR_CODE_AUTHORIZATION_PENDING) => {}
+            Some(ERROR_CODE_SLOW_DOWN) => interval += SLOW_DOWN_INCREMENT_SECS,
+            Some(error_code) => return Err(token_error(error_code, &token_response)),
+        }
+    }
+}
+
+/// 取得したトークンをストアに保存
+fn save_token(oauth2: &OAuth2Config, token_response: TokenResponse) -> Result<(), Box<dyn Error>> {
+    let path = default_token_path().ok_or(ERROR_MISSING_TOKEN_PATH)?;
+    let mut store = TokenStore::load(&path);
+    store.insert(oauth2, token_response)?;
+    store.save(&path)?;
+
+    println!("{}", TOKEN_SAVED_MSG.replace("{}", &path.display().to_string()));
+    Ok(())
+}
+
+/// 認可コードフロー（PKCE、ローカルのコールバック受信）でトークンを取得して保存
+pub fn authorize_login(oauth2: &OAuth2Config, config: &Config) -> Result<(), Box<dyn Error>> {
+    let authorization_url = oauth2
+        .authorization_url
+        .as_deref()
+        .ok_or(ERROR_MISSING_AUTHORIZATION_URL)?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, oauth2.redirect_port.unwrap_or(0)))?;
+    let redirect_uri = REDIRECT_URI_TEMPLATE
+        .replace("{1}", &listener.local_addr()?.port().to_string())
+        .replace("{2}", CALLBACK_PATH);
+
+    let code_verifier = random_url_safe_string(CODE_VERIFIER_BYTES)?;
+    let code_challenge = URL_SAFE_NO_PAD.encode(digest(&SHA256, code_verifier.as_bytes()));
+    let state = random_url_safe_string(STATE_BYTES)?;
+
+    let mut url = Url::parse(authorization_url)?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair(PARAM_RESPONSE_TYPE, RESPONSE_TYPE_CODE)
+            .append_pair(PARAM_CLIENT_ID, &oauth2.client_id)
+            .append_pair(PARAM_REDIRECT_URI, &redirect_uri)
+            .append_pair(PARAM_STATE, &state)
+            .append_pair(PARAM_CODE_CHALLENGE, &code_challenge)
+            .append_pair(PARAM_CODE_CHALLENGE_METHOD, CODE_CHALLENGE_METHOD_S256);
+        if let Some(scopes) = &oauth2.scopes {
+            query.append_pair(PARAM_SCOPE, &scopes.join(" "));
+        }
+    }
+
+    println!("{}", BROWSER_OPEN_MSG.replace("{}", url.as_str()));
+    let _ = open::that(url.as_str());
+    println!("{}", CALLBACK_WAITING_MSG.replace("{}", &redirect_uri));
+
+    let code = wait_for_authorization_code(&listener, &state)?;
+
+    let client = create_helper_client(config)?;
+    let mut params = vec![
+        (PARAM_GRANT_TYPE, GRANT_TYPE_AUTHORIZATION_CODE.to_string()),
+        (PARAM_CODE, code),
+        (PARAM_REDIRECT_URI, redirect_uri),
+        (PARAM_CLIENT_ID, oauth2.client_id.clone()),
+        (PARAM_CODE_VERIFIER, code_verifier),
+    ];
+    if let Some(client_secret) = &oauth2.client_secret {
+        params.push((PARAM_CLIENT_SECRET, client_secret.clone()));
+    }
+
+    let token_response = request_token(&client, &oauth2.token_url, &params)?;
+    if let Some(error_code) = &token_response.error {
+        return Err(token_error(error_code, &token_response));
+    }
+    save_token(oauth2, token_response)
+}
+
+/// コールバックへのリダイレクトを待ち受けて認可コードを取得
+fn wait_for_authorization_code(listener: &TcpListener, state: &str) -> Result<String, Box<dyn Error>> {
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        let Some(callback_url) = read_callback_url(&stream)? else {
+            stream.write_all(CALLBACK_NOT_FOUND_RESPONSE.as_bytes())?;
+            continue;
+        };
+
+        let params: HashMap<String, String> = callback_url.query_pairs().into_owned().collect();
+        let result = if let Some(error_code) = params.get(PARAM_ERROR) {
+            Err(ERROR_AUTHORIZATION_DENIED.replace("{}", error_code).into())
+        } else if params.get(PARAM_STATE).map(String::as_str) != Some(state) {
+            Err(ERROR_STATE_MISMATCH.into())
+        } else {
+            params
+                .get(PARAM_CODE)
+                .cloned()
+                .ok_or_else(|| ERROR_MISSING_CODE.into())
+        };
+
+        let response = if result.is_ok() {
+            CALLBACK_SUCCESS_RESPONSE
+        } else {
+            CALLBACK_ERROR_RESPONSE
+        };
+        stream.write_all(response.as_bytes())?;
+        return result;
+    }
+
+    Err(ERROR_MISSING_CODE.into())
+}
+
+/// リクエスト行からコールバックURLを取得（コールバック以外のパスはNone）
+fn read_callback_url(stream: &TcpStream) -> Result<Option<Url>, Box<dyn Error>> {
+    let mut request_line = String::new();
+    BufReader::new(stream).read_line(&mut request_line)?;
+
+    let Some(target) = request_line.split_whitespace().nth(1) else {
+        return Ok(None);
+    };
+    let url = Url::parse(CALLBACK_BASE_URL)?.join(target)?;
+
+    Ok((url.path() == CALLBACK_PATH).then_some(url))
+}
+
+/// URLセーフなランダム文字列を生成
+fn random_url_safe_string(byte_length: usize) -> Result<String, Box<dyn Error>> {
+    let mut bytes = vec![0u8; byte_length];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| ERROR_RANDOM_GENERATION)?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// トークンエンドポイントにリクエスト
+fn request_token(
+    client: &Client,
+    token_url: &str,
+    params: &[(&str, String)],
+) -> Result<TokenResponse, Box<dyn Error>> {
+    Ok(client
+        .post(token_url)
+        .header(ACCEPT, ACCEPT_JSON)
+        .form(params)
+        .send()?
+        .json()?)
+}
+
+/// トークンエンドポイントのエラー応答をエラーに変換
+fn token_error(error_code: &str, token_response: &TokenResponse) -> Box<dyn Error> {
+    let detail = match &token_response.error_description {
+        Some(description) => format!("{} ({})", error_code, description),
+        None => error_code.to_string(),
+    };
+    ERROR_TOKEN_REQUEST.replace("{}", &detail).into()
+}
+
+/// ストア内のキー（token_urlとclient_idの組）
+fn token_key(oauth2: &OAuth2Config) -> String {
+    format!("{} {}", oauth2.token_url, oauth2.client_id)
+}
@@ -0,0 +1,45 @@
+use crate::client::{Config, ResponseInfo, TimingInfo};
+use rustls::pki_types::CertificateDer;
+use std::time::Duration;
+
+/// リトライの理由
+#[derive(Debug)]
+pub enum RetryReason<'a> {
+    /// リトライ対象のステータスコード（5xx、429、408）
+    Status(reqwest::StatusCode),
+    /// 通信エラー
+    Error(&'a reqwest::Error),
+    /// ボディが --retry-if-body の条件に一致したレスポンスのステータスコード
+    Body(reqwest::StatusCode),
+    /// OAuth2のアクセストークンが拒否された（401）ため、取得し直したトークンで再送
+    Unauthorized,
+}
+
+/// リクエストの試行・リトライ・レスポンスの受信を通知する
+///
+/// 詳細出力（-v）とタイミング情報（--timing）の表示もこのトレイトで実装されています。
+/// 必要なメソッドだけを実装し、`execute_request_with` に渡します。
+/// 並列送信時、on_attempt・on_retry・on_body_chunk はワーカースレッドから呼び出されます。
+pub trait ResponseObserver: Send + Sync {
+    /// 各試行の送信前（attemptは1から）
+    fn on_attempt(&self, _config: &Config, _attempt: u32) {}
+
+    /// リトライの待機前
+    fn on_retry(&self, _config: &Config, _reason: &RetryReason, _delay: Duration) {}
+
+    /// レスポンスボディのチャンクを読み込むたび
+    fn on_body_chunk(&self, _config: &Config, _chunk: &[u8]) {}
+
+    /// TLSのハンドシェイクで提示された証明書チェーンの受信時（リーフ証明書が先頭、レスポンスの処理前）
+    fn on_certificates(&self, _config: &Config, _chain: &[CertificateDer<'static>]) {}
+
+    /// レスポンスの処理時（ボディの読み込み後、表示・保存の前）
+    fn on_response(
+        &self,
+        _config: &Config,
+        _response: &ResponseInfo,
+        _timing: &TimingInfo,
+        _body: &str,
+    ) {
+    }
+}
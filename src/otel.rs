@@ -0,0 +1,199 @@
+use crate::client::{Config, PhaseTimings, ResponseInfo, TimingInfo};
+use crate::trace_context::{random_hex_id, trace_ids};
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// OTLP/HTTP
+const TRACES_PATH: &str = "/v1/traces";
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+const SERVICE_NAME: &str = "rs-w3r";
+const SCOPE_NAME: &str = "rs-w3r";
+const TRACE_ID_BYTES: usize = 16;
+const SPAN_ID_BYTES: usize = 8;
+const SPAN_KIND_INTERNAL: u8 = 1;
+const SPAN_KIND_CLIENT: u8 = 3;
+const STATUS_CODE_OK: u8 = 1;
+const STATUS_CODE_ERROR: u8 = 2;
+
+// HTTPステータスコード
+const CLIENT_ERROR_START: u16 = 400;
+
+// 試行のスパン名
+const ATTEMPT_SPAN_NAME: &str = "attempt {}";
+
+// 表示メッセージ
+const EXPORT_FAILED_MSG: &str = "* Failed to export span to {1}: {2}";
+
+/// 1リクエスト分のスパンをOTLP/HTTP（JSON）で送信（失敗しても警告のみ）
+///
+/// スパンのIDは送信したtraceparentヘッダーのトレースID・親IDを使用し、
+/// サーバー側のスパンと同じトレースにまとめる。
+pub fn export_request_span(
+    endpoint: &str,
+    config: &Config,
+    traceparent: Option<&str>,
+    started: SystemTime,
+    result: &Result<(ResponseInfo, String, TimingInfo), Box<dyn Error>>,
+) {
+    if let Err(e) = send_span(endpoint, config, traceparent, started, result) {
+        eprintln!(
+            "{}",
+            EXPORT_FAILED_MSG
+                .replace("{1}", endpoint)
+                .replace("{2}", &e.to_string())
+        );
+    }
+}
+
+fn send_span(
+    endpoint: &str,
+    config: &Config,
+    traceparent: Option<&str>,
+    started: SystemTime,
+    result: &Result<(ResponseInfo, String, TimingInfo), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let (trace_id, span_id) = match traceparent.and_then(trace_ids) {
+        Some((trace_id, span_id)) => (trace_id.to_string(), span_id.to_string()),
+        None => (
+            random_hex_id(TRACE_ID_BYTES)?,
+            random_hex_id(SPAN_ID_BYTES)?,
+        ),
+    };
+    let mut attributes = vec![
+        attribute(
+            "http.request.method",
+            json!({ "stringValue": config.method }),
+        ),
+        attribute("url.full", json!({ "stringValue": config.url })),
+    ];
+
+    let spans = match result {
+        Ok((response, _, timing)) => {
+            let start = SystemTime::now()
+                .checked_sub(timing.total_time)
+                .unwrap_or(started);
+            let status = response.status().as_u16();
+            attributes.push(attribute(
+                "http.response.status_code",
+                json!({ "intValue": status.to_string() }),
+            ));
+
+            let mut spans = vec![json!({
+                "traceId": trace_id,
+                "spanId": span_id,
+                "name": config.method,
+                "kind": SPAN_KIND_CLIENT,
+                "startTimeUnixNano": unix_nanos(start),
+                "endTimeUnixNano": unix_nanos(start + timing.total_time),
+                "attributes": attributes,
+                "events": timing.phases.as_ref().map(|phases| phase_events(start, timing, phases)).unwrap_or_default(),
+                "status": { "code": if status < CLIENT_ERROR_START { STATUS_CODE_OK } else { STATUS_CODE_ERROR } },
+            })];
+            if timing.attempts.len() > 1 {
+                spans.extend(attempt_spans(&trace_id, &span_id, start, timing)?);
+            }
+            spans
+        }
+        Err(error) => vec![json!({
+            "traceId": trace_id,
+            "spanId": span_id,
+            "name": config.method,
+            "kind": SPAN_KIND_CLIENT,
+            "startTimeUnixNano": unix_nanos(started),
+            "endTimeUnixNano": unix_nanos(SystemTime::now()),
+            "attributes": attributes,
+            "status": { "code": STATUS_CODE_ERROR, "message": error.to_string() },
+        })],
+    };
+
+    let payload = json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attribute("service.name", json!({ "stringValue": SERVICE_NAME }))],
+            },
+            "scopeSpans": [{
+                "scope": { "name": SCOPE_NAME, "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    });
+
+    let url = format!("{}{}", endpoint.trim_end_matches('/'), TRACES_PATH);
+    Client::builder()
+        .timeout(EXPORT_TIMEOUT)
+        .build()?
+        .post(url)
+        .json(&payload)
+        .send()?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// 試行ごとの子スパン（リトライの待機時間を挟んで順に並べる）
+fn attempt_spans(
+    trace_id: &str,
+    parent_span_id: &str,
+    start: SystemTime,
+    timing: &TimingInfo,
+) -> Result<Vec<Value>, Box<dyn Error>> {
+    let mut cursor = start;
+    let mut spans = Vec::new();
+
+    for attempt in &timing.attempts {
+        let end = cursor + attempt.duration;
+        spans.push(json!({
+            "traceId": trace_id,
+            "spanId": random_hex_id(SPAN_ID_BYTES)?,
+            "parentSpanId": parent_span_id,
+            "name": ATTEMPT_SPAN_NAME.replace("{}", &attempt.number.to_string()),
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": unix_nanos(cursor),
+            "endTimeUnixNano": unix_nanos(end),
+            "attributes": [attribute("rs_w3r.attempt.outcome", json!({ "stringValue": attempt.outcome }))],
+        }));
+        cursor = end + attempt.delay.unwrap_or_default();
+    }
+
+    Ok(spans)
+}
+
+/// 最後の試行の段階（名前解決・接続・TLS・待機・ダウンロード）の完了をイベントとして記録
+fn phase_events(start: SystemTime, timing: &TimingInfo, phases: &PhaseTimings) -> Vec<Value> {
+    let last_attempt_start = start + timing.total_time
+        - (phases.dns + phases.connect + phases.tls + phases.wait + phases.download);
+    let mut elapsed = Duration::ZERO;
+
+    [
+        ("dns", phases.dns),
+        ("connect", phases.connect),
+        ("tls", phases.tls),
+        ("wait", phases.wait),
+        ("download", phases.download),
+    ]
+    .into_iter()
+    .filter(|(_, duration)| !duration.is_zero())
+    .map(|(name, duration)| {
+        elapsed += duration;
+        json!({
+            "timeUnixNano": unix_nanos(last_attempt_start + elapsed),
+            "name": name,
+            "attributes": [attribute("duration_ms", json!({ "doubleValue": duration.as_secs_f64() * 1000.0 }))],
+        })
+    })
+    .collect()
+}
+
+/// OTLPの属性（キーと型付きの値）
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+/// UNIX時刻のナノ秒（OTLP/JSONでは64ビット整数を文字列で表す）
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default()
+        .to_string()
+}
@@ -0,0 +1,150 @@
+use crate::output_path::compact_timestamp;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ローテーションの指定
+const ROTATE_SIZE_PREFIX: &str = "size:";
+const ROTATE_DAILY: &str = "daily";
+const SIZE_UNITS: [(char, u64); 3] = [('K', 1024), ('M', 1024 * 1024), ('G', 1024 * 1024 * 1024)];
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// エラーメッセージ
+const ERROR_INVALID_OUTPUT_ROTATE: &str =
+    "Invalid --output-rotate '{}'. Use size:<N>[K|M|G] (e.g. size:10M) or daily.";
+
+/// 出力ファイルのローテーション条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputRotation {
+    /// 書き込み後のサイズが指定バイト数を超える場合
+    Size(u64),
+    /// 最後に書き込んだ日（UTC）が変わった場合
+    Daily,
+}
+
+/// --output-rotate の値を解析（size:10M、daily）
+pub fn parse_output_rotate(value: &str) -> Result<OutputRotation, Box<dyn Error>> {
+    let invalid = || ERROR_INVALID_OUTPUT_ROTATE.replace("{}", value);
+    let value = value.trim();
+
+    if value.eq_ignore_ascii_case(ROTATE_DAILY) {
+        return Ok(OutputRotation::Daily);
+    }
+
+    let size = value.strip_prefix(ROTATE_SIZE_PREFIX).ok_or_else(invalid)?;
+    parse_byte_size(size)
+        .map(OutputRotation::Size)
+        .ok_or_else(|| invalid().into())
+}
+
+/// 単位付きのバイト数を解析（1024、512K、50M、2G）。0や不正な値は None
+pub(crate) fn parse_byte_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, multiplier) = match size.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some(unit) if unit.is_ascii_alphabetic() => {
+            let (_, multiplier) = SIZE_UNITS.iter().find(|(symbol, _)| *symbol == unit)?;
+            (&size[..size.len() - 1], *multiplier)
+        }
+        _ => (size, 1),
+    };
+
+    match number.parse::<u64>() {
+        Ok(number) if number > 0 => Some(number.saturating_mul(multiplier)),
+        _ => None,
+    }
+}
+
+/// レスポンスを出力ファイルに書き込む（追記・ローテーションに対応）
+///
+/// ローテーションを指定した場合は常に追記し、条件を満たした既存のファイルを
+/// 最終更新日時付きのファイル名に変更してから新しいファイルに書き込む。
+/// 追記するレスポンスは改行で区切る。
+pub fn write_output_file(
+    file_path: &str,
+    data: &[u8],
+    append: bool,
+    rotation: Option<OutputRotation>,
+) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(file_path);
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    // 追記時はレスポンスごとに改行で区切る
+    let append = append || rotation.is_some();
+    let separator: &[u8] = if append && !data.ends_with(b"\n") {
+        b"\n"
+    } else {
+        b""
+    };
+
+    if let Some(rotation) = rotation {
+        rotate_if_needed(path, (data.len() + separator.len()) as u64, rotation)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?;
+    file.write_all(data)?;
+    file.write_all(separator)?;
+    Ok(())
+}
+
+/// 既存のファイルがローテーションの条件を満たしていれば別名に変更
+fn rotate_if_needed(
+    path: &Path,
+    incoming: u64,
+    rotation: OutputRotation,
+) -> Result<(), Box<dyn Error>> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    let modified = metadata.modified()?;
+
+    let should_rotate = match rotation {
+        OutputRotation::Size(limit) => {
+            metadata.len() > 0 && metadata.len().saturating_add(incoming) > limit
+        }
+        OutputRotation::Daily => utc_day(modified) != utc_day(SystemTime::now()),
+    };
+    if should_rotate {
+        fs::rename(path, rotated_path(path, modified))?;
+    }
+    Ok(())
+}
+
+/// ローテーション後のファイル名（name-YYYYMMDDTHHMMSSZ.ext、重複する場合は連番を付与）
+fn rotated_path(path: &Path, modified: SystemTime) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let timestamp = compact_timestamp(modified);
+
+    let mut candidate = path.with_file_name(format!("{}-{}{}", stem, timestamp, extension));
+    let mut sequence = 1;
+    while candidate.exists() {
+        candidate =
+            path.with_file_name(format!("{}-{}-{}{}", stem, timestamp, sequence, extension));
+        sequence += 1;
+    }
+    candidate
+}
+
+/// UNIX時刻からのUTCの日数
+fn utc_day(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
@@ -0,0 +1,144 @@
+use crate::util::amz_date;
+use reqwest::Url;
+use std::path::{Path, MAIN_SEPARATOR};
+use std::time::SystemTime;
+
+// プレースホルダー
+const PLACEHOLDER_HOST: &str = "{host}";
+const PLACEHOLDER_PATH_SLUG: &str = "{path_slug}";
+const PLACEHOLDER_INDEX: &str = "{index}";
+const PLACEHOLDER_TIMESTAMP: &str = "{timestamp}";
+const PLACEHOLDER_STATUS: &str = "{status}";
+
+// パスのスラッグ
+const SLUG_SEPARATOR: char = '_';
+const ROOT_PATH_SLUG: &str = "index";
+
+// Content-Type からの拡張子
+const DEFAULT_FILE_EXTENSION: &str = "txt";
+const BINARY_FILE_EXTENSION: &str = "bin";
+const BINARY_MIME_PREFIXES: [&str; 5] = ["image/", "audio/", "video/", "font/", "application/"];
+const TEXT_MIME_SUFFIXES: [&str; 5] = ["json", "xml", "javascript", "yaml", "x-www-form-urlencoded"];
+
+/// 出力ファイル名のURL・連番・日時のプレースホルダーを置換（{status}はレスポンス受信後に置換）
+pub fn render_output_path(template: &str, url: &str, index: usize, time: SystemTime) -> String {
+    let parsed_url = Url::parse(url).ok();
+    let host = parsed_url
+        .as_ref()
+        .and_then(|parsed_url| parsed_url.host_str())
+        .unwrap_or_default();
+    let path = parsed_url
+        .as_ref()
+        .map(|parsed_url| parsed_url.path())
+        .unwrap_or_default();
+
+    template
+        .replace(PLACEHOLDER_HOST, host)
+        .replace(PLACEHOLDER_PATH_SLUG, &path_slug(path))
+        .replace(PLACEHOLDER_INDEX, &index.to_string())
+        .replace(PLACEHOLDER_TIMESTAMP, &compact_timestamp(time))
+}
+
+/// 出力ファイル名の {status} をステータスコードに置換
+pub fn apply_status_placeholder(path: &str, status: u16) -> String {
+    path.replace(PLACEHOLDER_STATUS, &status.to_string())
+}
+
+/// 保存先のディレクトリとして指定されたか（末尾が区切り文字、または既存のディレクトリ）
+pub fn is_output_directory(output: &str) -> bool {
+    output.ends_with('/') || output.ends_with(MAIN_SEPARATOR) || Path::new(output).is_dir()
+}
+
+/// URLの最後のパス要素から保存するファイル名を決める（-O・--output にディレクトリを指定した場合）
+///
+/// 拡張子がなければ Content-Type から付け、パスが空の場合は index にする。
+pub fn remote_file_name(url: &str, content_type: Option<&str>) -> String {
+    let name = Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back().map(str::to_string))
+        })
+        .filter(|name| !name.is_empty() && name != "." && name != "..")
+        .unwrap_or_else(|| ROOT_PATH_SLUG.to_string());
+
+    if Path::new(&name).extension().is_some() {
+        name
+    } else {
+        format!("{}.{}", name, extension_for_content_type(content_type))
+    }
+}
+
+/// Content-Typeからファイル拡張子を決定
+pub fn extension_for_content_type(content_type: Option<&str>) -> &'static str {
+    let mime = mime_type(content_type);
+
+    match mime.as_str() {
+        "text/html" | "application/xhtml+xml" => "html",
+        "application/xml" | "text/xml" => "xml",
+        "text/csv" => "csv",
+        "text/css" => "css",
+        "text/javascript" | "application/javascript" => "js",
+        "application/yaml" | "application/x-yaml" | "text/yaml" => "yaml",
+        "application/x-ndjson" => "ndjson",
+        "text/markdown" => "md",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/gzip" | "application/x-gzip" => "gz",
+        "application/x-tar" => "tar",
+        "application/wasm" => "wasm",
+        "audio/mpeg" => "mp3",
+        "video/mp4" => "mp4",
+        "font/woff2" => "woff2",
+        _ if mime.ends_with("json") => "json",
+        _ if is_binary_content_type(content_type) => BINARY_FILE_EXTENSION,
+        _ => DEFAULT_FILE_EXTENSION,
+    }
+}
+
+/// テキストとして扱えないContent-Typeか（画像・PDFなどは受信したバイト列のまま保存する）
+pub fn is_binary_content_type(content_type: Option<&str>) -> bool {
+    let mime = mime_type(content_type);
+    BINARY_MIME_PREFIXES
+        .iter()
+        .any(|prefix| mime.starts_with(prefix))
+        && !TEXT_MIME_SUFFIXES.iter().any(|suffix| mime.ends_with(suffix))
+}
+
+/// Content-Typeのパラメータを除いた小文字のMIMEタイプ
+fn mime_type(content_type: Option<&str>) -> String {
+    content_type
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// URLパスをファイル名に使える文字列に変換（英数字・'-'・'.'以外は'_'にまとめる）
+fn path_slug(path: &str) -> String {
+    let mut slug = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with(SLUG_SEPARATOR) {
+            slug.push(SLUG_SEPARATOR);
+        }
+    }
+
+    let slug = slug.trim_end_matches(SLUG_SEPARATOR);
+    if slug.is_empty() {
+        ROOT_PATH_SLUG.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// ファイル名向けのUTC日時（X-Amz-Dateと同じ YYYYMMDD'T'HHMMSS'Z' 形式）
+pub fn compact_timestamp(time: SystemTime) -> String {
+    amz_date(time)
+}
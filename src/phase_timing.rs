@@ -0,0 +1,298 @@
+use crate::client::{PhaseTimings, TimingInfo};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::client::{ClientSessionStore, Tls12ClientSessionValue, Tls13ClientSessionValue};
+use rustls::pki_types::ServerName;
+use rustls::NamedGroup;
+use std::future::Future;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+
+// ウォーターフォールの表示
+const WATERFALL_WIDTH: usize = 40;
+const WATERFALL_BAR: char = '#';
+const WATERFALL_SPACE: char = ' ';
+const WATERFALL_EDGE: char = '|';
+const PHASE_LABELS: [&str; 5] = ["DNS", "Connect", "TLS", "Wait", "Download"];
+
+// 表示メッセージ
+const WATERFALL_HEADER: &str = "--- Timing Waterfall ---";
+const WATERFALL_ROW_MSG: &str = "{1} {2} {3}";
+const WATERFALL_TOTAL_MSG: &str = "Total: {}";
+const REUSED_CONNECTION_MSG: &str = "(reused connection)";
+
+/// 接続の各段階の時刻
+#[derive(Debug, Default, Clone, Copy)]
+struct PhaseMarks {
+    connect_start: Option<Instant>,
+    dns_start: Option<Instant>,
+    dns_end: Option<Instant>,
+    tls_start: Option<Instant>,
+    connect_end: Option<Instant>,
+}
+
+/// 名前解決・接続・TLSハンドシェイクの時刻を記録（試行ごとにリセット）
+#[derive(Debug, Clone, Default)]
+pub struct PhaseRecorder {
+    marks: Arc<Mutex<PhaseMarks>>,
+}
+
+impl PhaseRecorder {
+    /// 記録をクリア
+    pub fn reset(&self) {
+        self.mark(|marks| *marks = PhaseMarks::default());
+    }
+
+    fn mark(&self, update: impl FnOnce(&mut PhaseMarks)) {
+        if let Ok(mut marks) = self.marks.lock() {
+            update(&mut marks);
+        }
+    }
+
+    /// レスポンス受信までの時間を段階ごとに分割（接続を再利用した場合は接続までの段階が0）
+    pub fn phases(&self, response_time: Duration, body_read_time: Duration) -> PhaseTimings {
+        let marks = self.marks.lock().map(|marks| *marks).unwrap_or_default();
+        let between = |from: Option<Instant>, to: Option<Instant>| match (from, to) {
+            (Some(from), Some(to)) => to.saturating_duration_since(from),
+            _ => Duration::ZERO,
+        };
+
+        let connected = marks.connect_end.is_some();
+        let dns = between(marks.dns_start, marks.dns_end);
+        let connect = between(
+            marks.dns_end.or(marks.connect_start),
+            marks.tls_start.or(marks.connect_end),
+        );
+        let tls = between(marks.tls_start, marks.connect_end);
+
+        PhaseTimings {
+            dns,
+            connect,
+            tls,
+            wait: response_time.saturating_sub(dns + connect + tls),
+            download: body_read_time,
+            reused_connection: !connected,
+        }
+    }
+}
+
+/// 接続（名前解決からTLSハンドシェイクまで）の開始・完了を記録するレイヤー
+#[derive(Clone)]
+pub struct ConnectTimingLayer {
+    recorder: PhaseRecorder,
+}
+
+impl ConnectTimingLayer {
+    pub fn new(recorder: PhaseRecorder) -> Self {
+        Self { recorder }
+    }
+}
+
+impl<S> Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTimingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTimingService {
+            inner,
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConnectTimingService<S> {
+    inner: S,
+    recorder: PhaseRecorder,
+}
+
+impl<S, R> Service<R> for ConnectTimingService<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.recorder
+            .mark(|marks| marks.connect_start = Some(Instant::now()));
+        let connecting = self.inner.call(request);
+        let recorder = self.recorder.clone();
+
+        Box::pin(async move {
+            let connection = connecting.await;
+            if connection.is_ok() {
+                recorder.mark(|marks| marks.connect_end = Some(Instant::now()));
+            }
+            connection
+        })
+    }
+}
+
+/// 名前解決の開始・完了を記録するリゾルバ（--dns-cache 指定時はそのリゾルバを使用）
+pub struct TimingResolver {
+    inner: Option<Arc<dyn Resolve>>,
+    recorder: PhaseRecorder,
+}
+
+impl TimingResolver {
+    pub fn new(inner: Option<Arc<dyn Resolve>>, recorder: PhaseRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        self.recorder
+            .mark(|marks| marks.dns_start = Some(Instant::now()));
+        let recorder = self.recorder.clone();
+        let resolving = match &self.inner {
+            Some(inner) => inner.resolve(name),
+            None => Box::pin(async move {
+                let host = name.as_str().to_string();
+                let addrs: Vec<SocketAddr> =
+                    tokio::task::spawn_blocking(move || (host.as_str(), 0).to_socket_addrs())
+                        .await??
+                        .collect();
+                Ok(Box::new(addrs.into_iter()) as Addrs)
+            }),
+        };
+
+        Box::pin(async move {
+            let addrs = resolving.await;
+            recorder.mark(|marks| marks.dns_end = Some(Instant::now()));
+            addrs
+        })
+    }
+}
+
+/// ClientHelloの作成時（セッション再開情報の取得時）をTLSハンドシェイクの開始として記録
+#[derive(Debug)]
+pub struct TlsStartRecorder {
+    inner: Arc<dyn ClientSessionStore>,
+    recorder: PhaseRecorder,
+}
+
+impl TlsStartRecorder {
+    pub fn new(inner: Arc<dyn ClientSessionStore>, recorder: PhaseRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl ClientSessionStore for TlsStartRecorder {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.inner.set_kx_hint(server_name, group);
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.inner.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.inner.set_tls12_session(server_name, value);
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.inner.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.inner.remove_tls12_session(server_name);
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.inner.insert_tls13_ticket(server_name, value);
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        // ハンドシェイクの開始時に必ず呼ばれる
+        self.recorder
+            .mark(|marks| marks.tls_start = Some(Instant::now()));
+        self.inner.take_tls13_ticket(server_name)
+    }
+}
+
+/// 段階ごとの所要時間を横棒グラフで表示
+pub fn display_timing_waterfall(timing: &TimingInfo) {
+    let phases = match &timing.phases {
+        Some(phases) => phases,
+        None => return,
+    };
+    let durations = [
+        phases.dns,
+        phases.connect,
+        phases.tls,
+        phases.wait,
+        phases.download,
+    ];
+    let total: Duration = durations.iter().sum();
+    let label_width = PHASE_LABELS
+        .iter()
+        .map(|label| label.len())
+        .max()
+        .unwrap_or(0);
+    let column = |elapsed: Duration| {
+        if total.is_zero() {
+            0
+        } else {
+            (elapsed.as_secs_f64() / total.as_secs_f64() * WATERFALL_WIDTH as f64).round() as usize
+        }
+    };
+
+    eprintln!("{}", WATERFALL_HEADER);
+    let mut elapsed = Duration::ZERO;
+    let mut previous_end = 0;
+    for (label, duration) in PHASE_LABELS.iter().zip(durations) {
+        // 短い段階も1文字分は表示し、前の段階の後ろから始める
+        let start = column(elapsed)
+            .max(previous_end)
+            .min(WATERFALL_WIDTH - usize::from(!duration.is_zero()));
+        elapsed += duration;
+        let end = column(elapsed)
+            .max(start + usize::from(!duration.is_zero()))
+            .min(WATERFALL_WIDTH);
+        previous_end = end;
+
+        let bar: String = (0..WATERFALL_WIDTH)
+            .map(|index| {
+                if (start..end).contains(&index) {
+                    WATERFALL_BAR
+                } else {
+                    WATERFALL_SPACE
+                }
+            })
+            .collect();
+        eprintln!(
+            "{}",
+            WATERFALL_ROW_MSG
+                .replace("{1}", &format!("{:<width$}", label, width = label_width))
+                .replace(
+                    "{2}",
+                    &format!("{}{}{}", WATERFALL_EDGE, bar, WATERFALL_EDGE)
+                )
+                .replace("{3}", &format!("{:>10.2?}", duration))
+        );
+    }
+
+    let total_line = WATERFALL_TOTAL_MSG.replace("{}", &format!("{:.2?}", total));
+    if phases.reused_connection {
+        eprintln!("{} {}", total_line, REUSED_CONNECTION_MSG);
+    } else {
+        eprintln!("{}", total_line);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::client::{load_config_presets, Config};
+use crate::util::prompt_line;
+use std::error::Error;
+
+// 一覧に表示する候補の上限
+const MAX_CANDIDATES: usize = 20;
+
+// あいまい検索のスコア
+const MATCH_SCORE: i64 = 1;
+const CONSECUTIVE_BONUS: i64 = 5;
+const WORD_START_BONUS: i64 = 8;
+const WORD_SEPARATORS: [char; 6] = ['-', '_', '.', '/', ':', ' '];
+
+// エラーメッセージ
+const ERROR_NO_SELECTION: &str = "No preset was selected.";
+
+// 表示メッセージ
+const CANDIDATE_MSG: &str = "  {1}) {2}  {3} {4}";
+const MORE_CANDIDATES_MSG: &str = "  ... {} more";
+const NO_CANDIDATES_MSG: &str = "  (no matching presets)";
+const QUERY_PROMPT_MSG: &str = "Search [{}] (number to run, empty to quit): ";
+
+/// プリセット名をあいまい検索して1つ選ぶ
+///
+/// 入力した文字列で候補を絞り込み、番号で選択する。
+/// 最初の検索語で候補が1つに絞り込まれた場合はそのまま選択する。
+pub fn pick_preset(
+    config_path: &str,
+    identity: Option<&str>,
+    query: Option<&str>,
+) -> Result<String, Box<dyn Error>> {
+    let presets = load_config_presets(config_path, identity, None)?;
+    let mut query = query.unwrap_or_default().to_string();
+
+    let candidates = rank_presets(&presets, &query);
+    if !query.is_empty() && candidates.len() == 1 {
+        return Ok(candidates[0].0.to_string());
+    }
+
+    loop {
+        let candidates = rank_presets(&presets, &query);
+        display_candidates(&candidates);
+
+        let input = prompt_line(&QUERY_PROMPT_MSG.replace("{}", &query))?.unwrap_or_default();
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(ERROR_NO_SELECTION.into());
+        }
+
+        match input.parse::<usize>() {
+            Ok(number) => {
+                if let Some((name, _)) = number
+                    .checked_sub(1)
+                    .and_then(|index| candidates.get(index))
+                {
+                    return Ok(name.to_string());
+                }
+            }
+            Err(_) => query = input.to_string(),
+        }
+    }
+}
+
+/// 検索語に一致するプリセットをスコアの高い順に並べる（同点は短い名前・名前順）
+fn rank_presets<'a>(presets: &'a [(String, Config)], query: &str) -> Vec<(&'a str, &'a Config)> {
+    let mut ranked: Vec<(i64, &str, &Config)> = presets
+        .iter()
+        .filter_map(|(name, config)| {
+            fuzzy_score(name, query).map(|score| (score, name.as_str(), config))
+        })
+        .collect();
+    ranked.sort_by(|(a_score, a_name, _), (b_score, b_name, _)| {
+        b_score
+            .cmp(a_score)
+            .then(a_name.len().cmp(&b_name.len()))
+            .then(a_name.cmp(b_name))
+    });
+
+    ranked
+        .into_iter()
+        .map(|(_, name, config)| (name, config))
+        .collect()
+}
+
+/// 検索語の文字が順に含まれていればスコアを返す（連続・単語の先頭での一致を優先）
+fn fuzzy_score(candidate: &str, query: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position
+            + candidate[position..]
+                .iter()
+                .position(|c| *c == query_char)?;
+
+        score += MATCH_SCORE;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if index == 0 || WORD_SEPARATORS.contains(&candidate[index - 1]) {
+            score += WORD_START_BONUS;
+        }
+
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+/// 候補を番号付きで表示
+fn display_candidates(candidates: &[(&str, &Config)]) {
+    if candidates.is_empty() {
+        eprintln!("{}", NO_CANDIDATES_MSG);
+    }
+
+    for (index, (name, config)) in candidates.iter().take(MAX_CANDIDATES).enumerate() {
+        eprintln!(
+            "{}",
+            CANDIDATE_MSG
+                .replace("{1}", &(index + 1).to_string())
+                .replace("{2}", name)
+                .replace("{3}", &config.method)
+                .replace("{4}", &config.url)
+        );
+    }
+
+    if candidates.len() > MAX_CANDIDATES {
+        eprintln!(
+            "{}",
+            MORE_CANDIDATES_MSG.replace("{}", &(candidates.len() - MAX_CANDIDATES).to_string())
+        );
+    }
+}
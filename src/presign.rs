@@ -0,0 +1,291 @@
+use crate::client::USER_AGENT;
+use crate::util::{amz_date, hex, hmac_sha256, home_dir};
+use reqwest::blocking::Client;
+use reqwest::{Method, Url};
+use ring::digest::{digest, SHA256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+// AWS認証情報の環境変数
+const ACCESS_KEY_ID_ENV: &str = "AWS_ACCESS_KEY_ID";
+const SECRET_ACCESS_KEY_ENV: &str = "AWS_SECRET_ACCESS_KEY";
+const SESSION_TOKEN_ENV: &str = "AWS_SESSION_TOKEN";
+const REGION_ENVS: [&str; 2] = ["AWS_REGION", "AWS_DEFAULT_REGION"];
+const ENDPOINT_ENVS: [&str; 2] = ["AWS_ENDPOINT_URL_S3", "AWS_ENDPOINT_URL"];
+const DEFAULT_REGION: &str = "us-east-1";
+
+// AWSの共有設定ファイル（~/.aws/credentials・~/.aws/config）
+const PROFILE_ENV: &str = "AWS_PROFILE";
+const SHARED_CREDENTIALS_FILE_ENV: &str = "AWS_SHARED_CREDENTIALS_FILE";
+const CONFIG_FILE_ENV: &str = "AWS_CONFIG_FILE";
+const DEFAULT_CREDENTIALS_PATH: &str = ".aws/credentials";
+const DEFAULT_CONFIG_PATH: &str = ".aws/config";
+const DEFAULT_PROFILE: &str = "default";
+const CONFIG_PROFILE_PREFIX: &str = "profile ";
+const KEY_ACCESS_KEY_ID: &str = "aws_access_key_id";
+const KEY_SECRET_ACCESS_KEY: &str = "aws_secret_access_key";
+const KEY_SESSION_TOKEN: &str = "aws_session_token";
+
+// S3
+const S3_SCHEME_PREFIX: &str = "s3://";
+pub(crate) const S3_SERVICE: &str = "s3";
+const S3_ENDPOINT_TEMPLATE: &str = "https://{1}.s3.{2}.amazonaws.com/";
+const MAX_PRESIGN_EXPIRES_SECS: u64 = 604_800;
+pub const DEFAULT_PRESIGN_EXPIRES_SECS: u64 = 3600;
+
+// 署名（SigV4）
+pub(crate) const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SIGNING_KEY_PREFIX: &str = "AWS4";
+pub(crate) const SIGNING_TERMINATOR: &str = "aws4_request";
+const SIGNED_HEADERS: &str = "host";
+pub(crate) const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+const PARAM_ALGORITHM: &str = "X-Amz-Algorithm";
+const PARAM_CREDENTIAL: &str = "X-Amz-Credential";
+const PARAM_DATE: &str = "X-Amz-Date";
+const PARAM_EXPIRES: &str = "X-Amz-Expires";
+const PARAM_SECURITY_TOKEN: &str = "X-Amz-Security-Token";
+const PARAM_SIGNED_HEADERS: &str = "X-Amz-SignedHeaders";
+const PARAM_SIGNATURE: &str = "X-Amz-Signature";
+
+// エラーメッセージ
+const ERROR_INVALID_S3_URL: &str = "Invalid S3 URL '{}'. Use s3://bucket/key.";
+const ERROR_MISSING_CREDENTIALS: &str = "AWS credentials not found. Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or configure ~/.aws/credentials (AWS_PROFILE selects the profile).";
+const ERROR_INVALID_EXPIRES: &str = "--expires must be between 1 and 604800 seconds";
+const ERROR_UNSUPPORTED_PRESIGN_METHOD: &str = "Presigned URLs support GET or PUT, got '{}'";
+const ERROR_UPLOAD_FAILED: &str = "Upload failed: HTTP {}";
+
+// 表示メッセージ
+const UPLOAD_COMPLETE_MSG: &str = "Uploaded {1} ({2})";
+
+/// 署名付きURLの発行内容
+pub struct PresignRequest<'a> {
+    pub s3_url: &'a str,
+    pub method: &'a str,
+    pub expires: u64,
+}
+
+pub(crate) struct AwsCredentials {
+    pub(crate) access_key_id: String,
+    pub(crate) secret_access_key: String,
+    pub(crate) session_token: Option<String>,
+}
+
+/// S3オブジェクトの署名付きURL（SigV4クエリ署名）を作成
+pub fn presign_s3_url(request: &PresignRequest) -> Result<String, Box<dyn Error>> {
+    let method = request.method.to_ascii_uppercase();
+    if method != Method::GET.as_str() && method != Method::PUT.as_str() {
+        return Err(ERROR_UNSUPPORTED_PRESIGN_METHOD.replace("{}", request.method).into());
+    }
+    if request.expires == 0 || request.expires > MAX_PRESIGN_EXPIRES_SECS {
+        return Err(ERROR_INVALID_EXPIRES.into());
+    }
+
+    let (bucket, key) = parse_s3_url(request.s3_url)?;
+    let credentials = load_credentials()?;
+    let region = first_env(&REGION_ENVS).unwrap_or_else(|| DEFAULT_REGION.to_string());
+
+    let mut url = object_url(&bucket, &key, &region)?;
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    let amz_date = amz_date(SystemTime::now());
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/{}/{}", date, region, S3_SERVICE, SIGNING_TERMINATOR);
+
+    let mut query = vec![
+        (PARAM_ALGORITHM, SIGNING_ALGORITHM.to_string()),
+        (PARAM_CREDENTIAL, format!("{}/{}", credentials.access_key_id, scope)),
+        (PARAM_DATE, amz_date.clone()),
+        (PARAM_EXPIRES, request.expires.to_string()),
+        (PARAM_SIGNED_HEADERS, SIGNED_HEADERS.to_string()),
+    ];
+    if let Some(session_token) = &credentials.session_token {
+        query.push((PARAM_SECURITY_TOKEN, session_token.clone()));
+    }
+    query.sort_by(|a, b| a.0.cmp(b.0));
+
+    let canonical_query = query
+        .iter()
+        .map(|(name, value)| format!("{}={}", uri_encode(name, true), uri_encode(value, true)))
+        .collect::<Vec<String>>()
+        .join("&");
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\n\n{}\n{}",
+        method,
+        url.path(),
+        canonical_query,
+        host,
+        SIGNED_HEADERS,
+        UNSIGNED_PAYLOAD
+    );
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        SIGNING_ALGORITHM,
+        amz_date,
+        scope,
+        hex(digest(&SHA256, canonical_request.as_bytes()).as_ref())
+    );
+
+    let signing_key = signing_key(&credentials, date, &region, S3_SERVICE);
+    let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    url.set_query(Some(&format!(
+        "{}&{}={}",
+        canonical_query, PARAM_SIGNATURE, signature
+    )));
+    Ok(url.to_string())
+}
+
+/// 署名付きURLにファイルをPUTでアップロード
+pub fn upload_to_presigned(url: &str, file_path: &str, timeout: Duration) -> Result<(), Box<dyn Error>> {
+    let client = Client::builder()
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+        .build()?;
+    let file = File::open(file_path)?;
+
+    let response = client.put(url).body(file).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ERROR_UPLOAD_FAILED.replace("{}", &status.to_string()).into());
+    }
+
+    println!(
+        "{}",
+        UPLOAD_COMPLETE_MSG
+            .replace("{1}", file_path)
+            .replace("{2}", &status.to_string())
+    );
+    Ok(())
+}
+
+/// s3://bucket/key をバケット名とキーに分解
+fn parse_s3_url(s3_url: &str) -> Result<(String, String), Box<dyn Error>> {
+    let invalid_url = || ERROR_INVALID_S3_URL.replace("{}", s3_url);
+
+    let path = s3_url.strip_prefix(S3_SCHEME_PREFIX).ok_or_else(invalid_url)?;
+    let (bucket, key) = path.split_once('/').ok_or_else(invalid_url)?;
+    if bucket.is_empty() || key.is_empty() {
+        return Err(invalid_url().into());
+    }
+
+    Ok((bucket.to_string(), key.to_string()))
+}
+
+/// オブジェクトのURL（エンドポイント指定時はパス形式、それ以外は仮想ホスト形式）
+fn object_url(bucket: &str, key: &str, region: &str) -> Result<Url, Box<dyn Error>> {
+    let encoded_key = uri_encode(key, false);
+
+    let url = match first_env(&ENDPOINT_ENVS) {
+        Some(endpoint) => format!(
+            "{}/{}/{}",
+            endpoint.trim_end_matches('/'),
+            uri_encode(bucket, true),
+            encoded_key
+        ),
+        None => format!(
+            "{}{}",
+            S3_ENDPOINT_TEMPLATE
+                .replace("{1}", bucket)
+                .replace("{2}", region),
+            encoded_key
+        ),
+    };
+
+    Ok(Url::parse(&url)?)
+}
+
+/// AWS認証情報を読み込む（環境変数、~/.aws/credentials、~/.aws/config の順）
+pub(crate) fn load_credentials() -> Result<AwsCredentials, Box<dyn Error>> {
+    if let (Ok(access_key_id), Ok(secret_access_key)) = (
+        std::env::var(ACCESS_KEY_ID_ENV),
+        std::env::var(SECRET_ACCESS_KEY_ENV),
+    ) {
+        return Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key,
+            session_token: std::env::var(SESSION_TOKEN_ENV).ok(),
+        });
+    }
+
+    let profile = first_env(&[PROFILE_ENV]).unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let config_section = if profile == DEFAULT_PROFILE {
+        profile.clone()
+    } else {
+        format!("{}{}", CONFIG_PROFILE_PREFIX, profile)
+    };
+    [
+        (SHARED_CREDENTIALS_FILE_ENV, DEFAULT_CREDENTIALS_PATH, profile),
+        (CONFIG_FILE_ENV, DEFAULT_CONFIG_PATH, config_section),
+    ]
+    .iter()
+    .find_map(|(path_env, default_path, section)| {
+        let path = shared_file_path(path_env, default_path)?;
+        let mut values = read_ini_section(&fs::read_to_string(path).ok()?, section);
+        Some(AwsCredentials {
+            access_key_id: values.remove(KEY_ACCESS_KEY_ID)?,
+            secret_access_key: values.remove(KEY_SECRET_ACCESS_KEY)?,
+            session_token: values.remove(KEY_SESSION_TOKEN),
+        })
+    })
+    .ok_or_else(|| ERROR_MISSING_CREDENTIALS.into())
+}
+
+/// 共有設定ファイルのパス（環境変数の指定がなければホームディレクトリ以下の既定のパス）
+fn shared_file_path(path_env: &str, default_path: &str) -> Option<PathBuf> {
+    first_env(&[path_env])
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(default_path)))
+}
+
+/// INI形式のファイルから指定したセクションのキーと値を取得
+fn read_ini_section(contents: &str, section: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut in_section = false;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            in_section = name.trim() == section;
+        } else if let (true, Some((key, value))) = (in_section, line.split_once('=')) {
+            values.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    values
+}
+
+/// SigV4の署名鍵（日付・リージョン・サービスごとに導出）
+pub(crate) fn signing_key(credentials: &AwsCredentials, date: &str, region: &str, service: &str) -> Vec<u8> {
+    [date, region, service, SIGNING_TERMINATOR].iter().fold(
+        format!("{}{}", SIGNING_KEY_PREFIX, credentials.secret_access_key).into_bytes(),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    )
+}
+
+/// 最初に設定されている環境変数の値
+fn first_env(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+/// SigV4のURIエンコード（encode_slashがfalseの場合は'/'を残す）
+pub(crate) fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
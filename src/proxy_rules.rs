@@ -0,0 +1,72 @@
+use crate::header_filter::wildcard_match;
+use reqwest::{Proxy, Url};
+use std::collections::HashMap;
+use std::error::Error;
+
+// ルールの値
+const DIRECT: &str = "direct";
+const SUPPORTED_SCHEMES: [&str; 6] = ["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+
+// エラーメッセージ
+const ERROR_INVALID_PROXY_RULE: &str =
+    "Invalid proxy rule \"{1}\" = \"{2}\". Use a proxy URL (http://, https://, socks5://, ...) or \"direct\".";
+
+/// ホストのパターンごとのプロキシ（[proxy_rules]）
+struct ProxyRule {
+    pattern: String,
+    /// None は直接接続
+    target: Option<Url>,
+}
+
+/// [proxy_rules] から接続先のホストに応じてプロキシを選ぶ Proxy を作成
+///
+/// 複数のルールに一致する場合はパターンが長い（より具体的な）ルールを使い、
+/// どのルールにも一致しないホストは fallback（--proxy・プリセットの proxy）を使う。
+pub fn build_rules_proxy(
+    proxy_rules: &HashMap<String, String>,
+    fallback: Option<Url>,
+) -> Result<Proxy, Box<dyn Error>> {
+    let mut rules = proxy_rules
+        .iter()
+        .map(|(pattern, target)| parse_rule(pattern, target))
+        .collect::<Result<Vec<ProxyRule>, Box<dyn Error>>>()?;
+    rules.sort_by(|a, b| {
+        b.pattern
+            .len()
+            .cmp(&a.pattern.len())
+            .then_with(|| a.pattern.cmp(&b.pattern))
+    });
+
+    Ok(Proxy::custom(move |url| {
+        let host = url.host_str()?;
+        match rules.iter().find(|rule| wildcard_match(&rule.pattern, host)) {
+            Some(rule) => rule.target.clone(),
+            None => fallback.clone(),
+        }
+    }))
+}
+
+/// "パターン" = "プロキシのURL" の1件を解析
+fn parse_rule(pattern: &str, target: &str) -> Result<ProxyRule, Box<dyn Error>> {
+    if target.eq_ignore_ascii_case(DIRECT) {
+        return Ok(ProxyRule {
+            pattern: pattern.to_string(),
+            target: None,
+        });
+    }
+
+    let invalid = || {
+        ERROR_INVALID_PROXY_RULE
+            .replace("{1}", pattern)
+            .replace("{2}", target)
+    };
+    let url = Url::parse(target).map_err(|_| invalid())?;
+    if !SUPPORTED_SCHEMES.contains(&url.scheme()) || url.host_str().is_none() {
+        return Err(invalid().into());
+    }
+
+    Ok(ProxyRule {
+        pattern: pattern.to_string(),
+        target: Some(url),
+    })
+}
@@ -0,0 +1,137 @@
+use crate::client::{shows_verbose_text, Config};
+use reqwest::blocking::{Client, ClientBuilder, Request, Response};
+use reqwest::header::{
+    HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION,
+    PROXY_AUTHORIZATION, REFERER, TRANSFER_ENCODING, WWW_AUTHENTICATE,
+};
+use reqwest::{redirect, Method, StatusCode, Url};
+
+// リダイレクトの上限（reqwestの既定と同じ）
+const MAX_REDIRECTS: usize = 10;
+
+// スキーム
+const HTTPS_SCHEME: &str = "https";
+const HTTP_SCHEME: &str = "http";
+
+// 表示メッセージ
+const REDIRECT_MSG: &str = "* Redirect {1} to {2} ({3})";
+
+/// --post301/--post302/--post303 のいずれかが指定されているか
+fn preserves_post(config: &Config) -> bool {
+    config.post301 || config.post302 || config.post303
+}
+
+/// POSTを維持する指定がある場合は自動リダイレクトを無効化し、送信時に追跡する
+pub fn setup_redirect_policy(client_builder: ClientBuilder, config: &Config) -> ClientBuilder {
+    if preserves_post(config) {
+        client_builder.redirect(redirect::Policy::none())
+    } else {
+        client_builder
+    }
+}
+
+/// リクエストを送信し、必要に応じてリダイレクトを追跡
+/// （自動リダイレクトが有効な場合はreqwestに任せる）
+pub fn execute_following_redirects(
+    client: &Client,
+    request: Request,
+    config: &Config,
+) -> reqwest::Result<Response> {
+    if !preserves_post(config) {
+        return client.execute(request);
+    }
+
+    let mut request = request;
+    for _ in 0..MAX_REDIRECTS {
+        let next_template = request.try_clone();
+        let response = client.execute(request)?;
+
+        let next_request =
+            next_template.and_then(|template| redirect_request(template, &response, config));
+        match next_request {
+            Some(next_request) => request = next_request,
+            None => return Ok(response),
+        }
+    }
+
+    client.execute(request)
+}
+
+/// リダイレクト先へのリクエストを作成（リダイレクトでない場合はNone）
+fn redirect_request(mut request: Request, response: &Response, config: &Config) -> Option<Request> {
+    let status = response.status();
+    if !matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return None;
+    }
+
+    let location = response.headers().get(LOCATION)?.to_str().ok()?;
+    let previous_url = request.url().clone();
+    let next_url = previous_url.join(location).ok()?;
+
+    if should_switch_to_get(status, request.method(), config) {
+        *request.method_mut() = Method::GET;
+        *request.body_mut() = None;
+        for name in [CONTENT_TYPE, CONTENT_LENGTH, TRANSFER_ENCODING] {
+            request.headers_mut().remove(name);
+        }
+    }
+
+    // 別のオリジンには認証情報を送らない
+    if next_url.origin() != previous_url.origin() {
+        for name in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE] {
+            request.headers_mut().remove(name);
+        }
+    }
+
+    request.headers_mut().remove(REFERER);
+    if let Some(referer) = referer_for(&previous_url, &next_url) {
+        request.headers_mut().insert(REFERER, referer);
+    }
+
+    if shows_verbose_text(config) {
+        eprintln!(
+            "{}",
+            REDIRECT_MSG
+                .replace("{1}", &status.as_u16().to_string())
+                .replace("{2}", next_url.as_str())
+                .replace("{3}", request.method().as_str())
+        );
+    }
+
+    *request.url_mut() = next_url;
+    Some(request)
+}
+
+/// リダイレクト時にGETへ切り替えるか（307/308と、--post30x を指定したPOSTは維持）
+fn should_switch_to_get(status: StatusCode, method: &Method, config: &Config) -> bool {
+    match status {
+        StatusCode::MOVED_PERMANENTLY => *method == Method::POST && !config.post301,
+        StatusCode::FOUND => *method == Method::POST && !config.post302,
+        StatusCode::SEE_OTHER => match *method {
+            Method::GET | Method::HEAD => false,
+            Method::POST => !config.post303,
+            _ => true,
+        },
+        _ => false,
+    }
+}
+
+/// リダイレクト元のURLをRefererとして設定（https→httpの場合は送らない）
+fn referer_for(previous_url: &Url, next_url: &Url) -> Option<HeaderValue> {
+    if previous_url.scheme() == HTTPS_SCHEME && next_url.scheme() == HTTP_SCHEME {
+        return None;
+    }
+
+    let mut referer = previous_url.clone();
+    let _ = referer.set_username("");
+    let _ = referer.set_password(None);
+    referer.set_fragment(None);
+    HeaderValue::from_str(referer.as_str()).ok()
+}
@@ -0,0 +1,223 @@
+use crate::client::{Config, ResponseInfo, TimingInfo};
+use crate::header_filter::is_header_visible;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::time::Duration;
+
+// 出力形式
+const OUTPUT_FORMAT_TEXT: &str = "text";
+const OUTPUT_FORMAT_JSON: &str = "json";
+
+// エラーメッセージ
+const ERROR_INVALID_OUTPUT_FORMAT: &str = "Invalid output format '{}'. Use text or json.";
+const ERROR_INVALID_META_FORMAT: &str = "Invalid meta format '{}'. Use text or json.";
+
+// 補足情報のイベント名
+const META_EVENT_REQUEST: &str = "request";
+const META_EVENT_RESPONSE: &str = "response";
+
+/// レスポンスの出力形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// ボディのみを表示（-v・--timing の情報は別途表示）
+    Text,
+    /// ステータス・ヘッダー・タイミング・ボディを1つのJSONドキュメントにまとめる
+    Json,
+}
+
+/// 1リクエスト分の結果
+#[derive(Serialize)]
+struct ResultDocument<'a> {
+    status: u16,
+    version: String,
+    url: &'a str,
+    headers: Map<String, Value>,
+    timing: TimingPhases,
+    retries: usize,
+    attempts: Vec<AttemptRecord<'a>>,
+    body: Value,
+}
+
+/// 送信するリクエストの補足情報（--meta-format json）
+#[derive(Serialize)]
+struct RequestMeta<'a> {
+    event: &'a str,
+    method: &'a str,
+    url: &'a str,
+    headers: Map<String, Value>,
+}
+
+/// 受信したレスポンスの補足情報（--meta-format json）
+#[derive(Serialize)]
+struct ResponseMeta<'a> {
+    event: &'a str,
+    status: u16,
+    reason: &'a str,
+    version: String,
+    url: &'a str,
+    headers: Map<String, Value>,
+    timing: TimingPhases,
+    retries: usize,
+    attempts: Vec<AttemptRecord<'a>>,
+}
+
+/// 所要時間の内訳（ミリ秒）
+#[derive(Serialize)]
+struct TimingPhases {
+    response_ms: f64,
+    body_read_ms: f64,
+    total_ms: f64,
+}
+
+/// 1回の試行（ミリ秒）
+#[derive(Serialize)]
+struct AttemptRecord<'a> {
+    number: u32,
+    outcome: &'a str,
+    duration_ms: f64,
+    delay_ms: Option<f64>,
+}
+
+/// 出力形式を解釈（未指定の場合はテキスト）
+pub fn parse_output_format(format: Option<&str>) -> Result<OutputFormat, Box<dyn Error>> {
+    match format {
+        None | Some(OUTPUT_FORMAT_TEXT) => Ok(OutputFormat::Text),
+        Some(OUTPUT_FORMAT_JSON) => Ok(OutputFormat::Json),
+        Some(format) => Err(ERROR_INVALID_OUTPUT_FORMAT.replace("{}", format).into()),
+    }
+}
+
+/// --meta-format を解釈（未指定の場合はテキスト）
+pub fn parse_meta_format(format: Option<&str>) -> Result<OutputFormat, Box<dyn Error>> {
+    parse_output_format(format).map_err(|_| {
+        ERROR_INVALID_META_FORMAT
+            .replace("{}", format.unwrap_or_default())
+            .into()
+    })
+}
+
+/// 補足情報（リクエスト・レスポンスのヘッダー、リトライ、タイミング）をJSONで表示するか
+pub fn is_json_meta_format(config: &Config) -> bool {
+    config.meta_format.as_deref() == Some(OUTPUT_FORMAT_JSON)
+}
+
+/// JSON形式で出力するかどうか（-v・--timing などの個別の表示は行わない）
+pub fn is_json_format(config: &Config) -> bool {
+    config.format.as_deref() == Some(OUTPUT_FORMAT_JSON)
+}
+
+/// レスポンスをJSONドキュメントに変換（ボディはJSONとして解釈できる場合は値として埋め込む）
+pub fn render_result_document(
+    response_info: &ResponseInfo,
+    timing_info: &TimingInfo,
+    body: &str,
+    config: &Config,
+) -> Result<String, Box<dyn Error>> {
+    let document = ResultDocument {
+        status: response_info.status().as_u16(),
+        version: format!("{:?}", response_info.version()),
+        url: response_info.url().as_str(),
+        headers: header_map(response_info.headers(), |_| true),
+        timing: timing_phases(timing_info),
+        retries: timing_info.attempts.len().saturating_sub(1),
+        attempts: attempt_records(timing_info),
+        body: serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string())),
+    };
+
+    let rendered = if config.pretty_json {
+        serde_json::to_string_pretty(&document)?
+    } else {
+        serde_json::to_string(&document)?
+    };
+
+    Ok(rendered)
+}
+
+/// 送信するリクエストの補足情報を1行のJSONに変換（ヘッダーは表示用に加工済みの値）
+pub fn render_request_meta(config: &Config, headers: &[(String, String)]) -> String {
+    let meta = RequestMeta {
+        event: META_EVENT_REQUEST,
+        method: &config.method,
+        url: &config.url,
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+            .collect(),
+    };
+    // 文字列と数値だけで構成されるため変換に失敗しない
+    serde_json::to_string(&meta).unwrap_or_default()
+}
+
+/// 受信したレスポンスの補足情報を1行のJSONに変換（--show-headers・--hide-headers を適用）
+pub fn render_response_meta(
+    response_info: &ResponseInfo,
+    timing_info: &TimingInfo,
+    config: &Config,
+) -> String {
+    let meta = ResponseMeta {
+        event: META_EVENT_RESPONSE,
+        status: response_info.status().as_u16(),
+        reason: response_info.status().canonical_reason().unwrap_or_default(),
+        version: format!("{:?}", response_info.version()),
+        url: response_info.url().as_str(),
+        headers: header_map(response_info.headers(), |name| {
+            is_header_visible(config, name)
+        }),
+        timing: timing_phases(timing_info),
+        retries: timing_info.attempts.len().saturating_sub(1),
+        attempts: attempt_records(timing_info),
+    };
+    // 文字列と数値だけで構成されるため変換に失敗しない
+    serde_json::to_string(&meta).unwrap_or_default()
+}
+
+/// 所要時間の内訳
+fn timing_phases(timing_info: &TimingInfo) -> TimingPhases {
+    TimingPhases {
+        response_ms: millis(timing_info.response_time),
+        body_read_ms: millis(timing_info.body_read_time),
+        total_ms: millis(timing_info.total_time),
+    }
+}
+
+/// 試行ごとの結果
+fn attempt_records(timing_info: &TimingInfo) -> Vec<AttemptRecord<'_>> {
+    timing_info
+        .attempts
+        .iter()
+        .map(|attempt| AttemptRecord {
+            number: attempt.number,
+            outcome: &attempt.outcome,
+            duration_ms: millis(attempt.duration),
+            delay_ms: attempt.delay.map(millis),
+        })
+        .collect()
+}
+
+/// ヘッダーをオブジェクトに変換（同名のヘッダーが複数ある場合は配列）
+fn header_map(headers: &HeaderMap, is_visible: impl Fn(&str) -> bool) -> Map<String, Value> {
+    headers
+        .keys()
+        .filter(|name| is_visible(name.as_str()))
+        .map(|name| {
+            let mut values: Vec<Value> = headers
+                .get_all(name)
+                .iter()
+                .map(|value| Value::String(String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .collect();
+            let value = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                Value::Array(values)
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// 所要時間をミリ秒に変換
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
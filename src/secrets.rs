@@ -0,0 +1,133 @@
+use crate::keychain::keychain_lookup;
+use age::armor::ArmoredReader;
+use age::{Decryptor, Identity, IdentityFile};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::error::Error;
+use std::io::Read;
+
+// 暗号化された値
+const ENCRYPTED_PREFIX: &str = "enc:";
+const KEYCHAIN_PREFIX: &str = "keychain:";
+const PRESET_TABLE: &str = "preset";
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+const SECRET_KEY_PREFIX: &str = "AGE-SECRET-KEY-";
+
+// エラーメッセージ
+const ERROR_MISSING_IDENTITY: &str =
+    "Config file contains encrypted values. Specify an age identity with --identity or AGE_IDENTITY.";
+const ERROR_DECRYPT_VALUE: &str = "Failed to decrypt config value: {}";
+
+/// 設定値中の "enc:" で始まる文字列をageで復号して置き換える
+pub fn decrypt_secrets(value: &mut toml::Value, identity: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let mut identities: Option<Vec<Box<dyn Identity>>> = None;
+    decrypt_value(value, identity, &mut identities)
+}
+
+/// 値を再帰的にたどって暗号化された文字列を復号（識別子は最初に必要になった時点で読み込む）
+fn decrypt_value(
+    value: &mut toml::Value,
+    identity: Option<&str>,
+    identities: &mut Option<Vec<Box<dyn Identity>>>,
+) -> Result<(), Box<dyn Error>> {
+    match value {
+        toml::Value::String(text) => {
+            if let Some(ciphertext) = text.strip_prefix(ENCRYPTED_PREFIX) {
+                if identities.is_none() {
+                    *identities = Some(load_identities(identity)?);
+                }
+                let loaded = identities.as_deref().unwrap_or_default();
+                *text = decrypt_text(ciphertext.trim(), loaded)
+                    .map_err(|e| ERROR_DECRYPT_VALUE.replace("{}", &e.to_string()))?;
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                decrypt_value(item, identity, identities)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                decrypt_value(item, identity, identities)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// 設定値中の "keychain:" で始まる文字列をOSのキーチェーンの値に置き換える
+///
+/// preset_name を指定した場合、ほかのプリセットの値は置き換えない（使わないプリセットのために
+/// キーチェーンへアクセスして、ロックの解除を求められたり失敗したりしないようにする）。
+pub fn resolve_keychain_refs(value: &mut toml::Value, preset_name: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let (Some(preset_name), toml::Value::Table(table)) = (preset_name, &mut *value) else {
+        return resolve_keychain_value(value);
+    };
+
+    for (key, item) in table.iter_mut() {
+        match (key.as_str(), item) {
+            (PRESET_TABLE, toml::Value::Table(presets)) => {
+                if let Some(preset) = presets.get_mut(preset_name) {
+                    resolve_keychain_value(preset)?;
+                }
+            }
+            (_, item) => resolve_keychain_value(item)?,
+        }
+    }
+    Ok(())
+}
+
+/// 値を再帰的にたどって "keychain:<name>" をキーチェーンの値に置き換える
+fn resolve_keychain_value(value: &mut toml::Value) -> Result<(), Box<dyn Error>> {
+    match value {
+        toml::Value::String(text) => {
+            if let Some(name) = text.strip_prefix(KEYCHAIN_PREFIX) {
+                *text = keychain_lookup(name.trim())?;
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                resolve_keychain_value(item)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                resolve_keychain_value(item)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// 識別子を読み込む（AGE-SECRET-KEY-で始まる場合は鍵そのもの、それ以外はファイルパス）
+fn load_identities(identity: Option<&str>) -> Result<Vec<Box<dyn Identity>>, Box<dyn Error>> {
+    let identity = identity.ok_or(ERROR_MISSING_IDENTITY)?;
+
+    let identity_file = if identity.trim().starts_with(SECRET_KEY_PREFIX) {
+        IdentityFile::from_buffer(identity.trim().as_bytes())?
+    } else {
+        IdentityFile::from_file(identity.to_string())?
+    };
+
+    Ok(identity_file.into_identities()?)
+}
+
+/// ASCIIアーマーまたはBase64でエンコードされた暗号文を復号
+fn decrypt_text(ciphertext: &str, identities: &[Box<dyn Identity>]) -> Result<String, Box<dyn Error>> {
+    let encrypted = if ciphertext.starts_with(ARMOR_BEGIN) {
+        ciphertext.as_bytes().to_vec()
+    } else {
+        STANDARD.decode(ciphertext)?
+    };
+
+    let decryptor = Decryptor::new(ArmoredReader::new(encrypted.as_slice()))?;
+    let mut reader = decryptor.decrypt(identities.iter().map(|identity| identity.as_ref()))?;
+
+    let mut plaintext = String::new();
+    reader.read_to_string(&mut plaintext)?;
+    Ok(plaintext)
+}
@@ -0,0 +1,95 @@
+use crate::client::{extract_json_path, Config};
+use serde_json::Value;
+use std::error::Error;
+
+// 指定の区切り
+const EXPORT_SEPARATOR: char = '=';
+
+// シェルの記法
+const EXPORT_LINE: &str = "export {1}={2}";
+const SINGLE_QUOTE: char = '\'';
+const ESCAPED_SINGLE_QUOTE: &str = "'\\''";
+
+// エラーメッセージ
+const ERROR_INVALID_EXPORT: &str =
+    "Invalid --export-shell '{}'. Use NAME=.json.path (NAME must be a valid shell variable name).";
+const ERROR_EXPORT_NOT_JSON: &str = "--export-shell requires a JSON response body: {}";
+const ERROR_EXPORT_MISSING: &str = "--export-shell {1}: {2} is missing or null in the response";
+
+/// 1件の書き出し（NAME=.data.access_token）
+struct ShellExport<'a> {
+    name: &'a str,
+    path: &'a str,
+}
+
+/// --export-shell の指定を検証
+pub fn validate_shell_exports(config: &Config) -> Result<(), Box<dyn Error>> {
+    parse_shell_exports(config).map(|_| ())
+}
+
+/// レスポンスのJSONから値を取り出し、`export NAME='value'` の行を作成
+///
+/// `eval "$(rs-w3r ... --export-shell TOKEN=.access_token)"` で読み込めるよう、
+/// 値はシングルクォートで囲む（文字列以外はJSONの表記）。値がない場合はエラーにする。
+pub fn render_shell_exports(config: &Config, body: &str) -> Result<String, Box<dyn Error>> {
+    let json: Value = serde_json::from_str(body)
+        .map_err(|e| ERROR_EXPORT_NOT_JSON.replace("{}", &e.to_string()))?;
+
+    let mut lines = Vec::new();
+    for export in parse_shell_exports(config)? {
+        let value = match extract_json_path(json.clone(), export.path)? {
+            Value::Null => {
+                return Err(ERROR_EXPORT_MISSING
+                    .replace("{1}", export.name)
+                    .replace("{2}", export.path)
+                    .into())
+            }
+            Value::String(text) => text,
+            other => other.to_string(),
+        };
+        lines.push(
+            EXPORT_LINE
+                .replace("{1}", export.name)
+                .replace("{2}", &shell_quote(&value)),
+        );
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// 設定の NAME=PATH を解析
+fn parse_shell_exports(config: &Config) -> Result<Vec<ShellExport<'_>>, Box<dyn Error>> {
+    config
+        .export_shell
+        .iter()
+        .flatten()
+        .map(|export| {
+            let invalid = || ERROR_INVALID_EXPORT.replace("{}", export);
+            let (name, path) = export.split_once(EXPORT_SEPARATOR).ok_or_else(invalid)?;
+            let (name, path) = (name.trim(), path.trim());
+            if !is_shell_variable_name(name) || path.is_empty() {
+                return Err(invalid().into());
+            }
+            Ok(ShellExport { name, path })
+        })
+        .collect()
+}
+
+/// シェルの変数名として使えるか（英字・_ で始まり、英数字・_ が続く）
+fn is_shell_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// シングルクォートで囲む（値の中の ' は '\'' に置き換える）
+fn shell_quote(value: &str) -> String {
+    format!(
+        "{}{}{}",
+        SINGLE_QUOTE,
+        value.replace(SINGLE_QUOTE, ESCAPED_SINGLE_QUOTE),
+        SINGLE_QUOTE
+    )
+}
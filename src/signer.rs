@@ -0,0 +1,40 @@
+use crate::client::Config;
+use crate::jwt::mint_jwt;
+use crate::sigv4::AwsSigV4Signer;
+use reqwest::blocking::Request;
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use std::error::Error;
+
+// 認証スキーム
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// 組み立て済みのリクエストに署名を付与する（送信前に build_request から順に呼び出される）
+///
+/// 独自のHMAC署名などが必要な場合はこのトレイトを実装し、
+/// `execute_request_with_signers` に渡します。
+pub trait RequestSigner: Send + Sync {
+    /// 署名を付与したリクエストを返す
+    fn sign(&self, request: Request, config: &Config) -> Result<Request, Box<dyn Error>>;
+}
+
+/// プリセットの[jwt]設定からJWTを発行し、Bearerトークンとして付与
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JwtSigner;
+
+impl RequestSigner for JwtSigner {
+    fn sign(&self, mut request: Request, config: &Config) -> Result<Request, Box<dyn Error>> {
+        if let Some(jwt_config) = &config.jwt {
+            let token = mint_jwt(jwt_config)?;
+            let mut value = HeaderValue::from_str(&format!("{}{}", BEARER_PREFIX, token))?;
+            value.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+
+        Ok(request)
+    }
+}
+
+/// CLIで使用する署名の一覧（SigV4は最後のヘッダーまで含めて署名するため最後に適用）
+pub fn default_signers() -> Vec<Box<dyn RequestSigner>> {
+    vec![Box::new(JwtSigner), Box::new(AwsSigV4Signer)]
+}
@@ -0,0 +1,232 @@
+use crate::client::Config;
+use crate::presign::{
+    load_credentials, signing_key, uri_encode, S3_SERVICE, SIGNING_ALGORITHM, SIGNING_TERMINATOR,
+    UNSIGNED_PAYLOAD,
+};
+use crate::signer::RequestSigner;
+use crate::util::{amz_date, hex, hmac_sha256};
+use reqwest::blocking::Request;
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use ring::digest::{digest, SHA256};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::time::SystemTime;
+
+// --aws-sigv4 の区切り
+const SCOPE_SEPARATOR: char = '/';
+
+// 署名するヘッダー
+const HEADER_HOST: &str = "host";
+const HEADER_AMZ_DATE: &str = "x-amz-date";
+const HEADER_AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
+const HEADER_AMZ_SECURITY_TOKEN: &str = "x-amz-security-token";
+const AMZ_HEADER_PREFIX: &str = "x-amz-";
+
+// Authorizationヘッダー
+const AUTHORIZATION_TEMPLATE: &str = "{1} Credential={2}/{3}, SignedHeaders={4}, Signature={5}";
+
+// エラーメッセージ
+const ERROR_INVALID_SIGV4: &str =
+    "Invalid --aws-sigv4 '{}'. Use <region>/<service> (e.g. us-east-1/execute-api).";
+const ERROR_SIGV4_AUTH_CONFLICT: &str =
+    "--aws-sigv4 cannot be combined with other Authorization methods (Basic auth, --bearer, [jwt], [oauth2])";
+const ERROR_MISSING_HOST: &str = "--aws-sigv4 requires a URL with a host";
+
+/// --aws-sigv4 <region>/<service> でリクエストにAWS Signature Version 4の署名を付与
+///
+/// 認証情報は環境変数（AWS_ACCESS_KEY_ID など）、~/.aws/credentials、~/.aws/config の順に探す。
+/// ボディをメモリに持たないリクエスト（ファイルのストリーミング送信など）は
+/// ペイロードを UNSIGNED-PAYLOAD として署名する。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AwsSigV4Signer;
+
+impl RequestSigner for AwsSigV4Signer {
+    fn sign(&self, mut request: Request, config: &Config) -> Result<Request, Box<dyn Error>> {
+        let Some(sigv4) = &config.aws_sigv4 else {
+            return Ok(request);
+        };
+        let (region, service) = parse_sigv4_scope(sigv4)?;
+        let credentials = load_credentials()?;
+
+        let amz_date = amz_date(SystemTime::now());
+        let date = &amz_date[..8];
+        let payload_hash = match request.body() {
+            None => hex(digest(&SHA256, b"").as_ref()),
+            Some(body) => match body.as_bytes() {
+                Some(bytes) => hex(digest(&SHA256, bytes).as_ref()),
+                None => UNSIGNED_PAYLOAD.to_string(),
+            },
+        };
+
+        let headers = request.headers_mut();
+        headers.insert(HEADER_AMZ_DATE, HeaderValue::from_str(&amz_date)?);
+        headers.insert(
+            HEADER_AMZ_CONTENT_SHA256,
+            HeaderValue::from_str(&payload_hash)?,
+        );
+        if let Some(session_token) = &credentials.session_token {
+            let mut value = HeaderValue::from_str(session_token)?;
+            value.set_sensitive(true);
+            headers.insert(HEADER_AMZ_SECURITY_TOKEN, value);
+        }
+
+        let (canonical_headers, signed_headers) = canonical_headers(&request)?;
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            request.method().as_str(),
+            canonical_uri(request.url().path(), service),
+            canonical_query(&request),
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/{}/{}", date, region, service, SIGNING_TERMINATOR);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            SIGNING_ALGORITHM,
+            amz_date,
+            scope,
+            hex(digest(&SHA256, canonical_request.as_bytes()).as_ref())
+        );
+        let signature = hex(&hmac_sha256(
+            &signing_key(&credentials, date, region, service),
+            string_to_sign.as_bytes(),
+        ));
+
+        let mut authorization = HeaderValue::from_str(
+            &AUTHORIZATION_TEMPLATE
+                .replace("{1}", SIGNING_ALGORITHM)
+                .replace("{2}", &credentials.access_key_id)
+                .replace("{3}", &scope)
+                .replace("{4}", &signed_headers)
+                .replace("{5}", &signature),
+        )?;
+        authorization.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, authorization);
+
+        Ok(request)
+    }
+}
+
+/// --aws-sigv4 の指定と、ほかの認証方式と併用していないことを検証
+pub fn validate_aws_sigv4(config: &Config) -> Result<(), Box<dyn Error>> {
+    let Some(sigv4) = &config.aws_sigv4 else {
+        return Ok(());
+    };
+    parse_sigv4_scope(sigv4)?;
+
+    if config.basic_auth.is_some()
+        || config.bearer_token.is_some()
+        || config.jwt.is_some()
+        || config.oauth2.is_some()
+    {
+        return Err(ERROR_SIGV4_AUTH_CONFLICT.into());
+    }
+    Ok(())
+}
+
+/// region/service に分解
+fn parse_sigv4_scope(sigv4: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    match sigv4.split_once(SCOPE_SEPARATOR) {
+        Some((region, service))
+            if !region.trim().is_empty()
+                && !service.trim().is_empty()
+                && !service.contains(SCOPE_SEPARATOR) =>
+        {
+            Ok((region.trim(), service.trim()))
+        }
+        _ => Err(ERROR_INVALID_SIGV4.replace("{}", sigv4).into()),
+    }
+}
+
+/// 正規化したパス（S3以外のサービスはエンコード済みのパスをもう一度エンコードする）
+fn canonical_uri(path: &str, service: &str) -> String {
+    let encoded = uri_encode(&percent_decode(path), false);
+    if service == S3_SERVICE {
+        encoded
+    } else {
+        uri_encode(&encoded, false)
+    }
+}
+
+/// 正規化したクエリ文字列（名前・値をエンコードして並べ替え）
+fn canonical_query(request: &Request) -> String {
+    let mut pairs: Vec<(String, String)> = request
+        .url()
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(&name, true), uri_encode(&value, true)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// 署名するヘッダー（host・content-type・x-amz-*）の正規化した一覧と名前の一覧
+fn canonical_headers(request: &Request) -> Result<(String, String), Box<dyn Error>> {
+    let url = request.url();
+    let host = url.host_str().ok_or(ERROR_MISSING_HOST)?;
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+
+    let mut headers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    headers.insert(HEADER_HOST.to_string(), vec![host]);
+    for (name, value) in request.headers() {
+        if is_signed_header(name) {
+            headers
+                .entry(name.as_str().to_string())
+                .or_default()
+                .push(normalize_header_value(value.to_str()?));
+        }
+    }
+
+    let canonical = headers
+        .iter()
+        .map(|(name, values)| format!("{}:{}\n", name, values.join(",")))
+        .collect();
+    let signed = headers
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<&str>>()
+        .join(";");
+    Ok((canonical, signed))
+}
+
+/// 署名の対象にするヘッダーか
+fn is_signed_header(name: &HeaderName) -> bool {
+    name == CONTENT_TYPE || name.as_str().starts_with(AMZ_HEADER_PREFIX)
+}
+
+/// 前後の空白を除き、連続する空白を1つにまとめる
+fn normalize_header_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// パーセントエンコードを元に戻す
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| bytes.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,235 @@
+use crate::client::{
+    execute_request_with, load_status_presets, Config, ResponseInfo, TimingInfo, QUIET_BODY,
+};
+use crate::observer::ResponseObserver;
+use crate::signer::default_signers;
+use crate::tls::certificate_not_after;
+use crate::variables::resolve_variables;
+use rustls::pki_types::CertificateDer;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// 証明書の有効期限の表示
+const SECONDS_PER_DAY: u64 = 86_400;
+const DATE_LENGTH: usize = 10;
+
+// 正常とみなすステータスコードの上限（4xx・5xxは異常）
+const CLIENT_ERROR_START: u16 = 400;
+
+// エラーメッセージ
+const ERROR_NO_STATUS_PRESETS: &str = "No presets found in {}";
+const ERROR_NO_RESPONSE: &str = "No response received";
+const ERROR_UNHEALTHY_STATUS: &str = "HTTP {}";
+const ERROR_CERTIFICATE_EXPIRED: &str = "certificate expired";
+
+// 表示メッセージ
+const TABLE_COLUMNS: [&str; 4] = ["NAME", "STATUS", "LATENCY", "CERT EXPIRY"];
+const TABLE_SEPARATOR: &str = "  ";
+const EMPTY_CELL: &str = "-";
+const FAILED_STATUS_CELL: &str = "ERR";
+const CERTIFICATE_EXPIRY_CELL: &str = "{1} ({2}d)";
+const CERTIFICATE_EXPIRED_CELL: &str = "{} (expired)";
+const UNHEALTHY_MSG: &str = "FAIL {1}: {2}";
+const STATUS_TOTAL_MSG: &str = "Endpoints: {1}, {2} healthy, {3} unhealthy";
+
+/// 1プリセットの確認結果
+#[derive(Debug, Clone)]
+pub struct EndpointStatus {
+    pub name: String,
+    pub status: Option<u16>,
+    pub latency: Option<Duration>,
+    pub certificate_expiry: Option<SystemTime>,
+    pub failure: Option<String>,
+}
+
+impl EndpointStatus {
+    pub fn healthy(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// 受信したレスポンスのステータス・所要時間と証明書の有効期限を記録するオブザーバー
+struct StatusRecorder {
+    record: Arc<Mutex<StatusRecord>>,
+}
+
+#[derive(Default)]
+struct StatusRecord {
+    status: Option<u16>,
+    latency: Option<Duration>,
+    certificate_expiry: Option<SystemTime>,
+}
+
+impl ResponseObserver for StatusRecorder {
+    fn on_certificates(&self, _config: &Config, chain: &[CertificateDer<'static>]) {
+        if let Ok(mut record) = self.record.lock() {
+            record.certificate_expiry = chain.first().and_then(certificate_not_after);
+        }
+    }
+
+    fn on_response(
+        &self,
+        _config: &Config,
+        response: &ResponseInfo,
+        timing: &TimingInfo,
+        _body: &str,
+    ) {
+        if let Ok(mut record) = self.record.lock() {
+            record.status = Some(response.status().as_u16());
+            record.latency = Some(timing.total_time);
+        }
+    }
+}
+
+/// 設定ファイルのプリセット（[status] があればそのグループ）を並列に確認
+pub fn run_status_checks(
+    config_path: &str,
+    identity: Option<&str>,
+    environment: Option<&str>,
+) -> Result<Vec<EndpointStatus>, Box<dyn Error>> {
+    let presets = load_status_presets(config_path, identity, environment)?;
+    if presets.is_empty() {
+        return Err(ERROR_NO_STATUS_PRESETS.replace("{}", config_path).into());
+    }
+
+    let results = thread::scope(|scope| {
+        let handles: Vec<_> = presets
+            .into_iter()
+            .map(|(name, config)| scope.spawn(move || check_endpoint(name, config)))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    });
+
+    Ok(results)
+}
+
+/// 1プリセットのリクエストを送信して正常かを判定
+///
+/// レスポンスは表示せず、繰り返し送信の設定（--watch・--until・--rps）は無視して1回だけ送信する。
+fn check_endpoint(name: String, mut config: Config) -> EndpointStatus {
+    config.quiet = QUIET_BODY;
+    config.verbose = false;
+    config.timing = false;
+    config.no_input = true;
+    config.watch = None;
+    config.until = None;
+    config.rps = None;
+
+    let record = Arc::new(Mutex::new(StatusRecord::default()));
+    let recorder = StatusRecorder {
+        record: Arc::clone(&record),
+    };
+    let outcome = resolve_variables(&mut config)
+        .and_then(|()| execute_request_with(config, default_signers(), vec![Box::new(recorder)]));
+
+    let record = record
+        .lock()
+        .map(|mut record| std::mem::take(&mut *record))
+        .unwrap_or_default();
+    let failure = match (outcome, record.status) {
+        (_, Some(status)) if status >= CLIENT_ERROR_START => {
+            Some(ERROR_UNHEALTHY_STATUS.replace("{}", &status.to_string()))
+        }
+        (Err(e), _) => Some(e.to_string()),
+        (Ok(()), None) => Some(ERROR_NO_RESPONSE.to_string()),
+        (Ok(()), Some(_)) => record
+            .certificate_expiry
+            .filter(|expiry| *expiry <= SystemTime::now())
+            .map(|_| ERROR_CERTIFICATE_EXPIRED.to_string()),
+    };
+
+    EndpointStatus {
+        name,
+        status: record.status,
+        latency: record.latency,
+        certificate_expiry: record.certificate_expiry,
+        failure,
+    }
+}
+
+/// 確認結果を表形式で表示（異常なエンドポイントは理由を続けて表示）
+pub fn display_status_table(results: &[EndpointStatus]) {
+    let rows: Vec<[String; 4]> = results
+        .iter()
+        .map(|result| {
+            [
+                result.name.clone(),
+                result
+                    .status
+                    .map(|status| status.to_string())
+                    .unwrap_or_else(|| FAILED_STATUS_CELL.to_string()),
+                result
+                    .latency
+                    .map(|latency| format!("{:.0?}", latency))
+                    .unwrap_or_else(|| EMPTY_CELL.to_string()),
+                result
+                    .certificate_expiry
+                    .map(format_certificate_expiry)
+                    .unwrap_or_else(|| EMPTY_CELL.to_string()),
+            ]
+        })
+        .collect();
+
+    let mut widths = TABLE_COLUMNS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    println!("{}", format_row(&TABLE_COLUMNS.map(String::from), &widths));
+    for row in &rows {
+        println!("{}", format_row(row, &widths));
+    }
+
+    for result in results.iter().filter(|result| !result.healthy()) {
+        println!(
+            "{}",
+            UNHEALTHY_MSG
+                .replace("{1}", &result.name)
+                .replace("{2}", result.failure.as_deref().unwrap_or_default())
+        );
+    }
+
+    let healthy = results.iter().filter(|result| result.healthy()).count();
+    println!(
+        "{}",
+        STATUS_TOTAL_MSG
+            .replace("{1}", &results.len().to_string())
+            .replace("{2}", &healthy.to_string())
+            .replace("{3}", &(results.len() - healthy).to_string())
+    );
+}
+
+/// 列幅に合わせて1行を整形（最後の列は詰めない）
+fn format_row(cells: &[String; 4], widths: &[usize; 4]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join(TABLE_SEPARATOR)
+        .trim_end()
+        .to_string()
+}
+
+/// 有効期限の日付と残り日数（YYYY-MM-DD (90d)）
+fn format_certificate_expiry(expiry: SystemTime) -> String {
+    let date: String = humantime::format_rfc3339_seconds(expiry)
+        .to_string()
+        .chars()
+        .take(DATE_LENGTH)
+        .collect();
+
+    match expiry.duration_since(SystemTime::now()) {
+        Ok(remaining) => CERTIFICATE_EXPIRY_CELL
+            .replace("{1}", &date)
+            .replace("{2}", &(remaining.as_secs() / SECONDS_PER_DAY).to_string()),
+        Err(_) => CERTIFICATE_EXPIRED_CELL.replace("{}", &date),
+    }
+}
@@ -0,0 +1,204 @@
+use crate::connection_stats::{display_connection_summary, ConnectionSummary};
+use serde::Serialize;
+use std::error::Error;
+use std::time::Duration;
+
+// 出力形式
+const SUMMARY_FORMAT_TABLE: &str = "table";
+const SUMMARY_FORMAT_JSON: &str = "json";
+const SUMMARY_FORMAT_CSV: &str = "csv";
+
+// CSV
+const CSV_HEADER: &str = "url,status,duration_ms,size,retries,error";
+const CSV_QUOTE: char = '"';
+
+// HTTPステータスコード
+const CLIENT_ERROR_START: u16 = 400;
+
+// 表示関連
+const EMPTY_VALUE_MARK: &str = "-";
+const URL_COLUMN_LABEL: &str = "URL";
+
+// エラーメッセージ
+const ERROR_INVALID_SUMMARY_FORMAT: &str = "Invalid summary format '{}'. Use table, json or csv.";
+
+// 表示メッセージ
+const SUMMARY_HEADER: &str = "--- Summary ---";
+const SUMMARY_TOTAL_MSG: &str = "Total: {1} requests, {2} succeeded, {3} failed in {4}";
+
+/// サマリーの出力形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SummaryFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+/// 1リクエスト分の実行結果
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestSummary {
+    pub url: String,
+    pub status: Option<u16>,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    pub size: usize,
+    pub retries: usize,
+    pub error: Option<String>,
+}
+
+/// JSON形式のサマリー
+#[derive(Serialize)]
+struct RunSummary<'a> {
+    requests: &'a [RequestSummary],
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    elapsed_ms: f64,
+    connections: &'a ConnectionSummary,
+}
+
+impl RequestSummary {
+    /// レスポンスを受信し、ステータスコードが4xx/5xxでなく、所要時間の上限も超えていないか
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none() && self.status.is_some_and(|status| status < CLIENT_ERROR_START)
+    }
+}
+
+/// サマリーの出力形式を解釈（未指定の場合は表形式）
+pub fn parse_summary_format(format: Option<&str>) -> Result<SummaryFormat, Box<dyn Error>> {
+    match format {
+        None | Some(SUMMARY_FORMAT_TABLE) => Ok(SummaryFormat::Table),
+        Some(SUMMARY_FORMAT_JSON) => Ok(SummaryFormat::Json),
+        Some(SUMMARY_FORMAT_CSV) => Ok(SummaryFormat::Csv),
+        Some(format) => Err(ERROR_INVALID_SUMMARY_FORMAT.replace("{}", format).into()),
+    }
+}
+
+/// 複数リクエストの実行結果と集計を表示
+pub fn display_run_summary(
+    requests: &[RequestSummary],
+    elapsed: Duration,
+    connections: &ConnectionSummary,
+    format: SummaryFormat,
+) -> Result<(), Box<dyn Error>> {
+    let succeeded = requests
+        .iter()
+        .filter(|request| request.succeeded())
+        .count();
+
+    match format {
+        SummaryFormat::Table => display_summary_table(requests, succeeded, elapsed, connections),
+        SummaryFormat::Json => {
+            let summary = RunSummary {
+                requests,
+                total: requests.len(),
+                succeeded,
+                failed: requests.len() - succeeded,
+                elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+                connections,
+            };
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        SummaryFormat::Csv => display_summary_csv(requests),
+    }
+
+    Ok(())
+}
+
+/// 表形式で表示
+fn display_summary_table(
+    requests: &[RequestSummary],
+    succeeded: usize,
+    elapsed: Duration,
+    connections: &ConnectionSummary,
+) {
+    let url_width = requests
+        .iter()
+        .map(|request| request.url.len())
+        .max()
+        .unwrap_or_default()
+        .max(URL_COLUMN_LABEL.len());
+
+    eprintln!("{}", SUMMARY_HEADER);
+    eprintln!(
+        "{:<url_width$} {:>6} {:>12} {:>10} {:>7}",
+        URL_COLUMN_LABEL,
+        "Status",
+        "Duration",
+        "Size",
+        "Retries",
+        url_width = url_width
+    );
+
+    for request in requests {
+        let (status, duration) = match request.status {
+            Some(status) => (status.to_string(), format!("{:.2?}", request.duration)),
+            None => (EMPTY_VALUE_MARK.to_string(), EMPTY_VALUE_MARK.to_string()),
+        };
+        eprintln!(
+            "{:<url_width$} {:>6} {:>12} {:>10} {:>7}",
+            request.url,
+            status,
+            duration,
+            request.size,
+            request.retries,
+            url_width = url_width
+        );
+        if let Some(error) = &request.error {
+            eprintln!("  {}", error);
+        }
+    }
+
+    eprintln!(
+        "{}",
+        SUMMARY_TOTAL_MSG
+            .replace("{1}", &requests.len().to_string())
+            .replace("{2}", &succeeded.to_string())
+            .replace("{3}", &(requests.len() - succeeded).to_string())
+            .replace("{4}", &format!("{:.2?}", elapsed))
+    );
+    display_connection_summary(connections);
+    eprintln!();
+}
+
+/// CSV形式で表示（1行1リクエスト、接続の集計は含まない）
+fn display_summary_csv(requests: &[RequestSummary]) {
+    println!("{}", CSV_HEADER);
+
+    for request in requests {
+        println!(
+            "{},{},{:.3},{},{},{}",
+            csv_field(&request.url),
+            request
+                .status
+                .map(|status| status.to_string())
+                .unwrap_or_default(),
+            request.duration.as_secs_f64() * 1000.0,
+            request.size,
+            request.retries,
+            csv_field(request.error.as_deref().unwrap_or_default())
+        );
+    }
+}
+
+/// CSVのフィールド（カンマ・引用符・改行を含む場合は引用符で囲む）
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', CSV_QUOTE, '\n', '\r']) {
+        format!(
+            "{}{}{}",
+            CSV_QUOTE,
+            value.replace(CSV_QUOTE, "\"\""),
+            CSV_QUOTE
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+/// 所要時間をミリ秒で出力
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,173 @@
+use crate::assertion::PresetResult;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// レポート形式
+const REPORT_FORMAT_JUNIT: &str = "junit";
+const REPORT_FORMAT_TAP: &str = "tap";
+const REPORT_TARGET_SEPARATOR: char = ':';
+
+// JUnit XML
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+const JUNIT_SUITES_NAME: &str = "rs-w3r";
+
+// TAP
+const TAP_VERSION: &str = "TAP version 13";
+
+// エラーメッセージ
+const ERROR_INVALID_REPORT: &str = "Invalid report '{}'. Use junit:<FILE>, tap or tap:<FILE>.";
+
+/// テスト結果のレポート形式
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestReport {
+    /// JUnit互換のXMLをファイルに出力
+    Junit(String),
+    /// Test Anything Protocolで出力（ファイル未指定の場合は標準出力）
+    Tap(Option<String>),
+}
+
+impl TestReport {
+    /// 標準出力に書き出すか（通常の結果表示の代わりに出力する）
+    pub fn writes_to_stdout(&self) -> bool {
+        matches!(self, TestReport::Tap(None))
+    }
+}
+
+/// --report の指定を解釈（junit:<FILE>、tap、tap:<FILE>）
+pub fn parse_test_report(report: &str) -> Result<TestReport, Box<dyn Error>> {
+    if report == REPORT_FORMAT_TAP {
+        return Ok(TestReport::Tap(None));
+    }
+
+    match report.split_once(REPORT_TARGET_SEPARATOR) {
+        Some((REPORT_FORMAT_JUNIT, path)) if !path.is_empty() => {
+            Ok(TestReport::Junit(path.to_string()))
+        }
+        Some((REPORT_FORMAT_TAP, path)) if !path.is_empty() => {
+            Ok(TestReport::Tap(Some(path.to_string())))
+        }
+        _ => Err(ERROR_INVALID_REPORT.replace("{}", report).into()),
+    }
+}
+
+/// テスト結果をレポートとして出力
+pub fn write_test_report(
+    report: &TestReport,
+    results: &[PresetResult],
+) -> Result<(), Box<dyn Error>> {
+    match report {
+        TestReport::Junit(path) => write_report_file(path, &render_junit(results)),
+        TestReport::Tap(Some(path)) => write_report_file(path, &render_tap(results)),
+        TestReport::Tap(None) => {
+            print!("{}", render_tap(results));
+            Ok(())
+        }
+    }
+}
+
+/// レポートをファイルに書き出す（親ディレクトリがなければ作成）
+fn write_report_file(path: &str, contents: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// JUnit XMLを生成（プリセットごとにtestsuite、アサーションごとにtestcase）
+fn render_junit(results: &[PresetResult]) -> String {
+    let total: usize = results.iter().map(|result| result.checks.len()).sum();
+    let failures: usize = results.iter().map(|result| result.failed_count()).sum();
+    let time: f64 = results
+        .iter()
+        .map(|result| result.duration.as_secs_f64())
+        .sum();
+
+    let mut xml = String::new();
+    xml.push_str(XML_DECLARATION);
+    xml.push('\n');
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        JUNIT_SUITES_NAME, total, failures, time
+    ));
+
+    for result in results {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+            escape_xml(&result.name),
+            result.checks.len(),
+            result.failed_count(),
+            result.duration.as_secs_f64()
+        ));
+
+        for check in &result.checks {
+            let testcase = format!(
+                "    <testcase classname=\"{}\" name=\"{}\"",
+                escape_xml(&result.name),
+                escape_xml(&check.name)
+            );
+            match &check.failure {
+                Some(failure) => xml.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\"/>\n    </testcase>\n",
+                    testcase,
+                    escape_xml(failure)
+                )),
+                None => xml.push_str(&format!("{}/>\n", testcase)),
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// TAPを生成（アサーションごとに1行、失敗時はYAMLブロックで理由を付与）
+fn render_tap(results: &[PresetResult]) -> String {
+    let total: usize = results.iter().map(|result| result.checks.len()).sum();
+
+    let mut tap = format!("{}\n1..{}\n", TAP_VERSION, total);
+    let checks = results
+        .iter()
+        .flat_map(|result| result.checks.iter().map(move |check| (result, check)));
+
+    for (number, (result, check)) in checks.enumerate() {
+        let description = format!("{}: {}", result.name, check.name).replace('#', "\\#");
+        match &check.failure {
+            Some(failure) => {
+                tap.push_str(&format!("not ok {} - {}\n", number + 1, description));
+                tap.push_str("  ---\n");
+                tap.push_str(&format!(
+                    "  message: {}\n",
+                    serde_json::to_string(failure).unwrap_or_default()
+                ));
+                tap.push_str("  ...\n");
+            }
+            None => tap.push_str(&format!("ok {} - {}\n", number + 1, description)),
+        }
+    }
+
+    tap
+}
+
+/// XMLの属性値として使えるようにエスケープ
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
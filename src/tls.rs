@@ -0,0 +1,357 @@
+use crate::client::Config;
+use crate::client_key::{decrypt_private_key, load_pkcs12};
+use crate::connection_stats::ConnectionStats;
+use crate::util::hex;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, KeyLog, RootCertStore, SignatureScheme};
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+// ALPN
+const ALPN_H2: &[u8] = b"h2";
+const ALPN_HTTP11: &[u8] = b"http/1.1";
+
+// PEM
+const PEM_BEGIN_CERTIFICATE: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END_CERTIFICATE: &str = "-----END CERTIFICATE-----";
+const PEM_LINE_WIDTH: usize = 64;
+
+// クライアント証明書の形式（--cert-type）
+const CERT_TYPE_PEM: &str = "PEM";
+const CERT_TYPE_PKCS12: [&str; 3] = ["P12", "PKCS12", "PFX"];
+
+// エラーメッセージ
+const ERROR_INVALID_CERT_TYPE: &str = "Invalid --cert-type '{}'. Use PEM or P12.";
+const ERROR_KEY_WITH_PKCS12: &str = "--key cannot be used with --cert-type P12 (the private key is read from the PKCS#12 file)";
+const ERROR_KEY_WITHOUT_CERT: &str = "--key requires --cert";
+const ERROR_NO_CLIENT_CERTIFICATE: &str = "No certificate found in {}";
+const ERROR_NO_PRIVATE_KEY: &str = "No private key found in {}. Specify it with --key.";
+const ERROR_INVALID_CLIENT_IDENTITY: &str = "Invalid client certificate or key: {}";
+const ERROR_READ_CLIENT_IDENTITY: &str = "Failed to read {1}: {2}";
+
+// 表示メッセージ
+const CERTIFICATE_HEADER: &str = "--- Certificate Chain ---";
+const CERTIFICATE_INDEX_MSG: &str = "[{}]";
+const CERTIFICATE_SUBJECT_MSG: &str = "  Subject: {}";
+const CERTIFICATE_ISSUER_MSG: &str = "  Issuer: {}";
+const CERTIFICATE_SANS_MSG: &str = "  SANs: {}";
+const CERTIFICATE_VALIDITY_MSG: &str = "  Validity: {1} - {2}";
+const CERTIFICATE_PARSE_ERROR_MSG: &str = "  <unable to parse certificate: {}>";
+const CERTIFICATE_SAVED_MSG: &str = "Saved {1} certificate(s) to {2}";
+const NO_CERTIFICATES_MSG: &str = "No certificates were presented (not a TLS connection?)";
+
+// 鍵ログ
+pub const SSLKEYLOGFILE_ENV: &str = "SSLKEYLOGFILE";
+
+/// ハンドシェイクで提示された証明書チェーン（リーフ証明書が先頭）
+pub type CertificateChain = Arc<Mutex<Vec<CertificateDer<'static>>>>;
+
+/// 検証を標準の検証器に委譲しつつ、提示された証明書チェーンを記録する検証器
+///
+/// セッションを再開したハンドシェイクでは証明書が提示されず呼ばれないため、
+/// 呼ばれた回数を完全なハンドシェイクの数として記録する。
+#[derive(Debug)]
+struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    chain: CertificateChain,
+    connection_stats: ConnectionStats,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.connection_stats.record_full_handshake();
+        if let Ok(mut chain) = self.chain.lock() {
+            chain.clear();
+            chain.push(end_entity.clone().into_owned());
+            chain.extend(intermediates.iter().map(|cert| cert.clone().into_owned()));
+        }
+
+        self.inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// TLSのセッション鍵をNSS Key Log形式でファイルに追記する（Wiresharkでの復号用）
+#[derive(Debug)]
+struct KeyLogWriter {
+    file: Mutex<File>,
+}
+
+impl KeyLog for KeyLogWriter {
+    fn log(&self, label: &str, client_random: &[u8], secret: &[u8]) {
+        if let Ok(mut file) = self.file.lock() {
+            // 書き込みに失敗しても通信は継続
+            let _ = writeln!(file, "{} {} {}", label, hex(client_random), hex(secret));
+        }
+    }
+}
+
+/// セッション鍵の書き出し先を開く（既存のファイルには追記）
+pub fn open_key_log(file_path: &str) -> Result<Arc<dyn KeyLog>, Box<dyn Error>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    Ok(Arc::new(KeyLogWriter {
+        file: Mutex::new(file),
+    }))
+}
+
+/// クライアント証明書（相互TLS）の証明書チェーンと秘密鍵
+pub struct ClientIdentity {
+    certificates: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+/// --cert・--key からクライアント証明書を読み込む（--cert を指定していない場合はNone）
+///
+/// PEM形式とPKCS#12形式に対応。PEMで --key を省略した場合は --cert のファイルから秘密鍵を探す。
+/// 秘密鍵が暗号化されている場合はパスフレーズを入力する。
+pub fn load_client_identity(config: &Config) -> Result<Option<ClientIdentity>, Box<dyn Error>> {
+    let Some(cert_path) = &config.cert else {
+        return match config.key {
+            Some(_) => Err(ERROR_KEY_WITHOUT_CERT.into()),
+            None => Ok(None),
+        };
+    };
+
+    match config.cert_type.as_deref() {
+        None => {}
+        Some(cert_type) if cert_type.eq_ignore_ascii_case(CERT_TYPE_PEM) => {}
+        Some(cert_type)
+            if CERT_TYPE_PKCS12
+                .iter()
+                .any(|name| cert_type.eq_ignore_ascii_case(name)) =>
+        {
+            if config.key.is_some() {
+                return Err(ERROR_KEY_WITH_PKCS12.into());
+            }
+            let (certificates, key) = load_pkcs12(&read_identity_file(cert_path)?, cert_path)?;
+            return Ok(Some(ClientIdentity { certificates, key }));
+        }
+        Some(cert_type) => return Err(ERROR_INVALID_CERT_TYPE.replace("{}", cert_type).into()),
+    }
+
+    let cert_pem = read_identity_file(cert_path)?;
+    let certificates = CertificateDer::pem_slice_iter(&cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ERROR_INVALID_CLIENT_IDENTITY.replace("{}", &e.to_string()))?;
+    if certificates.is_empty() {
+        return Err(ERROR_NO_CLIENT_CERTIFICATE.replace("{}", cert_path).into());
+    }
+
+    let key_path = config.key.as_deref().unwrap_or(cert_path);
+    let key_pem = match &config.key {
+        Some(key_path) => read_identity_file(key_path)?,
+        None => cert_pem,
+    };
+    let key = match decrypt_private_key(&key_pem, key_path)? {
+        Some(key) => key,
+        None => PrivateKeyDer::from_pem_slice(&key_pem)
+            .map_err(|_| ERROR_NO_PRIVATE_KEY.replace("{}", key_path))?,
+    };
+
+    Ok(Some(ClientIdentity { certificates, key }))
+}
+
+/// 証明書・秘密鍵のファイルを読み込む（エラーにはパスを含める）
+fn read_identity_file(path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    fs::read(path).map_err(|e| {
+        ERROR_READ_CLIENT_IDENTITY
+            .replace("{1}", path)
+            .replace("{2}", &e.to_string())
+            .into()
+    })
+}
+
+/// 証明書チェーンを記録するTLS設定を作成（クライアント証明書があれば提示する）
+pub fn build_recording_tls_config(
+    client_identity: Option<ClientIdentity>,
+    connection_stats: &ConnectionStats,
+) -> Result<(ClientConfig, CertificateChain), Box<dyn Error>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let mut root_store = RootCertStore::empty();
+    root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(root_store), provider.clone())
+        .build()?;
+    let chain: CertificateChain = Arc::new(Mutex::new(Vec::new()));
+    let verifier = RecordingVerifier {
+        inner,
+        chain: Arc::clone(&chain),
+        connection_stats: connection_stats.clone(),
+    };
+
+    let tls_config_builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier));
+    let mut tls_config = match client_identity {
+        Some(identity) => tls_config_builder
+            .with_client_auth_cert(identity.certificates, identity.key)
+            .map_err(|e| ERROR_INVALID_CLIENT_IDENTITY.replace("{}", &e.to_string()))?,
+        None => tls_config_builder.with_no_client_auth(),
+    };
+    tls_config.alpn_protocols = vec![ALPN_H2.to_vec(), ALPN_HTTP11.to_vec()];
+
+    Ok((tls_config, chain))
+}
+
+/// 証明書チェーンをPEM形式で保存し、概要を表示（show_summary が false の場合は保存のみ）
+pub fn dump_certificate_chain(
+    chain: &CertificateChain,
+    file_path: &str,
+    show_summary: bool,
+) -> Result<(), Box<dyn Error>> {
+    let certificates = chain.lock().map(|chain| chain.clone()).unwrap_or_default();
+
+    if certificates.is_empty() {
+        eprintln!("{}", NO_CERTIFICATES_MSG);
+        return Ok(());
+    }
+
+    let mut file = File::create(file_path)?;
+    for certificate in &certificates {
+        file.write_all(encode_pem(certificate).as_bytes())?;
+    }
+
+    if !show_summary {
+        return Ok(());
+    }
+
+    eprintln!("{}", CERTIFICATE_HEADER);
+    for (index, certificate) in certificates.iter().enumerate() {
+        eprintln!("{}", CERTIFICATE_INDEX_MSG.replace("{}", &index.to_string()));
+        display_certificate_summary(certificate);
+    }
+    eprintln!(
+        "{}",
+        CERTIFICATE_SAVED_MSG
+            .replace("{1}", &certificates.len().to_string())
+            .replace("{2}", file_path)
+    );
+    eprintln!();
+
+    Ok(())
+}
+
+/// 証明書をPEM形式に変換
+fn encode_pem(certificate: &CertificateDer<'_>) -> String {
+    let encoded = STANDARD.encode(certificate.as_ref());
+    let mut pem = String::from(PEM_BEGIN_CERTIFICATE);
+    pem.push('\n');
+
+    for line in encoded.as_bytes().chunks(PEM_LINE_WIDTH) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+
+    pem.push_str(PEM_END_CERTIFICATE);
+    pem.push('\n');
+    pem
+}
+
+/// 証明書の有効期限（解析できない場合はNone）
+pub fn certificate_not_after(certificate: &CertificateDer<'_>) -> Option<SystemTime> {
+    let (_, parsed) = X509Certificate::from_der(certificate.as_ref()).ok()?;
+    let not_after = u64::try_from(parsed.validity().not_after.timestamp()).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(not_after))
+}
+
+/// 証明書の概要（サブジェクト、発行者、SAN、有効期間）を表示
+fn display_certificate_summary(certificate: &CertificateDer<'_>) {
+    let parsed = match X509Certificate::from_der(certificate.as_ref()) {
+        Ok((_, parsed)) => parsed,
+        Err(e) => {
+            eprintln!("{}", CERTIFICATE_PARSE_ERROR_MSG.replace("{}", &e.to_string()));
+            return;
+        }
+    };
+
+    eprintln!("{}", CERTIFICATE_SUBJECT_MSG.replace("{}", &parsed.subject().to_string()));
+    eprintln!("{}", CERTIFICATE_ISSUER_MSG.replace("{}", &parsed.issuer().to_string()));
+
+    let subject_alt_names = subject_alt_names(&parsed);
+    if !subject_alt_names.is_empty() {
+        eprintln!("{}", CERTIFICATE_SANS_MSG.replace("{}", &subject_alt_names.join(", ")));
+    }
+
+    let validity = parsed.validity();
+    eprintln!(
+        "{}",
+        CERTIFICATE_VALIDITY_MSG
+            .replace("{1}", &validity.not_before.to_string())
+            .replace("{2}", &validity.not_after.to_string())
+    );
+}
+
+/// サブジェクト代替名（DNS名・IPアドレス）を取得
+fn subject_alt_names(certificate: &X509Certificate<'_>) -> Vec<String> {
+    let Ok(Some(extension)) = certificate.subject_alternative_name() else {
+        return Vec::new();
+    };
+
+    extension
+        .value
+        .general_names
+        .iter()
+        .filter_map(|name| match name {
+            GeneralName::DNSName(dns_name) => Some(dns_name.to_string()),
+            GeneralName::IPAddress(bytes) => format_ip_address(bytes),
+            _ => None,
+        })
+        .collect()
+}
+
+/// SANのIPアドレスを表示用の文字列に変換
+fn format_ip_address(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes)
+            .ok()
+            .map(|octets| std::net::Ipv4Addr::from(octets).to_string()),
+        16 => <[u8; 16]>::try_from(bytes)
+            .ok()
+            .map(|octets| std::net::Ipv6Addr::from(octets).to_string()),
+        _ => None,
+    }
+}
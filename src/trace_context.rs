@@ -0,0 +1,128 @@
+use crate::client::{shows_meta, Config};
+use crate::util::hex;
+use reqwest::blocking::RequestBuilder;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::error::Error;
+
+// W3C Trace Context
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+const TRACESTATE_HEADER: &str = "tracestate";
+const TRACEPARENT_AUTO: &str = "auto";
+const TRACEPARENT_VERSION: &str = "00";
+const INVALID_VERSION: &str = "ff";
+const TRACE_FLAGS_SAMPLED: &str = "01";
+const TRACEPARENT_SEPARATOR: char = '-';
+const TRACE_ID_BYTES: usize = 16;
+const PARENT_ID_BYTES: usize = 8;
+const FLAGS_BYTES: usize = 1;
+
+// エラーメッセージ
+const ERROR_INVALID_TRACEPARENT: &str = "Invalid --traceparent '{}'. Use auto or 00-<32 hex trace ID>-<16 hex parent ID>-<2 hex flags>.";
+const ERROR_TRACESTATE_WITHOUT_TRACEPARENT: &str = "--tracestate requires --traceparent.";
+const ERROR_RANDOM: &str = "Failed to generate a random trace ID";
+
+// 表示メッセージ
+const TRACE_ID_MSG: &str = "* Trace ID: {}";
+
+/// traceparent・tracestateヘッダーを付与し、トレースIDを表示（auto はリクエストごとに生成）
+pub fn apply_trace_context(
+    mut request_builder: RequestBuilder,
+    config: &Config,
+) -> Result<RequestBuilder, Box<dyn Error>> {
+    let traceparent = match (
+        config
+            .traceparent
+            .as_deref()
+            .or(default_traceparent(config)),
+        &config.tracestate,
+    ) {
+        (Some(traceparent), _) => traceparent,
+        (None, Some(_)) => return Err(ERROR_TRACESTATE_WITHOUT_TRACEPARENT.into()),
+        (None, None) => return Ok(request_builder),
+    };
+
+    let traceparent = if traceparent == TRACEPARENT_AUTO {
+        generate_traceparent()?
+    } else {
+        validate_traceparent(traceparent)?
+    };
+    let (trace_id, _) = trace_ids(&traceparent).unwrap_or_default();
+    if shows_meta(config) {
+        eprintln!("{}", TRACE_ID_MSG.replace("{}", trace_id));
+    }
+
+    request_builder = request_builder.header(TRACEPARENT_HEADER, &traceparent);
+    if let Some(tracestate) = &config.tracestate {
+        request_builder = request_builder.header(TRACESTATE_HEADER, tracestate);
+    }
+
+    Ok(request_builder)
+}
+
+/// --traceparent を指定しない場合の値（--otel-endpoint ではスパンとサーバー側のトレースをつなぐため自動で生成）
+#[cfg(feature = "otel")]
+fn default_traceparent(config: &Config) -> Option<&'static str> {
+    config.otel_endpoint.is_some().then_some(TRACEPARENT_AUTO)
+}
+
+#[cfg(not(feature = "otel"))]
+fn default_traceparent(_config: &Config) -> Option<&'static str> {
+    None
+}
+
+/// traceparentのトレースIDと親ID
+pub fn trace_ids(traceparent: &str) -> Option<(&str, &str)> {
+    let mut fields = traceparent.split(TRACEPARENT_SEPARATOR).skip(1);
+    Some((fields.next()?, fields.next()?))
+}
+
+/// ランダムなトレースID・親IDでサンプリング対象のtraceparentを生成
+fn generate_traceparent() -> Result<String, Box<dyn Error>> {
+    Ok([
+        TRACEPARENT_VERSION.to_string(),
+        random_hex_id(TRACE_ID_BYTES)?,
+        random_hex_id(PARENT_ID_BYTES)?,
+        TRACE_FLAGS_SAMPLED.to_string(),
+    ]
+    .join(&TRACEPARENT_SEPARATOR.to_string()))
+}
+
+/// すべて0ではないランダムなIDを小文字の16進数で生成
+pub fn random_hex_id(bytes: usize) -> Result<String, Box<dyn Error>> {
+    let rng = SystemRandom::new();
+    let mut id = vec![0u8; bytes];
+    while id.iter().all(|byte| *byte == 0) {
+        rng.fill(&mut id).map_err(|_| ERROR_RANDOM)?;
+    }
+    Ok(hex(&id))
+}
+
+/// 指定されたtraceparentの形式を検証（バージョン・トレースID・親ID・フラグ）
+fn validate_traceparent(traceparent: &str) -> Result<String, Box<dyn Error>> {
+    let invalid = || ERROR_INVALID_TRACEPARENT.replace("{}", traceparent);
+    let fields: Vec<&str> = traceparent.split(TRACEPARENT_SEPARATOR).collect();
+    let [version, trace_id, parent_id, flags] = fields.as_slice() else {
+        return Err(invalid().into());
+    };
+
+    let valid = is_hex_id(version, FLAGS_BYTES)
+        && *version != INVALID_VERSION
+        && is_hex_id(trace_id, TRACE_ID_BYTES)
+        && is_hex_id(parent_id, PARENT_ID_BYTES)
+        && is_hex_id(flags, FLAGS_BYTES)
+        && trace_id.chars().any(|c| c != '0')
+        && parent_id.chars().any(|c| c != '0');
+    if !valid {
+        return Err(invalid().into());
+    }
+
+    Ok(traceparent.to_string())
+}
+
+/// 指定バイト数の小文字の16進数か
+fn is_hex_id(value: &str, bytes: usize) -> bool {
+    value.len() == bytes * 2
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
@@ -0,0 +1,756 @@
+use crate::client::{
+    execute_request_with, load_config_presets, Config, ResponseInfo, TimingInfo, QUIET_BODY,
+};
+use crate::config_include::read_included_value;
+use crate::observer::ResponseObserver;
+use crate::signer::default_signers;
+use crate::variables::resolve_variables;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{stdout, IsTerminal};
+use std::iter;
+use std::sync::{Arc, Mutex};
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+// 設定ファイルのキー
+const PRESET_TABLE: &str = "preset";
+const KEY_URL: &str = "url";
+const KEY_METHOD: &str = "method";
+const KEY_HEADERS: &str = "headers";
+const KEY_JSON: &str = "json";
+
+// キー操作（Ctrl と組み合わせる）
+const KEY_SEND: char = 's';
+const KEY_WRITE: char = 'w';
+const KEY_QUIT: char = 'q';
+const KEY_INTERRUPT: char = 'c';
+
+// 画面のレイアウト
+const PRESET_LIST_WIDTH: u16 = 28;
+const METHOD_WIDTH: u16 = 12;
+const SINGLE_LINE_HEIGHT: u16 = 3;
+const HEADERS_HEIGHT: u16 = 6;
+const BODY_HEIGHT: u16 = 8;
+const RESPONSE_PAGE_LINES: u16 = 10;
+
+// 表示メッセージ
+const PRESETS_TITLE: &str = " Presets ";
+const METHOD_TITLE: &str = " Method ";
+const URL_TITLE: &str = " URL ";
+const HEADERS_TITLE: &str = " Headers (Name: value per line) ";
+const BODY_TITLE: &str = " JSON body ";
+const RESPONSE_TITLE: &str = " Response ";
+const NEW_REQUEST_ITEM: &str = "(new request)";
+const INCLUDED_PRESET_ITEM: &str = "{} (included)";
+const HELP_MSG: &str =
+    "Tab: next field  Enter: load preset  Ctrl+S: send  Ctrl+W: write preset  Ctrl+Q: quit";
+const SAVE_HELP_MSG: &str = "Enter: write  Esc: cancel";
+const SAVE_PROMPT_MSG: &str = "Preset name: ";
+const LOADED_MSG: &str = "Loaded preset '{}'";
+const SENDING_MSG: &str = "Sending {1} {2} ...";
+const SAVED_MSG: &str = "Saved preset '{1}' to {2}";
+const RESPONSE_STATUS_MSG: &str = "{1} {2}  ({3} ms)";
+const RESPONSE_HEADER_MSG: &str = "{1}: {2}";
+const REQUEST_FAILED_MSG: &str = "Error: {}";
+
+// エラーメッセージ
+const ERROR_NOT_A_TERMINAL: &str = "The tui command requires an interactive terminal.";
+const ERROR_PRESET_TABLE: &str = "'{}' has a [preset] entry that is not a table.";
+const ERROR_INCLUDED_PRESET: &str =
+    "Preset '{}' is defined in an included config file. Edit that file instead.";
+const ERROR_NO_RESPONSE: &str = "No response received";
+
+/// 対話的にプリセットを選んでリクエストを編集・送信し、プリセットとして保存
+///
+/// 左にプリセットの一覧、右にメソッド・URL・ヘッダー・ボディの入力欄とレスポンスを表示する。
+pub fn run_request_builder(
+    config_path: &str,
+    identity: Option<&str>,
+    base_config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    if !stdout().is_terminal() {
+        return Err(ERROR_NOT_A_TERMINAL.into());
+    }
+
+    // 保存するプリセットに環境の値が混ざらないよう、環境は重ねずに読み込む
+    let presets = load_config_presets(config_path, identity, None)?;
+    let included = included_presets(config_path)?;
+    let mut app = RequestBuilder::new(config_path, presets, included, base_config);
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// 入力欄の位置（Tab で順に移動）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Presets,
+    Method,
+    Url,
+    Headers,
+    Body,
+    Response,
+}
+
+const FOCUS_ORDER: [Focus; 6] = [
+    Focus::Presets,
+    Focus::Method,
+    Focus::Url,
+    Focus::Headers,
+    Focus::Body,
+    Focus::Response,
+];
+
+impl Focus {
+    fn next(self) -> Self {
+        let index = FOCUS_ORDER
+            .iter()
+            .position(|focus| *focus == self)
+            .unwrap_or(0);
+        FOCUS_ORDER[(index + 1) % FOCUS_ORDER.len()]
+    }
+
+    fn previous(self) -> Self {
+        let index = FOCUS_ORDER
+            .iter()
+            .position(|focus| *focus == self)
+            .unwrap_or(0);
+        FOCUS_ORDER[(index + FOCUS_ORDER.len() - 1) % FOCUS_ORDER.len()]
+    }
+}
+
+/// 編集中の入力欄（カーソルは文字単位の位置）
+#[derive(Debug, Default)]
+struct TextField {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl TextField {
+    fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = chars.len();
+        Self { chars, cursor }
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// カーソルのある行の先頭
+    fn line_start(&self) -> usize {
+        self.chars[..self.cursor]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map_or(0, |index| index + 1)
+    }
+
+    /// カーソルのある行の末尾（改行の位置）
+    fn line_end(&self) -> usize {
+        self.chars[self.cursor..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(self.chars.len(), |index| self.cursor + index)
+    }
+
+    /// カーソルの行と表示上の列（全角文字は2列）
+    fn cursor_position(&self) -> (u16, u16) {
+        let row = self.chars[..self.cursor]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count();
+        let before_cursor: String = self.chars[self.line_start()..self.cursor].iter().collect();
+        (row as u16, Span::raw(before_cursor).width() as u16)
+    }
+
+    /// 上下の行へ移動（同じ文字位置、行が短い場合は行末）
+    fn move_vertical(&mut self, up: bool) {
+        let column = self.cursor - self.line_start();
+        if up {
+            let start = self.line_start();
+            if start == 0 {
+                return;
+            }
+            self.cursor = start - 1;
+        } else {
+            let end = self.line_end();
+            if end == self.chars.len() {
+                return;
+            }
+            self.cursor = end + 1;
+        }
+        self.cursor = (self.line_start() + column).min(self.line_end());
+    }
+
+    /// キー入力で編集（multiline の場合のみ Enter で改行）
+    fn handle_key(&mut self, key: KeyEvent, multiline: bool) {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            KeyCode::Enter if multiline => {
+                self.chars.insert(self.cursor, '\n');
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            KeyCode::Home => self.cursor = self.line_start(),
+            KeyCode::End => self.cursor = self.line_end(),
+            KeyCode::Up if multiline => self.move_vertical(true),
+            KeyCode::Down if multiline => self.move_vertical(false),
+            _ => {}
+        }
+    }
+}
+
+/// 画面の状態（選択中のプリセット・入力欄・レスポンス）
+struct RequestBuilder<'a> {
+    config_path: &'a str,
+    base_config: &'a Config,
+    presets: Vec<(String, Config)>,
+    /// include で取り込んだファイルで定義されたプリセット（保存できない）
+    included: HashSet<String>,
+    /// 一覧の選択位置（0は新しいリクエスト）
+    preset_list: ListState,
+    /// 入力欄に読み込んだプリセットの名前（新しいリクエストの場合はNone）
+    source: Option<String>,
+    /// 入力欄に読み込んだ時点（または最後に保存した時点）の設定
+    original: Config,
+    method: TextField,
+    url: TextField,
+    headers: TextField,
+    body: TextField,
+    focus: Focus,
+    response: Vec<String>,
+    response_scroll: u16,
+    /// 保存するプリセット名の入力中はSome
+    save_name: Option<TextField>,
+    message: String,
+}
+
+impl<'a> RequestBuilder<'a> {
+    fn new(
+        config_path: &'a str,
+        presets: Vec<(String, Config)>,
+        included: HashSet<String>,
+        base_config: &'a Config,
+    ) -> Self {
+        let mut builder = Self {
+            config_path,
+            base_config,
+            presets,
+            included,
+            preset_list: ListState::default().with_selected(Some(0)),
+            source: None,
+            original: base_config.clone(),
+            method: TextField::default(),
+            url: TextField::default(),
+            headers: TextField::default(),
+            body: TextField::default(),
+            focus: Focus::Presets,
+            response: Vec::new(),
+            response_scroll: 0,
+            save_name: None,
+            message: String::new(),
+        };
+        builder.load(None, base_config.clone());
+        builder
+    }
+
+    /// キー入力を処理し、Ctrl+Q（Ctrl+C）で終了
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if self.save_name.is_some() {
+                self.handle_save_key(key);
+                continue;
+            }
+
+            let control = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char(KEY_QUIT | KEY_INTERRUPT) if control => return Ok(()),
+                KeyCode::Char(KEY_SEND) if control => {
+                    let config = self.form_config();
+                    self.message = SENDING_MSG
+                        .replace("{1}", &config.method)
+                        .replace("{2}", &config.url);
+                    terminal.draw(|frame| self.draw(frame))?;
+                    self.send(config);
+                    // 送信中に端末へ直接出力された表示を消して描き直す
+                    terminal.clear()?;
+                }
+                KeyCode::Char(KEY_WRITE) if control => {
+                    self.save_name =
+                        Some(TextField::new(self.source.as_deref().unwrap_or_default()));
+                }
+                KeyCode::Tab => self.focus = self.focus.next(),
+                KeyCode::BackTab => self.focus = self.focus.previous(),
+                _ => self.handle_focus_key(key),
+            }
+        }
+    }
+
+    /// 選択中の入力欄へのキー入力
+    fn handle_focus_key(&mut self, key: KeyEvent) {
+        match self.focus {
+            Focus::Presets => match key.code {
+                KeyCode::Up => self.select_preset(-1),
+                KeyCode::Down => self.select_preset(1),
+                KeyCode::Enter => self.load_selected(),
+                _ => {}
+            },
+            Focus::Method | Focus::Url if key.code == KeyCode::Enter => {
+                self.focus = self.focus.next();
+            }
+            Focus::Method => self.method.handle_key(key, false),
+            Focus::Url => self.url.handle_key(key, false),
+            Focus::Headers => self.headers.handle_key(key, true),
+            Focus::Body => self.body.handle_key(key, true),
+            Focus::Response => {
+                self.response_scroll = match key.code {
+                    KeyCode::Up => self.response_scroll.saturating_sub(1),
+                    KeyCode::Down => self.response_scroll.saturating_add(1),
+                    KeyCode::PageUp => self.response_scroll.saturating_sub(RESPONSE_PAGE_LINES),
+                    KeyCode::PageDown => self.response_scroll.saturating_add(RESPONSE_PAGE_LINES),
+                    KeyCode::Home => 0,
+                    _ => self.response_scroll,
+                }
+                .min(self.response.len().saturating_sub(1) as u16);
+            }
+        }
+    }
+
+    /// 保存するプリセット名の入力
+    fn handle_save_key(&mut self, key: KeyEvent) {
+        let Some(name) = &mut self.save_name else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.save_name = None,
+            KeyCode::Enter => {
+                let name = name.text().trim().to_string();
+                self.save_name = None;
+                if name.is_empty() {
+                    return;
+                }
+                self.message = match self.save(&name) {
+                    Ok(()) => SAVED_MSG
+                        .replace("{1}", &name)
+                        .replace("{2}", self.config_path),
+                    Err(e) => REQUEST_FAILED_MSG.replace("{}", &e.to_string()),
+                };
+            }
+            _ => name.handle_key(key, false),
+        }
+    }
+
+    /// 一覧の選択位置を移動
+    fn select_preset(&mut self, offset: isize) {
+        let selected = self.preset_list.selected().unwrap_or(0);
+        let selected = selected
+            .saturating_add_signed(offset)
+            .min(self.presets.len());
+        self.preset_list.select(Some(selected));
+    }
+
+    /// 一覧で選択したプリセット（または新しいリクエスト）を入力欄に読み込む
+    fn load_selected(&mut self) {
+        let selected = self.preset_list.selected().unwrap_or(0);
+        match selected
+            .checked_sub(1)
+            .and_then(|index| self.presets.get(index))
+        {
+            Some((name, config)) => {
+                self.message = LOADED_MSG.replace("{}", name);
+                self.load(Some(name.clone()), config.clone());
+            }
+            None => {
+                self.message.clear();
+                self.load(None, self.base_config.clone());
+            }
+        }
+        self.focus = Focus::Method;
+    }
+
+    fn load(&mut self, source: Option<String>, config: Config) {
+        self.method = TextField::new(&config.method);
+        self.url = TextField::new(&config.url);
+        self.headers = TextField::new(&config.headers.as_deref().unwrap_or_default().join("\n"));
+        self.body = TextField::new(config.json.as_deref().unwrap_or_default());
+        self.source = source;
+        self.original = config;
+    }
+
+    /// 入力欄の値を反映した設定
+    fn form_config(&self) -> Config {
+        let mut config = self.original.clone();
+
+        let method = self.method.text().trim().to_uppercase();
+        if !method.is_empty() {
+            config.method = method;
+        }
+        config.url = self.url.text().trim().to_string();
+
+        let headers: Vec<String> = self
+            .headers
+            .text()
+            .lines()
+            .map(str::trim)
+            .filter(|header| !header.is_empty())
+            .map(str::to_string)
+            .collect();
+        config.headers = (!headers.is_empty()).then_some(headers);
+
+        let body = self.body.text();
+        config.json = (!body.trim().is_empty()).then_some(body);
+
+        config
+    }
+
+    /// リクエストを送信し、レスポンスを表示用の行にする（失敗しても編集を続ける）
+    ///
+    /// 画面を崩さないよう、通常の表示は行わず（status サブコマンドと同じ）オブザーバーで受け取る。
+    fn send(&mut self, mut config: Config) {
+        config.quiet = QUIET_BODY;
+        config.verbose = false;
+        config.timing = false;
+        config.no_input = true;
+        config.watch = None;
+        config.until = None;
+        config.rps = None;
+
+        let record = Arc::new(Mutex::new(Vec::new()));
+        let recorder = ResponseRecorder {
+            lines: Arc::clone(&record),
+        };
+        let outcome = resolve_variables(&mut config).and_then(|()| {
+            execute_request_with(config, default_signers(), vec![Box::new(recorder)])
+        });
+
+        let mut lines = record
+            .lock()
+            .map(|mut lines| std::mem::take(&mut *lines))
+            .unwrap_or_default();
+        match outcome {
+            Err(e) => lines.push(REQUEST_FAILED_MSG.replace("{}", &e.to_string())),
+            Ok(()) if lines.is_empty() => lines.push(ERROR_NO_RESPONSE.to_string()),
+            Ok(()) => {}
+        }
+
+        self.response = lines;
+        self.response_scroll = 0;
+        self.message.clear();
+    }
+
+    /// 入力欄の値をプリセットとして保存し、一覧を更新
+    fn save(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let config = self.form_config();
+        save_preset(
+            self.config_path,
+            self.source.as_deref(),
+            name,
+            &self.original,
+            &config,
+            &self.included,
+        )?;
+
+        match self.presets.iter_mut().find(|(preset, _)| preset == name) {
+            Some((_, preset)) => *preset = config.clone(),
+            None => {
+                self.presets.push((name.to_string(), config.clone()));
+                self.presets.sort_by(|(a, _), (b, _)| a.cmp(b));
+            }
+        }
+        let index = self.presets.iter().position(|(preset, _)| preset == name);
+        self.preset_list.select(index.map(|index| index + 1));
+
+        // 保存した内容を基準にする（続けて保存しても同じ変更を書き込まない）
+        self.source = Some(name.to_string());
+        self.original = config;
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, message_area, help_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, form_area] =
+            Layout::horizontal([Constraint::Length(PRESET_LIST_WIDTH), Constraint::Min(0)])
+                .areas(main_area);
+        let [request_area, headers_area, body_area, response_area] = Layout::vertical([
+            Constraint::Length(SINGLE_LINE_HEIGHT),
+            Constraint::Length(HEADERS_HEIGHT),
+            Constraint::Length(BODY_HEIGHT),
+            Constraint::Min(0),
+        ])
+        .areas(form_area);
+        let [method_area, url_area] =
+            Layout::horizontal([Constraint::Length(METHOD_WIDTH), Constraint::Min(0)])
+                .areas(request_area);
+
+        let items: Vec<ListItem> = iter::once(NEW_REQUEST_ITEM.to_string())
+            .chain(self.presets.iter().map(|(name, _)| {
+                if self.included.contains(name) {
+                    INCLUDED_PRESET_ITEM.replace("{}", name)
+                } else {
+                    name.clone()
+                }
+            }))
+            .map(ListItem::new)
+            .collect();
+        let list = List::new(items)
+            .block(self.block(PRESETS_TITLE, Focus::Presets))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.preset_list);
+
+        let fields = [
+            (&self.method, METHOD_TITLE, Focus::Method, method_area),
+            (&self.url, URL_TITLE, Focus::Url, url_area),
+            (&self.headers, HEADERS_TITLE, Focus::Headers, headers_area),
+            (&self.body, BODY_TITLE, Focus::Body, body_area),
+        ];
+        for (field, title, focus, area) in fields {
+            let focused = self.save_name.is_none() && self.focus == focus;
+            draw_field(frame, field, self.block(title, focus), area, focused);
+        }
+
+        let response: Vec<Line> = self
+            .response
+            .iter()
+            .enumerate()
+            .map(|(index, line)| match index {
+                0 => Line::styled(line.as_str(), Style::new().add_modifier(Modifier::BOLD)),
+                _ => Line::raw(line.as_str()),
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(response)
+                .block(self.block(RESPONSE_TITLE, Focus::Response))
+                .scroll((self.response_scroll, 0)),
+            response_area,
+        );
+
+        let help_style = Style::new().fg(Color::DarkGray);
+        match &self.save_name {
+            Some(name) => {
+                let [prompt_area, name_area] = Layout::horizontal([
+                    Constraint::Length(SAVE_PROMPT_MSG.len() as u16),
+                    Constraint::Min(0),
+                ])
+                .areas(message_area);
+                frame.render_widget(Paragraph::new(SAVE_PROMPT_MSG), prompt_area);
+                draw_field(frame, name, Block::new(), name_area, true);
+                frame.render_widget(Paragraph::new(SAVE_HELP_MSG).style(help_style), help_area);
+            }
+            None => {
+                frame.render_widget(Paragraph::new(self.message.as_str()), message_area);
+                frame.render_widget(Paragraph::new(HELP_MSG).style(help_style), help_area);
+            }
+        }
+    }
+
+    /// 枠と見出し（選択中の欄は枠の色を変える）
+    fn block(&self, title: &'static str, focus: Focus) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.save_name.is_none() && self.focus == focus {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+}
+
+/// 入力欄を描画（カーソルが見える位置までスクロールし、選択中ならカーソルを表示）
+fn draw_field(frame: &mut Frame, field: &TextField, block: Block, area: Rect, focused: bool) {
+    let inner = block.inner(area);
+    let (row, column) = field.cursor_position();
+    let scroll_y = row.saturating_sub(inner.height.saturating_sub(1));
+    let scroll_x = column.saturating_sub(inner.width.saturating_sub(1));
+
+    frame.render_widget(
+        Paragraph::new(field.text())
+            .block(block)
+            .scroll((scroll_y, scroll_x)),
+        area,
+    );
+    if focused {
+        frame.set_cursor_position(Position::new(
+            inner.x + column - scroll_x,
+            inner.y + row - scroll_y,
+        ));
+    }
+}
+
+/// 受信したレスポンスを表示用の行にするオブザーバー（複数回受信した場合は最後のレスポンス）
+struct ResponseRecorder {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl ResponseObserver for ResponseRecorder {
+    fn on_response(
+        &self,
+        _config: &Config,
+        response: &ResponseInfo,
+        timing: &TimingInfo,
+        body: &str,
+    ) {
+        let mut lines = vec![RESPONSE_STATUS_MSG
+            .replace("{1}", &format!("{:?}", response.version()))
+            .replace("{2}", &response.status().to_string())
+            .replace("{3}", &timing.total_time.as_millis().to_string())];
+        for (name, value) in response.headers() {
+            lines.push(
+                RESPONSE_HEADER_MSG
+                    .replace("{1}", name.as_str())
+                    .replace("{2}", &String::from_utf8_lossy(value.as_bytes())),
+            );
+        }
+        lines.push(String::new());
+
+        let body = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|value| serde_json::to_string_pretty(&value).ok())
+            .unwrap_or_else(|| body.to_string());
+        lines.extend(body.lines().map(str::to_string));
+
+        if let Ok(mut record) = self.lines.lock() {
+            *record = lines;
+        }
+    }
+}
+
+/// include で取り込んだファイルで定義されたプリセットの名前
+fn included_presets(config_path: &str) -> Result<HashSet<String>, Box<dyn Error>> {
+    let included = read_included_value(config_path)?;
+    Ok(included
+        .get(PRESET_TABLE)
+        .and_then(toml::Value::as_table)
+        .map(|presets| presets.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// 変更したメソッド・URL・ヘッダー・ボディを設定ファイルのプリセットに書き込む
+///
+/// 変更したキーだけを書き換えるため、コメント・キーの順序・他のプリセットはそのまま残る
+/// （暗号化された値も復号して書き戻さない）。新しい名前で保存する場合は元のプリセットのキーを
+/// 引き継ぐ。取り込んだファイルで定義されたプリセットは、書き込み先のファイルが違うため保存しない。
+fn save_preset(
+    config_path: &str,
+    source: Option<&str>,
+    name: &str,
+    original: &Config,
+    config: &Config,
+    included: &HashSet<String>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(preset) = [Some(name), source]
+        .into_iter()
+        .flatten()
+        .find(|preset| included.contains(*preset))
+    {
+        return Err(ERROR_INCLUDED_PRESET.replace("{}", preset).into());
+    }
+
+    let invalid = || ERROR_PRESET_TABLE.replace("{}", config_path);
+    let mut document: DocumentMut = fs::read_to_string(config_path)?.parse()?;
+    let presets = document
+        .entry(PRESET_TABLE)
+        .or_insert_with(|| {
+            let mut presets = Table::new();
+            presets.set_implicit(true);
+            Item::Table(presets)
+        })
+        .as_table_like_mut()
+        .ok_or_else(invalid)?;
+    if !presets.contains_key(name) {
+        let preset = match source.and_then(|source| presets.get(source)) {
+            Some(source_preset) => copy_table(source_preset.as_table_like().ok_or_else(invalid)?),
+            None => Table::new(),
+        };
+        presets.insert(name, Item::Table(preset));
+    }
+    let preset = presets
+        .get_mut(name)
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(invalid)?;
+
+    if config.url != original.url {
+        set_value(preset, KEY_URL, config.url.as_str().into());
+    }
+    if config.method != original.method {
+        set_value(preset, KEY_METHOD, config.method.as_str().into());
+    }
+    if config.headers != original.headers {
+        match &config.headers {
+            Some(headers) => set_value(preset, KEY_HEADERS, Value::Array(headers.iter().collect())),
+            None => {
+                preset.remove(KEY_HEADERS);
+            }
+        }
+    }
+    if config.json != original.json {
+        match &config.json {
+            Some(json) => set_value(preset, KEY_JSON, json.as_str().into()),
+            None => {
+                preset.remove(KEY_JSON);
+            }
+        }
+    }
+
+    fs::write(config_path, document.to_string())?;
+    Ok(())
+}
+
+/// プリセットのキーを新しいテーブルにコピー（コメントと元の位置は引き継がない）
+fn copy_table(source: &dyn TableLike) -> Table {
+    let mut table = Table::new();
+    for (key, item) in source.iter() {
+        let item = match item {
+            Item::Value(value) => {
+                let mut value = value.clone();
+                value.decor_mut().clear();
+                Item::Value(value)
+            }
+            Item::Table(nested) => Item::Table(copy_table(nested)),
+            Item::ArrayOfTables(tables) => {
+                Item::ArrayOfTables(tables.iter().map(|nested| copy_table(nested)).collect())
+            }
+            Item::None => Item::None,
+        };
+        table.insert(key, item);
+    }
+    table
+}
+
+/// 値を書き込む（既存の値の前後の空白・行末のコメントは残す）
+fn set_value(table: &mut dyn TableLike, key: &str, mut value: Value) {
+    if let Some(existing) = table.get(key).and_then(Item::as_value) {
+        *value.decor_mut() = existing.decor().clone();
+    }
+    table.insert(key, Item::Value(value));
+}
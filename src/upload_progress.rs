@@ -0,0 +1,91 @@
+use std::io::{self, stderr, IsTerminal, Read, Write};
+use std::time::{Duration, Instant};
+
+// スピナーの表示
+const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+// サイズの単位
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+// 表示メッセージ
+const UPLOADING_MSG: &str = "\r{1} Sent {2} MB";
+const UPLOADED_MSG: &str = "\r* Sent {1} MB in {2}";
+
+/// 読み込んだバイト数をスピナーとともに標準エラー出力に表示するボディのリーダー
+///
+/// 標準エラー出力が端末でない場合は表示せず、そのまま読み込む。
+pub struct UploadProgress<R> {
+    inner: R,
+    enabled: bool,
+    sent: u64,
+    frame: usize,
+    started: Instant,
+    last_drawn: Option<Instant>,
+    finished: bool,
+}
+
+impl<R: Read> UploadProgress<R> {
+    pub fn new(inner: R, silent: bool) -> Self {
+        Self {
+            inner,
+            enabled: !silent && stderr().is_terminal(),
+            sent: 0,
+            frame: 0,
+            started: Instant::now(),
+            last_drawn: None,
+            finished: false,
+        }
+    }
+
+    /// 前回の表示から一定時間が経過していればスピナーを進めて再表示
+    fn draw(&mut self) {
+        if self
+            .last_drawn
+            .is_some_and(|last_drawn| last_drawn.elapsed() < REDRAW_INTERVAL)
+        {
+            return;
+        }
+
+        self.frame = (self.frame + 1) % SPINNER_FRAMES.len();
+        self.last_drawn = Some(Instant::now());
+        eprint!(
+            "{}",
+            UPLOADING_MSG
+                .replace("{1}", &SPINNER_FRAMES[self.frame].to_string())
+                .replace("{2}", &megabytes(self.sent))
+        );
+        let _ = stderr().flush();
+    }
+
+    /// 読み込みの完了時に合計を表示
+    fn finish(&mut self) {
+        self.finished = true;
+        eprintln!(
+            "{}",
+            UPLOADED_MSG
+                .replace("{1}", &megabytes(self.sent))
+                .replace("{2}", &format!("{:.2?}", self.started.elapsed()))
+        );
+    }
+}
+
+impl<R: Read> Read for UploadProgress<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.sent += read as u64;
+
+        if self.enabled && !self.finished {
+            if read == 0 {
+                self.finish();
+            } else {
+                self.draw();
+            }
+        }
+        Ok(read)
+    }
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.2}", bytes as f64 / BYTES_PER_MB)
+}
@@ -0,0 +1,59 @@
+use ring::hmac;
+use std::error::Error;
+use std::io::{stderr, stdin, BufRead, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ホームディレクトリの環境変数（USERPROFILEはWindows用）
+const HOME_ENV: &str = "HOME";
+const USERPROFILE_ENV: &str = "USERPROFILE";
+
+/// ホームディレクトリ（HOMEもUSERPROFILEも設定されていない場合は None）
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    [HOME_ENV, USERPROFILE_ENV]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .find(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 現在のUNIX時刻（秒）
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// 16進文字列に変換
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// HMAC-SHA256
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let signing_key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&signing_key, message).as_ref().to_vec()
+}
+
+/// X-Amz-Date形式（YYYYMMDD'T'HHMMSS'Z'）の日時
+pub(crate) fn amz_date(time: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .chars()
+        .filter(|c| *c != '-' && *c != ':')
+        .collect()
+}
+
+/// プロンプトを表示して1行読み込む（入力の終わりではNone）
+pub(crate) fn prompt_line(prompt: &str) -> Result<Option<String>, Box<dyn Error>> {
+    eprint!("{}", prompt);
+    stderr().flush()?;
+
+    let mut line = String::new();
+    if stdin().lock().read_line(&mut line)? == 0 {
+        eprintln!();
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
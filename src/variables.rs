@@ -0,0 +1,194 @@
+use crate::client::Config;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{stderr, stdin, BufRead, IsTerminal, Write};
+
+// プレースホルダー
+const VARIABLE_OPEN: &str = "{{";
+const VARIABLE_CLOSE: &str = "}}";
+
+// エラーメッセージ
+const ERROR_MISSING_VARIABLES: &str = "Missing values for variables: {}";
+const ERROR_MISSING_PARAMS: &str = "Missing required parameters: {} (use --param name=value)";
+
+// 表示メッセージ
+const VARIABLE_PROMPT_MSG: &str = "{}: ";
+
+/// リクエスト設定中の {{name}} を変数の値で置換（未定義の変数は対話的に入力）
+pub fn resolve_variables(config: &mut Config) -> Result<(), Box<dyn Error>> {
+    check_required_params(config)?;
+
+    let mut names: Vec<String> = Vec::new();
+    for_each_request_field(config, &mut |field| {
+        for name in placeholder_names(field) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    });
+
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let mut values = config.vars.clone().unwrap_or_default();
+    let secret_names = config.secret_vars.clone().unwrap_or_default();
+    let interactive = !config.no_input && stdin().is_terminal();
+    let mut missing: Vec<String> = Vec::new();
+
+    for name in names {
+        if values.contains_key(&name) {
+            continue;
+        }
+
+        if interactive {
+            let value = prompt_variable(&name, secret_names.contains(&name))?;
+            values.insert(name, value);
+        } else {
+            missing.push(name);
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(ERROR_MISSING_VARIABLES
+            .replace("{}", &missing.join(", "))
+            .into());
+    }
+
+    for_each_request_field(config, &mut |field| {
+        *field = substitute_variables(field, &values);
+    });
+
+    Ok(())
+}
+
+/// プリセットで宣言されたパラメータがすべて指定されているか検証
+fn check_required_params(config: &Config) -> Result<(), Box<dyn Error>> {
+    let Some(params) = &config.params else {
+        return Ok(());
+    };
+
+    let missing: Vec<&str> = params
+        .iter()
+        .filter(|name| {
+            !config
+                .vars
+                .as_ref()
+                .is_some_and(|vars| vars.contains_key(name.as_str()))
+        })
+        .map(String::as_str)
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ERROR_MISSING_PARAMS
+            .replace("{}", &missing.join(", "))
+            .into())
+    }
+}
+
+/// 変数の値を入力（シークレット変数は入力内容を表示しない）
+fn prompt_variable(name: &str, secret: bool) -> Result<String, Box<dyn Error>> {
+    let prompt = VARIABLE_PROMPT_MSG.replace("{}", name);
+
+    if secret {
+        return Ok(rpassword::prompt_password(prompt)?);
+    }
+
+    eprint!("{}", prompt);
+    stderr().flush()?;
+
+    let mut line = String::new();
+    stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// 変数を置換できるリクエスト設定の各文字列に処理を適用
+fn for_each_request_field(config: &mut Config, apply: &mut impl FnMut(&mut String)) {
+    apply(&mut config.url);
+
+    for field in [
+        &mut config.json,
+        &mut config.yaml,
+        &mut config.ndjson,
+        &mut config.form_data,
+        &mut config.api_key,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        apply(field);
+    }
+
+    for fields in [&mut config.headers, &mut config.cookies, &mut config.form]
+        .into_iter()
+        .flatten()
+    {
+        fields.iter_mut().for_each(&mut *apply);
+    }
+
+    if let Some(basic_auth) = &mut config.basic_auth {
+        apply(&mut basic_auth.user);
+        apply(&mut basic_auth.pass);
+    }
+
+    if let Some(query) = &mut config.query {
+        query.values_mut().for_each(&mut *apply);
+    }
+}
+
+/// 文字列中のプレースホルダーの変数名を取得
+fn placeholder_names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(VARIABLE_OPEN) {
+        let after_open = &rest[start + VARIABLE_OPEN.len()..];
+        let Some(end) = after_open.find(VARIABLE_CLOSE) else {
+            break;
+        };
+
+        let name = after_open[..end].trim();
+        if is_variable_name(name) {
+            names.push(name.to_string());
+        }
+        rest = &after_open[end + VARIABLE_CLOSE.len()..];
+    }
+
+    names
+}
+
+/// プレースホルダーを変数の値で置換
+fn substitute_variables(text: &str, values: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(VARIABLE_OPEN) {
+        let after_open = &rest[start + VARIABLE_OPEN.len()..];
+        let Some(end) = after_open.find(VARIABLE_CLOSE) else {
+            break;
+        };
+
+        result.push_str(&rest[..start]);
+        let name = after_open[..end].trim();
+        match values.get(name) {
+            Some(value) if is_variable_name(name) => result.push_str(value),
+            _ => result
+                .push_str(&rest[start..start + VARIABLE_OPEN.len() + end + VARIABLE_CLOSE.len()]),
+        }
+        rest = &after_open[end + VARIABLE_CLOSE.len()..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// 変数名として有効か（英字・アンダースコアで始まり、英数字・アンダースコア・ハイフンのみ）
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
@@ -0,0 +1,74 @@
+use crate::client::parse_duration;
+use reqwest::blocking::Client;
+use std::error::Error;
+use std::thread;
+use std::time::Instant;
+
+// 既定の待機時間と確認間隔
+pub const DEFAULT_WAIT_TIMEOUT: &str = "60s";
+pub const DEFAULT_WAIT_INTERVAL: &str = "1s";
+
+// エラーメッセージ
+const ERROR_WAIT_TIMED_OUT: &str = "{1} did not become ready within {2} ({3} attempts, last: {4})";
+
+// 表示メッセージ
+const READY_MSG: &str = "Ready: {1} returned HTTP {2} after {3} ({4} attempts)";
+const NOT_READY_STATUS_MSG: &str = "HTTP {}";
+
+/// エンドポイントが2xxを返すまで待機（docker-compose・CIでの起動待ち）
+///
+/// 接続エラーや2xx以外のレスポンスは表示せずに再試行し、
+/// 最初に2xxを返した時点で起動にかかった時間を表示して終了する。
+pub fn wait_for_endpoint(
+    client: &Client,
+    url: &str,
+    timeout: &str,
+    interval: &str,
+    silent: bool,
+) -> Result<(), Box<dyn Error>> {
+    let timeout = parse_duration(timeout)?;
+    let interval = parse_duration(interval)?;
+
+    let started = Instant::now();
+    let mut attempts = 0;
+
+    loop {
+        let attempt_start = Instant::now();
+        let remaining = timeout.saturating_sub(started.elapsed());
+        attempts += 1;
+
+        // 1回の試行も残り時間を超えないようにする
+        let last_failure = match client.get(url).timeout(remaining).send() {
+            Ok(response) if response.status().is_success() => {
+                if !silent {
+                    println!(
+                        "{}",
+                        READY_MSG
+                            .replace("{1}", url)
+                            .replace("{2}", &response.status().as_u16().to_string())
+                            .replace("{3}", &format!("{:.1?}", started.elapsed()))
+                            .replace("{4}", &attempts.to_string())
+                    );
+                }
+                return Ok(());
+            }
+            Ok(response) => {
+                NOT_READY_STATUS_MSG.replace("{}", &response.status().as_u16().to_string())
+            }
+            Err(e) => e.to_string(),
+        };
+
+        // 次の試行を待機時間内に始められない場合は打ち切る
+        let delay = interval.saturating_sub(attempt_start.elapsed());
+        if started.elapsed() + delay >= timeout {
+            return Err(ERROR_WAIT_TIMED_OUT
+                .replace("{1}", url)
+                .replace("{2}", &humantime::format_duration(timeout).to_string())
+                .replace("{3}", &attempts.to_string())
+                .replace("{4}", &last_failure)
+                .into());
+        }
+
+        thread::sleep(delay);
+    }
+}
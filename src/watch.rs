@@ -0,0 +1,87 @@
+use crate::golden::{display_diff, normalize_body};
+use ring::digest::{digest, SHA256};
+use std::time::{Duration, SystemTime};
+
+// 差分表示のラベル
+const DIFF_PREVIOUS_LABEL: &str = "previous";
+const DIFF_CURRENT_LABEL: &str = "current ({})";
+
+// 区間ごとの集計（--report-interval）
+const WATCH_INTERVAL_MSG: &str = "[{1}] {2} requests, {3} failed, avg {4}";
+
+/// --watch で前回のレスポンスからの変更を検出（--changes-only）
+///
+/// 正規化したボディのハッシュを比較し、差分を表示する場合のみ前回のボディを保持する。
+pub struct ChangeTracker {
+    show_diff: bool,
+    last_digest: Option<Vec<u8>>,
+    last_body: Option<String>,
+}
+
+impl ChangeTracker {
+    pub fn new(show_diff: bool) -> Self {
+        Self {
+            show_diff,
+            last_digest: None,
+            last_body: None,
+        }
+    }
+
+    /// ボディを記録し、前回と異なるか（初回は常に変更あり）を返す（--show-diff では差分を表示）
+    pub fn record(&mut self, body: &str) -> bool {
+        let normalized = normalize_body(body);
+        let body_digest = digest(&SHA256, normalized.as_bytes()).as_ref().to_vec();
+        if self.last_digest.as_ref() == Some(&body_digest) {
+            return false;
+        }
+
+        if self.show_diff {
+            if let Some(last_body) = &self.last_body {
+                let current_label = DIFF_CURRENT_LABEL.replace(
+                    "{}",
+                    &humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+                );
+                display_diff(DIFF_PREVIOUS_LABEL, &current_label, last_body, &normalized);
+            }
+            self.last_body = Some(normalized);
+        }
+        self.last_digest = Some(body_digest);
+        true
+    }
+}
+
+/// --watch の --report-interval ごとの集計（件数・失敗数・平均応答時間）
+#[derive(Default)]
+pub struct WatchStats {
+    requests: u64,
+    failed: u64,
+    total_time: Duration,
+}
+
+impl WatchStats {
+    /// 1回のリクエストの結果を記録
+    pub fn record(&mut self, elapsed: Duration, failed: bool) {
+        self.requests += 1;
+        self.failed += u64::from(failed);
+        self.total_time += elapsed;
+    }
+
+    /// 区間の集計を1行で表示してリセット（区間内にリクエストがない場合は表示しない）
+    pub fn report(&mut self, elapsed: Duration) {
+        if self.requests == 0 {
+            return;
+        }
+
+        let elapsed = Duration::from_secs(elapsed.as_secs_f64().round() as u64);
+        let average = self.total_time / self.requests as u32;
+        eprintln!(
+            "{}",
+            WATCH_INTERVAL_MSG
+                .replace("{1}", &humantime::format_duration(elapsed).to_string())
+                .replace("{2}", &self.requests.to_string())
+                .replace("{3}", &self.failed.to_string())
+                .replace("{4}", &format!("{:.2?}", average))
+        );
+        *self = Self::default();
+    }
+}